@@ -1,3 +1,4 @@
+import "../src/threadpool";
 import serverless from "serverless-http";
 import { app } from "../src/app";
 
@@ -0,0 +1,69 @@
+import express, { type Request, type Response, type NextFunction } from "express";
+import healthRoutes from "./routes/health";
+import adminRoutes from "./routes/admin";
+import adminConfigRoutes from "./routes/adminConfig";
+import debugRoutes, { DEBUG_ENDPOINTS_ENABLED } from "./routes/debug";
+import { parseJson } from "./middleware/json";
+import { requestLogger } from "./middleware/logger";
+import { stripResponseHeaders } from "./middleware/responseHeaders";
+import { responseTime } from "./middleware/responseTime";
+import { requestId } from "./middleware/requestId";
+import { resolveTenant } from "./middleware/tenant";
+import { errorHandler, notFoundHandler, sendError } from "./utils/errors";
+import { matchesAnySecret, readSecretList } from "./utils/secrets";
+
+// With ADMIN_PORT set, health, admin, and debug routes are served only by a
+// second listener on that port (see server.ts) and the public app answers
+// them with 404.
+export const ADMIN_PORT = Number(process.env.ADMIN_PORT) || null;
+export const ADMIN_TOKENS = readSecretList("ADMIN_TOKEN");
+const OPERATIONAL_PATH_PREFIXES = ["/admin", "/healthz", "/db/healthz", "/debug"];
+
+export function isOperationalPath(path: string) {
+  return OPERATIONAL_PATH_PREFIXES.some((prefix) => path === prefix || path.startsWith(`${prefix}/`));
+}
+
+export function refuseOperationalPaths(req: Request, res: Response, next: NextFunction) {
+  if (isOperationalPath(req.path)) {
+    notFoundHandler(req, res);
+    return;
+  }
+  next();
+}
+
+// Listener-level gate in front of the routes' own auth: X-Admin-Token rather
+// than Authorization, which the admin routes still need for the caller's JWT.
+function requireAdminToken(req: Request, res: Response, next: NextFunction) {
+  if (ADMIN_TOKENS.length === 0) {
+    next();
+    return;
+  }
+  const presented = req.get("x-admin-token");
+  if (!presented || !matchesAnySecret(presented, ADMIN_TOKENS)) {
+    sendError(res, 401, "unauthorized", "Unauthorized");
+    return;
+  }
+  next();
+}
+
+// Own middleware stack: no CORS, URL limits, or debug capture, since only
+// operators reach this port.
+export const adminApp = express();
+
+adminApp.use(stripResponseHeaders);
+adminApp.use(responseTime);
+adminApp.use(requestId);
+adminApp.use(requireAdminToken);
+adminApp.use(resolveTenant);
+adminApp.use(parseJson);
+adminApp.use(requestLogger);
+
+adminApp.use(healthRoutes);
+adminApp.use(adminRoutes);
+adminApp.use(adminConfigRoutes);
+if (DEBUG_ENDPOINTS_ENABLED) {
+  adminApp.use(debugRoutes);
+}
+
+adminApp.use(notFoundHandler);
+adminApp.use(errorHandler);
@@ -1,13 +1,60 @@
 import express from "express";
 import healthRoutes from "./routes/health";
 import authRoutes from "./routes/auth";
+import sessionRoutes from "./routes/sessions";
+import adminRoutes from "./routes/admin";
+import debugRoutes, { DEBUG_ENDPOINTS_ENABLED } from "./routes/debug";
+import scimRoutes, { SCIM_TOKENS } from "./routes/scim";
+import { ADMIN_PORT, refuseOperationalPaths } from "./adminApp";
+import { DEBUG_CAPTURE_ENABLED } from "./debugCapture";
+import { cors, CORS_ENABLED } from "./middleware/cors";
+import { debugCapture } from "./middleware/debugCapture";
+import { parseAuthJson, parseJson, parseScimJson } from "./middleware/json";
+import { limitUrlLength } from "./middleware/limits";
 import { requestLogger } from "./middleware/logger";
+import { stripResponseHeaders } from "./middleware/responseHeaders";
+import { responseTime } from "./middleware/responseTime";
+import { requestId } from "./middleware/requestId";
+import { resolveTenant } from "./middleware/tenant";
+import { errorHandler, notFoundHandler } from "./utils/errors";
+import { cidrContains, parseCidrList } from "./utils/ip";
+import { enforceJwtSecretPolicy } from "./utils/jwt";
+
+enforceJwtSecretPolicy();
 
 export const app = express();
 
-app.set("trust proxy", true);
-app.use(express.json());
+// With TRUSTED_PROXIES set, X-Forwarded-For is only followed through hops whose
+// address is in the list; the first untrusted hop becomes req.ip. Unset keeps
+// the historical behavior of trusting every hop, which lets clients choose
+// their own IP unless a proxy in front overwrites the header.
+export const TRUSTED_PROXIES = process.env.TRUSTED_PROXIES?.trim()
+  ? parseCidrList(process.env.TRUSTED_PROXIES, "app")
+  : null;
+app.set(
+  "trust proxy",
+  TRUSTED_PROXIES ? (address: string) => TRUSTED_PROXIES.some((cidr) => cidrContains(cidr, address)) : true,
+);
+app.use(stripResponseHeaders);
+app.use(responseTime);
+app.use(requestId);
+if (CORS_ENABLED) {
+  app.use(cors);
+}
+app.use(limitUrlLength);
+app.use(resolveTenant);
+// The first parser to run wins, so auth routes get the stricter size cap.
+app.use("/auth", parseAuthJson);
+app.use("/scim", parseScimJson);
+app.use(parseJson);
 app.use(requestLogger);
+if (DEBUG_CAPTURE_ENABLED) {
+  console.log("[app] DEBUG_CAPTURE enabled, recording matching requests");
+  app.use(debugCapture);
+}
+if (ADMIN_PORT !== null) {
+  app.use(refuseOperationalPaths);
+}
 
 app.get('/', (_req, res) => {
   res.send('Hello Express!');
@@ -23,5 +70,20 @@ app.get('/api/posts/:postId/comments/:commentId', (_req, res) => {
 
 app.use(healthRoutes);
 app.use(authRoutes);
+app.use(sessionRoutes);
+app.use(adminRoutes);
+if (SCIM_TOKENS.length > 0) {
+  app.use(scimRoutes);
+}
+
+// Debug routes are not registered at all unless explicitly enabled, so they
+// cannot be reached in production even by an admin.
+if (DEBUG_ENDPOINTS_ENABLED) {
+  console.log("[app] DEBUG_ENDPOINTS enabled, mounting /debug routes");
+  app.use(debugRoutes);
+}
+
+app.use(notFoundHandler);
+app.use(errorHandler);
 
 export default app;
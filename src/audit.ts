@@ -0,0 +1,30 @@
+import { getDatabaseName, getMongoClient } from "./db";
+import { nowDate } from "./utils/clock";
+
+export type AuditEvent = {
+  type: string;
+  tenantId?: string;
+  // Subject the event is about, and who caused it when that differs (admins).
+  userId?: string;
+  actorId?: string;
+  ip?: string;
+  details?: Record<string, unknown>;
+  createdAt: Date;
+};
+
+export async function getAuditCollection() {
+  const client = await getMongoClient();
+  return client.db(getDatabaseName()).collection<AuditEvent>("audit_events");
+}
+
+// Best effort: a failure to write the audit trail is logged but never fails
+// the request that triggered it.
+export async function recordAuditEvent(event: Omit<AuditEvent, "createdAt">) {
+  try {
+    const events = await getAuditCollection();
+    await events.insertOne({ ...event, createdAt: nowDate() });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    console.error("[audit] Failed to record event:", { type: event.type, message });
+  }
+}
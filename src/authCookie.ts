@@ -0,0 +1,53 @@
+import type { Request, Response } from "express";
+import { parseBooleanEnv } from "./utils/env";
+
+// Cookie-only mode keeps tokens away from browser JavaScript: login, register,
+// and session extension set an HttpOnly cookie instead of returning the token,
+// and requireAuth accepts that cookie when no Authorization header is sent.
+export const AUTH_COOKIE_ONLY = parseBooleanEnv(process.env.AUTH_COOKIE_ONLY) ?? false;
+export const AUTH_COOKIE_NAME = process.env.AUTH_COOKIE_NAME?.trim() || "auth_token";
+// Only worth disabling for local development over plain HTTP.
+export const AUTH_COOKIE_SECURE = parseBooleanEnv(process.env.AUTH_COOKIE_SECURE) ?? true;
+
+// Returns the body fields carrying the token: the token itself normally, or
+// nothing once it has been set as a cookie.
+export function deliverToken(res: Response, token: string, expiresAt: Date): { token?: string } {
+  if (!AUTH_COOKIE_ONLY) {
+    return { token };
+  }
+  res.cookie(AUTH_COOKIE_NAME, token, {
+    httpOnly: true,
+    secure: AUTH_COOKIE_SECURE,
+    sameSite: "strict",
+    path: "/",
+    expires: expiresAt,
+  });
+  return {};
+}
+
+export function readAuthCookie(req: Request): string | undefined {
+  if (!AUTH_COOKIE_ONLY) {
+    return undefined;
+  }
+  for (const part of (req.headers.cookie ?? "").split(";")) {
+    const separator = part.indexOf("=");
+    if (separator !== -1 && part.slice(0, separator).trim() === AUTH_COOKIE_NAME) {
+      const value = part.slice(separator + 1).trim();
+      try {
+        return decodeURIComponent(value) || undefined;
+      } catch {
+        return undefined;
+      }
+    }
+  }
+  return undefined;
+}
+
+// Bearer header first, then the auth cookie in cookie-only mode.
+export function readRequestToken(req: Request): string | undefined {
+  const authHeader = req.headers.authorization;
+  if (authHeader?.startsWith("Bearer ")) {
+    return authHeader.slice("Bearer ".length);
+  }
+  return authHeader ? undefined : readAuthCookie(req);
+}
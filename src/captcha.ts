@@ -0,0 +1,110 @@
+import { TtlCache } from "./utils/cache";
+import { parseBooleanEnv, parseNumberEnv } from "./utils/env";
+import { outboundFetch, type OutboundCallOptions } from "./utils/http";
+
+export type CaptchaOutcome = "passed" | "failed" | "unavailable";
+
+// Calling request's context for the provider call (see utils/http.ts).
+export type CaptchaCallContext = Omit<OutboundCallOptions, "timeoutMs">;
+
+export interface CaptchaVerifier {
+  readonly enabled: boolean;
+  verify(token: string, ip: string | undefined, context?: CaptchaCallContext): Promise<CaptchaOutcome>;
+}
+
+// Development default: no widget is required and any token passes.
+export class NoopCaptchaVerifier implements CaptchaVerifier {
+  readonly enabled = false;
+
+  async verify(): Promise<CaptchaOutcome> {
+    return "passed";
+  }
+}
+
+// hCaptcha and Cloudflare Turnstile share the same siteverify contract: a form
+// POST of secret/response/remoteip answered with { success: boolean }.
+export class SiteVerifyCaptchaVerifier implements CaptchaVerifier {
+  readonly enabled = true;
+
+  constructor(
+    private readonly verifyUrl: string,
+    private readonly secret: string,
+    private readonly timeoutMs: number,
+  ) {}
+
+  async verify(token: string, ip: string | undefined, context: CaptchaCallContext = {}): Promise<CaptchaOutcome> {
+    const form = new URLSearchParams({ secret: this.secret, response: token });
+    if (ip) {
+      form.set("remoteip", ip);
+    }
+    try {
+      const response = await outboundFetch(
+        this.verifyUrl,
+        { method: "POST", body: form },
+        { ...context, timeoutMs: this.timeoutMs },
+      );
+      if (!response.ok) {
+        console.error("[captcha] Provider returned status", response.status);
+        return "unavailable";
+      }
+      const result = (await response.json()) as { success?: unknown };
+      return result.success === true ? "passed" : "failed";
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      console.error("[captcha] Provider request failed:", message);
+      return "unavailable";
+    }
+  }
+}
+
+const PROVIDER_VERIFY_URLS: Record<string, string> = {
+  hcaptcha: "https://api.hcaptcha.com/siteverify",
+  turnstile: "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+};
+
+export const CAPTCHA_PROVIDER = process.env.CAPTCHA_PROVIDER?.trim().toLowerCase() || "none";
+export const CAPTCHA_VERIFY_URL =
+  process.env.CAPTCHA_VERIFY_URL?.trim() || PROVIDER_VERIFY_URLS[CAPTCHA_PROVIDER] || null;
+export const CAPTCHA_SECRET = process.env.CAPTCHA_SECRET || undefined;
+export const CAPTCHA_TIMEOUT_MS = parseNumberEnv("CAPTCHA_TIMEOUT_MS", 3000);
+
+function createCaptchaVerifier(): CaptchaVerifier {
+  if (CAPTCHA_PROVIDER === "none") {
+    return new NoopCaptchaVerifier();
+  }
+  if (!CAPTCHA_VERIFY_URL || !CAPTCHA_SECRET) {
+    throw new Error(
+      `CAPTCHA_PROVIDER=${CAPTCHA_PROVIDER} needs CAPTCHA_SECRET and a known provider or CAPTCHA_VERIFY_URL`,
+    );
+  }
+  return new SiteVerifyCaptchaVerifier(CAPTCHA_VERIFY_URL, CAPTCHA_SECRET, CAPTCHA_TIMEOUT_MS);
+}
+
+export const captchaVerifier = createCaptchaVerifier();
+
+// Whether a provider outage lets requests through (open) or rejects them (closed).
+export const CAPTCHA_FAIL_OPEN = parseBooleanEnv(process.env.CAPTCHA_FAIL_OPEN) ?? false;
+
+export const CAPTCHA_LOGIN_FAILURE_THRESHOLD = parseNumberEnv("CAPTCHA_LOGIN_FAILURE_THRESHOLD", 5);
+export const CAPTCHA_LOGIN_FAILURE_WINDOW_MS = parseNumberEnv("CAPTCHA_LOGIN_FAILURE_WINDOW_MS", 15 * 60 * 1000);
+
+// Failed logins per client IP. Logins only need a CAPTCHA once an IP reaches
+// the threshold; the count resets after a quiet window or a successful login.
+const loginFailures = new TtlCache<number>(10_000);
+
+export function recordLoginFailure(ip: string | undefined) {
+  if (!ip) {
+    return;
+  }
+  loginFailures.set(ip, (loginFailures.get(ip) ?? 0) + 1, CAPTCHA_LOGIN_FAILURE_WINDOW_MS);
+}
+
+export function clearLoginFailures(ip: string | undefined) {
+  if (ip) {
+    loginFailures.delete(ip);
+  }
+}
+
+export function loginNeedsCaptcha(ip: string | undefined) {
+  return ip !== undefined && (loginFailures.get(ip) ?? 0) >= CAPTCHA_LOGIN_FAILURE_THRESHOLD;
+}
@@ -0,0 +1,302 @@
+import crypto from "crypto";
+import { TRUSTED_PROXIES } from "./app";
+import { ADMIN_PORT, ADMIN_TOKENS } from "./adminApp";
+import { AUTH_COOKIE_NAME, AUTH_COOKIE_ONLY, AUTH_COOKIE_SECURE } from "./authCookie";
+import {
+  CAPTCHA_FAIL_OPEN,
+  CAPTCHA_LOGIN_FAILURE_THRESHOLD,
+  CAPTCHA_LOGIN_FAILURE_WINDOW_MS,
+  CAPTCHA_PROVIDER,
+  CAPTCHA_SECRET,
+  CAPTCHA_TIMEOUT_MS,
+  CAPTCHA_VERIFY_URL,
+} from "./captcha";
+import { getDatabaseName, getMongoClientOptions } from "./db";
+import {
+  DEBUG_CAPTURE_BODY_MAX_BYTES,
+  DEBUG_CAPTURE_ENABLED,
+  DEBUG_CAPTURE_MAX_ENTRIES,
+  DEBUG_CAPTURE_PATH_PREFIX,
+  DEBUG_CAPTURE_TTL_SECONDS,
+  DEBUG_CAPTURE_USER_ID,
+} from "./debugCapture";
+import { EMAIL_FROM, EMAIL_HTTP_TIMEOUT_MS, EMAIL_HTTP_TOKEN, EMAIL_HTTP_URL } from "./email";
+import { GEOIP_DB_PATH } from "./geoip";
+import { INVITE_TTL_SECONDS } from "./invites";
+import {
+  LOGIN_LOCKOUT_DURATION_SECONDS,
+  LOGIN_LOCKOUT_ENABLED,
+  LOGIN_LOCKOUT_THRESHOLD,
+  STEALTH_LOCKOUT,
+} from "./lockout";
+import { NEW_DEVICE_NOTIFICATIONS } from "./loginNotifications";
+import { MAINTENANCE_INTERVAL_MS } from "./maintenance";
+import { IMPERSONATION_ALLOW_WRITES, IP_BINDING_PREFIX_V4, IP_BINDING_PREFIX_V6 } from "./middleware/auth";
+import { INTERNAL_JWT_SECRETS } from "./middleware/internalAuth";
+import { DEFAULT_ORIGINS, ROUTE_POLICIES } from "./middleware/cors";
+import { AUTH_BODY_MAX_BYTES, DENY_UNKNOWN_FIELDS, JSON_MAX_DEPTH } from "./middleware/json";
+import { MAX_URL_LEN } from "./middleware/limits";
+import { LOGIN_MAX_CONCURRENT_PER_EMAIL } from "./middleware/loginConcurrency";
+import { RATE_LIMIT_SETTINGS } from "./middleware/rateLimit";
+import { RESPONSE_HEADER_DENYLIST } from "./middleware/responseHeaders";
+import { SLOW_REQUEST_MS } from "./middleware/responseTime";
+import { DEFAULT_TENANT_ID, KNOWN_TENANTS, TENANT_BASE_DOMAIN } from "./middleware/tenant";
+import { PASSWORD_RESET_TTL_SECONDS, PASSWORD_RESET_URL, PASSWORD_RESETS_PER_EMAIL_PER_HOUR } from "./passwordResets";
+import {
+  BULK_USERS_MAX,
+  IMPERSONATION_EXPIRES_IN,
+  IMPORT_ROW_MAX_BYTES,
+  IMPORT_USERS_MAX,
+  PASSWORD_HASH_CONCURRENCY,
+} from "./routes/admin";
+import { EMAIL_CHECK_ENABLED, REGISTRATION_ENABLED, TOKEN_EXCHANGE_EXPIRES_IN } from "./routes/auth";
+import { DEBUG_ENDPOINTS_ENABLED } from "./routes/debug";
+import { SCIM_TOKENS } from "./routes/scim";
+import { HEARTBEAT_INTERVAL_MS } from "./routes/sessions";
+import {
+  REMEMBER_ME_TTL_SECONDS,
+  REVOKED_TOKEN_CACHE_MAX_ENTRIES,
+  SESSION_DEGRADATION_POLICY,
+  SESSION_EXTEND_WINDOW_SECONDS,
+  SESSION_MAX_LIFETIME_SECONDS,
+  SESSION_SLIDING,
+  TOKEN_CACHE_MAX_ENTRIES,
+  TOKEN_CACHE_TTL_MS,
+  TOKEN_IP_BINDING_DEFAULT,
+} from "./sessions";
+import { PORT, STARTUP_DEADLINE_MS, STARTUP_EXIT_ON_FAILURE, STARTUP_MODE } from "./startup";
+import { THREADPOOL_SIZE } from "./threadpool";
+import { TOS_CURRENT_VERSION, TOS_STRICT, TOS_URL } from "./tos";
+import { ACCOUNT_DELETION_GRACE_DAYS, PASSWORD_HISTORY_SIZE, TOKEN_LIFETIME_BY_ROLE } from "./users";
+import { OUTBOUND_HTTP_REDIRECT } from "./utils/http";
+import { ERROR_CATALOG_PATH } from "./utils/i18n";
+import { formatCidr } from "./utils/ip";
+import { APP_ENV, JWT_EXPIRES_IN, JWT_HEADER_TYP, JWT_SECRETS } from "./utils/jwt";
+import { PASSWORD_MIN_LENGTH } from "./utils/passwordPolicy";
+import { Secret } from "./utils/secrets";
+
+type SettingDescriptor = {
+  name: string;
+  // The value as parsed by the module that reads the setting, defaults
+  // applied. Secrets are wrapped in Secret. Read when the report is built, so
+  // module load order does not matter.
+  value: () => unknown;
+  // Connection strings are shown with their credentials masked.
+  uri?: boolean;
+};
+
+const mongoOptions = () => getMongoClientOptions(process.env.MONGODB_URI ?? "");
+const rateLimit = (prefix: string, field: "windowMs" | "limit") => () => RATE_LIMIT_SETTINGS.get(prefix)?.[field];
+const secretFile = (name: string) => () => process.env[`${name}_FILE`]?.trim() || null;
+
+// Every environment variable the service reads. Keep this in sync when adding
+// settings so the startup self-check reports them.
+const SETTINGS: SettingDescriptor[] = [
+  { name: "PORT", value: () => PORT },
+  { name: "MONGODB_URI", value: () => process.env.MONGODB_URI, uri: true },
+  { name: "MONGODB_DB", value: () => getDatabaseName() },
+  { name: "MONGODB_OP_TIMEOUT_MS", value: () => mongoOptions().timeoutMS },
+  { name: "MONGODB_MAX_POOL_SIZE", value: () => mongoOptions().maxPoolSize },
+  { name: "MONGODB_MIN_POOL_SIZE", value: () => mongoOptions().minPoolSize },
+  { name: "MONGODB_MAX_IDLE_TIME_MS", value: () => mongoOptions().maxIdleTimeMS },
+  { name: "MONGODB_CONNECT_TIMEOUT_MS", value: () => mongoOptions().connectTimeoutMS },
+  { name: "MONGODB_TLS", value: () => mongoOptions().tls },
+  { name: "MONGODB_TLS_CA_FILE", value: () => mongoOptions().tlsCAFile },
+  { name: "MONGODB_TLS_ALLOW_INVALID_CERTIFICATES", value: () => mongoOptions().tlsAllowInvalidCertificates },
+  { name: "MONGODB_TLS_ALLOW_INVALID_HOSTNAMES", value: () => mongoOptions().tlsAllowInvalidHostnames },
+  { name: "APP_ENV", value: () => APP_ENV },
+  { name: "JWT_SECRET", value: () => new Secret(JWT_SECRETS) },
+  { name: "JWT_SECRET_FILE", value: secretFile("JWT_SECRET") },
+  { name: "JWT_EXPIRES_IN", value: () => JWT_EXPIRES_IN },
+  { name: "JWT_EXPIRY_BY_ROLE", value: () => TOKEN_LIFETIME_BY_ROLE },
+  { name: "REMEMBER_ME_TTL_SECONDS", value: () => REMEMBER_ME_TTL_SECONDS },
+  { name: "LOGIN_LOCKOUT_ENABLED", value: () => LOGIN_LOCKOUT_ENABLED },
+  { name: "LOGIN_LOCKOUT_THRESHOLD", value: () => LOGIN_LOCKOUT_THRESHOLD },
+  { name: "LOGIN_LOCKOUT_DURATION_SECONDS", value: () => LOGIN_LOCKOUT_DURATION_SECONDS },
+  { name: "STEALTH_LOCKOUT", value: () => STEALTH_LOCKOUT },
+  { name: "HEARTBEAT_INTERVAL_MS", value: () => HEARTBEAT_INTERVAL_MS },
+  { name: "AUTH_COOKIE_ONLY", value: () => AUTH_COOKIE_ONLY },
+  { name: "AUTH_COOKIE_NAME", value: () => AUTH_COOKIE_NAME },
+  { name: "AUTH_COOKIE_SECURE", value: () => AUTH_COOKIE_SECURE },
+  { name: "JWT_HEADER_TYP", value: () => JWT_HEADER_TYP },
+  { name: "TOKEN_EXCHANGE_EXPIRES_IN", value: () => TOKEN_EXCHANGE_EXPIRES_IN },
+  { name: "TOKEN_CACHE_TTL_MS", value: () => TOKEN_CACHE_TTL_MS },
+  { name: "TOKEN_CACHE_MAX_ENTRIES", value: () => TOKEN_CACHE_MAX_ENTRIES },
+  { name: "REVOKED_TOKEN_CACHE_MAX_ENTRIES", value: () => REVOKED_TOKEN_CACHE_MAX_ENTRIES },
+  { name: "SESSION_EXTEND_WINDOW_SECONDS", value: () => SESSION_EXTEND_WINDOW_SECONDS },
+  { name: "SESSION_SLIDING", value: () => SESSION_SLIDING },
+  { name: "SESSION_MAX_LIFETIME_SECONDS", value: () => SESSION_MAX_LIFETIME_SECONDS },
+  { name: "SESSION_DEGRADATION_POLICY", value: () => SESSION_DEGRADATION_POLICY },
+  { name: "TOKEN_IP_BINDING", value: () => TOKEN_IP_BINDING_DEFAULT },
+  { name: "TOKEN_IP_BINDING_PREFIX_V4", value: () => IP_BINDING_PREFIX_V4 },
+  { name: "TOKEN_IP_BINDING_PREFIX_V6", value: () => IP_BINDING_PREFIX_V6 },
+  { name: "REGISTRATION_ENABLED", value: () => REGISTRATION_ENABLED },
+  { name: "NEW_DEVICE_NOTIFICATIONS", value: () => NEW_DEVICE_NOTIFICATIONS },
+  { name: "GEOIP_DB_PATH", value: () => GEOIP_DB_PATH },
+  { name: "INVITE_TTL_SECONDS", value: () => INVITE_TTL_SECONDS },
+  { name: "TOS_CURRENT_VERSION", value: () => TOS_CURRENT_VERSION },
+  { name: "TOS_URL", value: () => TOS_URL },
+  { name: "TOS_STRICT", value: () => TOS_STRICT },
+  { name: "EMAIL_HTTP_URL", value: () => EMAIL_HTTP_URL },
+  { name: "EMAIL_HTTP_TOKEN", value: () => new Secret(EMAIL_HTTP_TOKEN) },
+  { name: "EMAIL_HTTP_TIMEOUT_MS", value: () => EMAIL_HTTP_TIMEOUT_MS },
+  { name: "EMAIL_FROM", value: () => EMAIL_FROM },
+  { name: "CAPTCHA_PROVIDER", value: () => CAPTCHA_PROVIDER },
+  { name: "CAPTCHA_SECRET", value: () => new Secret(CAPTCHA_SECRET) },
+  { name: "CAPTCHA_VERIFY_URL", value: () => CAPTCHA_VERIFY_URL },
+  { name: "CAPTCHA_TIMEOUT_MS", value: () => CAPTCHA_TIMEOUT_MS },
+  { name: "CAPTCHA_FAIL_OPEN", value: () => CAPTCHA_FAIL_OPEN },
+  { name: "CAPTCHA_LOGIN_FAILURE_THRESHOLD", value: () => CAPTCHA_LOGIN_FAILURE_THRESHOLD },
+  { name: "CAPTCHA_LOGIN_FAILURE_WINDOW_MS", value: () => CAPTCHA_LOGIN_FAILURE_WINDOW_MS },
+  { name: "AUTH_RATE_LIMIT_WINDOW_MS", value: rateLimit("AUTH", "windowMs") },
+  { name: "AUTH_RATE_LIMIT_MAX", value: rateLimit("AUTH", "limit") },
+  { name: "LOGIN_RATE_LIMIT_WINDOW_MS", value: rateLimit("LOGIN", "windowMs") },
+  { name: "LOGIN_RATE_LIMIT_MAX", value: rateLimit("LOGIN", "limit") },
+  { name: "LOGIN_MAX_CONCURRENT_PER_EMAIL", value: () => LOGIN_MAX_CONCURRENT_PER_EMAIL },
+  { name: "EMAIL_CHECK_ENABLED", value: () => EMAIL_CHECK_ENABLED },
+  { name: "EMAIL_CHECK_RATE_LIMIT_WINDOW_MS", value: rateLimit("EMAIL_CHECK", "windowMs") },
+  { name: "EMAIL_CHECK_RATE_LIMIT_MAX", value: rateLimit("EMAIL_CHECK", "limit") },
+  { name: "PASSWORD_RESET_RATE_LIMIT_WINDOW_MS", value: rateLimit("PASSWORD_RESET", "windowMs") },
+  { name: "PASSWORD_RESET_RATE_LIMIT_MAX", value: rateLimit("PASSWORD_RESET", "limit") },
+  { name: "PASSWORD_RESET_TTL_SECONDS", value: () => PASSWORD_RESET_TTL_SECONDS },
+  { name: "PASSWORD_RESETS_PER_EMAIL_PER_HOUR", value: () => PASSWORD_RESETS_PER_EMAIL_PER_HOUR },
+  { name: "PASSWORD_RESET_URL", value: () => PASSWORD_RESET_URL },
+  { name: "PASSWORD_MIN_LENGTH", value: () => PASSWORD_MIN_LENGTH },
+  { name: "PASSWORD_HISTORY_SIZE", value: () => PASSWORD_HISTORY_SIZE },
+  { name: "PASSWORD_HASH_CONCURRENCY", value: () => PASSWORD_HASH_CONCURRENCY },
+  { name: "UV_THREADPOOL_SIZE", value: () => THREADPOOL_SIZE },
+  { name: "ACCOUNT_DELETION_GRACE_DAYS", value: () => ACCOUNT_DELETION_GRACE_DAYS },
+  { name: "MAINTENANCE_INTERVAL_MS", value: () => MAINTENANCE_INTERVAL_MS },
+  { name: "IMPERSONATION_EXPIRES_IN", value: () => IMPERSONATION_EXPIRES_IN },
+  { name: "IMPERSONATION_ALLOW_WRITES", value: () => IMPERSONATION_ALLOW_WRITES },
+  { name: "ADMIN_BULK_USERS_MAX", value: () => BULK_USERS_MAX },
+  { name: "ADMIN_IMPORT_USERS_MAX", value: () => IMPORT_USERS_MAX },
+  { name: "ADMIN_IMPORT_ROW_MAX_BYTES", value: () => IMPORT_ROW_MAX_BYTES },
+  { name: "DEFAULT_TENANT_ID", value: () => DEFAULT_TENANT_ID },
+  { name: "TENANT_BASE_DOMAIN", value: () => TENANT_BASE_DOMAIN },
+  { name: "TENANTS", value: () => KNOWN_TENANTS },
+  { name: "TRUSTED_PROXIES", value: () => TRUSTED_PROXIES?.map(formatCidr) },
+  { name: "MAX_URL_LEN", value: () => MAX_URL_LEN },
+  { name: "RESPONSE_HEADER_DENYLIST", value: () => RESPONSE_HEADER_DENYLIST },
+  { name: "SLOW_REQUEST_MS", value: () => SLOW_REQUEST_MS },
+  { name: "OUTBOUND_HTTP_REDIRECT", value: () => OUTBOUND_HTTP_REDIRECT },
+  { name: "CORS_ALLOWED_ORIGINS", value: () => DEFAULT_ORIGINS },
+  {
+    name: "CORS_ROUTE_ORIGINS",
+    value: () =>
+      ROUTE_POLICIES.length > 0
+        ? ROUTE_POLICIES.map(({ prefix, origins }) => `${prefix}=${formatValue(origins)}`).join(";")
+        : null,
+  },
+  { name: "AUTH_BODY_MAX_BYTES", value: () => AUTH_BODY_MAX_BYTES },
+  { name: "JSON_DENY_UNKNOWN_FIELDS", value: () => DENY_UNKNOWN_FIELDS },
+  { name: "JSON_MAX_DEPTH", value: () => JSON_MAX_DEPTH },
+  { name: "ERROR_CATALOG_PATH", value: () => ERROR_CATALOG_PATH },
+  { name: "DEBUG_ENDPOINTS", value: () => DEBUG_ENDPOINTS_ENABLED },
+  { name: "DEBUG_CAPTURE", value: () => DEBUG_CAPTURE_ENABLED },
+  { name: "DEBUG_CAPTURE_PATH_PREFIX", value: () => DEBUG_CAPTURE_PATH_PREFIX },
+  { name: "DEBUG_CAPTURE_USER_ID", value: () => DEBUG_CAPTURE_USER_ID },
+  { name: "DEBUG_CAPTURE_BODY_MAX_BYTES", value: () => DEBUG_CAPTURE_BODY_MAX_BYTES },
+  { name: "DEBUG_CAPTURE_TTL_SECONDS", value: () => DEBUG_CAPTURE_TTL_SECONDS },
+  { name: "DEBUG_CAPTURE_MAX_ENTRIES", value: () => DEBUG_CAPTURE_MAX_ENTRIES },
+  { name: "STARTUP_MODE", value: () => STARTUP_MODE },
+  { name: "ADMIN_PORT", value: () => ADMIN_PORT },
+  { name: "ADMIN_TOKEN", value: () => new Secret(ADMIN_TOKENS) },
+  { name: "ADMIN_TOKEN_FILE", value: secretFile("ADMIN_TOKEN") },
+  { name: "SCIM_TOKEN", value: () => new Secret(SCIM_TOKENS) },
+  { name: "SCIM_TOKEN_FILE", value: secretFile("SCIM_TOKEN") },
+  { name: "INTERNAL_JWT_SECRET", value: () => new Secret(INTERNAL_JWT_SECRETS) },
+  { name: "INTERNAL_JWT_SECRET_FILE", value: secretFile("INTERNAL_JWT_SECRET") },
+  { name: "STARTUP_DEADLINE_MS", value: () => STARTUP_DEADLINE_MS },
+  { name: "STARTUP_EXIT_ON_FAILURE", value: () => STARTUP_EXIT_ON_FAILURE },
+];
+
+export function redactUri(uri: string): string {
+  try {
+    const parsed = new URL(uri);
+    if (parsed.password) {
+      parsed.password = "****";
+    }
+    return parsed.toString();
+  } catch {
+    return "(set, unparseable)";
+  }
+}
+
+// Names that look like credentials are redacted even when their value is not
+// wrapped in Secret, so a new secret setting cannot leak by being left unmarked.
+const SECRET_NAME_PATTERN = /(^|_)(SECRET|TOKEN|PASSWORD|PRIVATE_KEY)$/;
+
+export type SettingSource = "env" | "file" | "default" | "unset";
+
+export type EffectiveSetting = {
+  value: string | null;
+  source: SettingSource;
+  // Env var (or, for file-backed secrets, <NAME>_FILE) the value came from.
+  from?: string;
+};
+
+// Secrets are shown as the first 4 hex chars of their SHA-256: enough to tell
+// whether two deployments use the same value, not enough to recover it.
+function fingerprint(value: string) {
+  return `sha256:${crypto.createHash("sha256").update(value).digest("hex").slice(0, 4)}`;
+}
+
+// Parsed values rendered the way they would be written in the environment;
+// empty lists count as not set.
+function formatValue(value: unknown): string | null {
+  if (value === undefined || value === null) {
+    return null;
+  }
+  if (value instanceof Map) {
+    return value.size > 0 ? Array.from(value, ([key, entry]) => `${key}=${formatValue(entry)}`).join(",") : null;
+  }
+  if (value instanceof Set || Array.isArray(value)) {
+    const items = Array.from(value as Iterable<unknown>, formatValue);
+    return items.length > 0 ? items.join(",") : null;
+  }
+  return typeof value === "object" ? JSON.stringify(value) : String(value);
+}
+
+// Each key of a list is fingerprinted separately, so a rotation shows up as
+// one fingerprint changing.
+function formatSecret(value: unknown): string | null {
+  const keys = value instanceof Secret ? value.keys : [formatValue(value)].filter((key): key is string => key !== null);
+  return keys.length > 0 ? keys.map(fingerprint).join(",") : null;
+}
+
+// Every setting as the service parsed it, with where it came from. All values
+// pass through here, so secret redaction does not depend on each caller
+// remembering it.
+export function describeSettings(): Record<string, EffectiveSetting> {
+  const settings: Record<string, EffectiveSetting> = {};
+  for (const setting of SETTINGS) {
+    const parsed = setting.value();
+    const secret = parsed instanceof Secret || SECRET_NAME_PATTERN.test(setting.name);
+    const value = secret ? formatSecret(parsed) : formatValue(parsed);
+    const raw = process.env[setting.name];
+    if (secret && process.env[`${setting.name}_FILE`]?.trim()) {
+      settings[setting.name] = { value, source: "file", from: `${setting.name}_FILE` };
+    } else if (raw !== undefined && raw !== "") {
+      const shown = setting.uri && value !== null ? redactUri(value) : value;
+      settings[setting.name] = { value: shown, source: "env", from: setting.name };
+    } else {
+      settings[setting.name] = value === null ? { value, source: "unset" } : { value, source: "default" };
+    }
+  }
+  return settings;
+}
+
+export function describeEffectiveConfig(): Record<string, string> {
+  const config: Record<string, string> = {};
+  for (const [name, { value, source }] of Object.entries(describeSettings())) {
+    if (source === "unset") {
+      config[name] = "(not set)";
+    } else if (source === "default" || source === "file") {
+      config[name] = `${value} (${source})`;
+    } else {
+      config[name] = value ?? "";
+    }
+  }
+  return config;
+}
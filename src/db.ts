@@ -1,25 +1,62 @@
-import { MongoClient, type MongoClientOptions } from "mongodb";
+import {
+  MongoClient,
+  type CommandFailedEvent,
+  type CommandSucceededEvent,
+  type MongoClientOptions,
+} from "mongodb";
+import { parseBooleanEnv, parseNumberEnv } from "./utils/env";
 
 let cachedClient: MongoClient | null = null;
 
-function parseBooleanEnv(value: string | undefined): boolean | undefined {
-  if (value === undefined) {
-    return undefined;
+type CommandStats = {
+  count: number;
+  failures: number;
+  totalMs: number;
+  maxMs: number;
+};
+
+const commandStats = new Map<string, CommandStats>();
+
+function recordCommand(event: CommandSucceededEvent | CommandFailedEvent, failed: boolean) {
+  const stats = commandStats.get(event.commandName) ?? { count: 0, failures: 0, totalMs: 0, maxMs: 0 };
+  stats.count += 1;
+  stats.totalMs += event.duration;
+  stats.maxMs = Math.max(stats.maxMs, event.duration);
+  if (failed) {
+    stats.failures += 1;
   }
+  commandStats.set(event.commandName, stats);
+}
 
-  const normalized = value.trim().toLowerCase();
-  if (["1", "true", "yes", "on"].includes(normalized)) {
-    return true;
-  }
+// Pool connection counts from the driver's CMAP events: open connections and
+// how many of them are idle (open but not checked out).
+const poolStats = { open: 0, inUse: 0 };
 
-  if (["0", "false", "no", "off"].includes(normalized)) {
-    return false;
-  }
+export function getDbPoolStats() {
+  return { open: poolStats.open, idle: Math.max(0, poolStats.open - poolStats.inUse), inUse: poolStats.inUse };
+}
 
-  return undefined;
+export function getDbCommandStats() {
+  return Object.fromEntries(
+    Array.from(commandStats, ([command, stats]) => [
+      command,
+      {
+        count: stats.count,
+        failures: stats.failures,
+        avgMs: stats.count > 0 ? Math.round((stats.totalMs / stats.count) * 100) / 100 : 0,
+        maxMs: stats.maxMs,
+      },
+    ]),
+  );
+}
+
+// Read on every call rather than at load, like MONGODB_URI, so tests can point
+// each process at its own database.
+export function getDatabaseName() {
+  return process.env.MONGODB_DB ?? "adventure";
 }
 
-function getMongoClientOptions(): MongoClientOptions {
+export function getMongoClientOptions(uri: string): MongoClientOptions {
   const allowInvalidCertificates = parseBooleanEnv(
     process.env.MONGODB_TLS_ALLOW_INVALID_CERTIFICATES,
   );
@@ -29,8 +66,30 @@ function getMongoClientOptions(): MongoClientOptions {
 
   const options: MongoClientOptions = {
     serverSelectionTimeoutMS: 10_000,
+    monitorCommands: true,
   };
 
+  // Client-side operation timeout. The driver discards the connection an
+  // operation timed out on, so the pool is never left with a half-read reply.
+  if (process.env.MONGODB_OP_TIMEOUT_MS) {
+    options.timeoutMS = parseNumberEnv("MONGODB_OP_TIMEOUT_MS", 5_000);
+  }
+
+  // Pool tuning; unset values keep the driver defaults (100 max, 0 min, no
+  // idle reaping, 30s connect timeout).
+  if (process.env.MONGODB_MAX_POOL_SIZE) {
+    options.maxPoolSize = parseNumberEnv("MONGODB_MAX_POOL_SIZE", 100);
+  }
+  if (process.env.MONGODB_MIN_POOL_SIZE) {
+    options.minPoolSize = parseNumberEnv("MONGODB_MIN_POOL_SIZE", 0);
+  }
+  if (process.env.MONGODB_MAX_IDLE_TIME_MS) {
+    options.maxIdleTimeMS = parseNumberEnv("MONGODB_MAX_IDLE_TIME_MS", 0);
+  }
+  if (process.env.MONGODB_CONNECT_TIMEOUT_MS) {
+    options.connectTimeoutMS = parseNumberEnv("MONGODB_CONNECT_TIMEOUT_MS", 30_000);
+  }
+
   if (allowInvalidCertificates !== undefined) {
     options.tlsAllowInvalidCertificates = allowInvalidCertificates;
   }
@@ -43,6 +102,11 @@ function getMongoClientOptions(): MongoClientOptions {
     options.tlsCAFile = process.env.MONGODB_TLS_CA_FILE;
   }
 
+  // MONGODB_TLS=false is for local, non-TLS servers (e.g. the test database).
+  if (!uri.startsWith("mongodb+srv://")) {
+    options.tls = parseBooleanEnv(process.env.MONGODB_TLS) ?? true;
+  }
+
   return options;
 }
 
@@ -56,7 +120,7 @@ export async function getMongoClient(): Promise<MongoClient> {
     throw new Error("MONGODB_URI environment variable is not set");
   }
 
-  const options = getMongoClientOptions();
+  const options = getMongoClientOptions(uri);
   const isSrvUri = uri.startsWith("mongodb+srv://");
 
   console.log("[db] Connecting to MongoDB...", {
@@ -68,13 +132,35 @@ export async function getMongoClient(): Promise<MongoClient> {
     tlsAllowInvalidCertificates: options.tlsAllowInvalidCertificates,
     tlsAllowInvalidHostnames: options.tlsAllowInvalidHostnames,
     tlsCAFile: options.tlsCAFile ?? "(not set)",
+    timeoutMS: options.timeoutMS ?? "(not set)",
+    maxPoolSize: options.maxPoolSize ?? "(driver default)",
+    minPoolSize: options.minPoolSize ?? "(driver default)",
+    maxIdleTimeMS: options.maxIdleTimeMS ?? "(driver default)",
+    connectTimeoutMS: options.connectTimeoutMS ?? "(driver default)",
   });
 
-  if (!isSrvUri) {
-    options.tls = true;
-  }
-
   const client = new MongoClient(uri, options);
+  client.on("commandSucceeded", (event) => recordCommand(event, false));
+  client.on("connectionCreated", () => {
+    poolStats.open += 1;
+  });
+  client.on("connectionClosed", () => {
+    poolStats.open = Math.max(0, poolStats.open - 1);
+  });
+  client.on("connectionCheckedOut", () => {
+    poolStats.inUse += 1;
+  });
+  client.on("connectionCheckedIn", () => {
+    poolStats.inUse = Math.max(0, poolStats.inUse - 1);
+  });
+  client.on("commandFailed", (event) => {
+    recordCommand(event, true);
+    console.error("[db] Command failed:", {
+      command: event.commandName,
+      durationMs: event.duration,
+      message: event.failure.message,
+    });
+  });
 
   try {
     await client.connect();
@@ -95,10 +181,38 @@ export async function getMongoClient(): Promise<MongoClient> {
   return client;
 }
 
+// Round-trips a ping plus a write/read/delete of a throwaway document, proving
+// the connection is usable and not just reachable.
+export async function probeMongo(): Promise<void> {
+  const client = await getMongoClient();
+  await client.db("admin").command({ ping: 1 });
+  const probes = client.db(getDatabaseName()).collection<{ _id: string; at: Date }>(
+    "startup_probes",
+  );
+  const id = `probe-${process.pid}-${Date.now()}`;
+  await probes.insertOne({ _id: id, at: new Date() });
+  const found = await probes.findOne({ _id: id });
+  await probes.deleteOne({ _id: id });
+  if (!found) {
+    throw new Error("Startup probe document could not be read back");
+  }
+}
+
+export function isOperationTimeoutError(error: unknown): boolean {
+  return error instanceof Error && error.name === "MongoOperationTimeoutError";
+}
+
 export function isTlsError(message: string): boolean {
   return /ssl|tls/i.test(message);
 }
 
+// Lets short-lived processes (tests, scripts) exit once they are done.
+export async function closeMongoClient(): Promise<void> {
+  const client = cachedClient;
+  cachedClient = null;
+  await client?.close();
+}
+
 function resetCachedClient(): void {
   if (cachedClient) {
     cachedClient.close(true).catch(() => {});
@@ -0,0 +1,120 @@
+import crypto from "crypto";
+import { getDatabaseName, getMongoClient } from "./db";
+import { nowDate } from "./utils/clock";
+import { parseBooleanEnv, parseNumberEnv } from "./utils/env";
+
+// Off unless explicitly enabled; captures hold request/response pairs for
+// reproducing customer reports and expire on their own.
+export const DEBUG_CAPTURE_ENABLED = parseBooleanEnv(process.env.DEBUG_CAPTURE) ?? false;
+export const DEBUG_CAPTURE_PATH_PREFIX = process.env.DEBUG_CAPTURE_PATH_PREFIX?.trim() || null;
+export const DEBUG_CAPTURE_USER_ID = process.env.DEBUG_CAPTURE_USER_ID?.trim() || null;
+export const DEBUG_CAPTURE_BODY_MAX_BYTES = parseNumberEnv("DEBUG_CAPTURE_BODY_MAX_BYTES", 16 * 1024);
+export const DEBUG_CAPTURE_TTL_SECONDS = parseNumberEnv("DEBUG_CAPTURE_TTL_SECONDS", 60 * 60);
+export const DEBUG_CAPTURE_MAX_ENTRIES = parseNumberEnv("DEBUG_CAPTURE_MAX_ENTRIES", 500);
+
+// Body fields whose values are never stored, matched by name at any depth.
+const SENSITIVE_FIELD_PATTERN = /password|token|secret|invite|salt/i;
+// Header names that may carry credentials, e.g. X-Refreshed-Token or
+// X-Internal-Authorization as well as the standard ones.
+const SENSITIVE_HEADER_PATTERN = /authorization|token|cookie|secret|key/i;
+
+export type CapturedMessage = {
+  headers: Record<string, string>;
+  body: unknown;
+  bodyTruncated: boolean;
+};
+
+export type DebugCapture = {
+  tenantId: string;
+  userId?: string;
+  requestId?: string;
+  method: string;
+  path: string;
+  status: number;
+  durationMs: number;
+  request: CapturedMessage;
+  response: CapturedMessage;
+  createdAt: Date;
+};
+
+let indexesReady: Promise<unknown> | null = null;
+
+export async function getDebugCapturesCollection() {
+  const client = await getMongoClient();
+  const captures = client.db(getDatabaseName()).collection<DebugCapture>("debug_captures");
+  indexesReady ??= captures
+    .createIndex({ createdAt: 1 }, { expireAfterSeconds: DEBUG_CAPTURE_TTL_SECONDS })
+    .catch((error) => {
+      indexesReady = null;
+      throw error;
+    });
+  await indexesReady;
+  return captures;
+}
+
+// Credentials are replaced by a short hash prefix so captures of the same
+// token can still be correlated without storing it.
+function redactHeaderValue(value: string) {
+  return `sha256:${crypto.createHash("sha256").update(value).digest("hex").slice(0, 12)}`;
+}
+
+export function redactHeaders(headers: Record<string, unknown>) {
+  const redacted: Record<string, string> = {};
+  for (const [name, value] of Object.entries(headers)) {
+    if (value === undefined) {
+      continue;
+    }
+    const text = Array.isArray(value) ? value.join(", ") : String(value);
+    redacted[name] = SENSITIVE_HEADER_PATTERN.test(name) ? redactHeaderValue(text) : text;
+  }
+  return redacted;
+}
+
+export function redactBody(value: unknown): unknown {
+  if (Array.isArray(value)) {
+    return value.map(redactBody);
+  }
+  if (typeof value !== "object" || value === null) {
+    return value;
+  }
+  const redacted: Record<string, unknown> = {};
+  for (const [name, child] of Object.entries(value)) {
+    redacted[name] = SENSITIVE_FIELD_PATTERN.test(name) ? "[REDACTED]" : redactBody(child);
+  }
+  return redacted;
+}
+
+// Redacts, then caps the stored size. A body over the cap is kept as a
+// truncated JSON string rather than dropped.
+export function captureBody(body: unknown): { body: unknown; bodyTruncated: boolean } {
+  if (body === undefined) {
+    return { body: null, bodyTruncated: false };
+  }
+  const redacted = redactBody(body);
+  const serialized = JSON.stringify(redacted) ?? "";
+  if (Buffer.byteLength(serialized) <= DEBUG_CAPTURE_BODY_MAX_BYTES) {
+    return { body: redacted, bodyTruncated: false };
+  }
+  return { body: serialized.slice(0, DEBUG_CAPTURE_BODY_MAX_BYTES), bodyTruncated: true };
+}
+
+// Best effort, like the audit trail: capture failures are logged only.
+export async function recordDebugCapture(capture: Omit<DebugCapture, "createdAt">) {
+  try {
+    const captures = await getDebugCapturesCollection();
+    await captures.insertOne({ ...capture, createdAt: nowDate() });
+    const overflow = (await captures.estimatedDocumentCount()) - DEBUG_CAPTURE_MAX_ENTRIES;
+    if (overflow > 0) {
+      const oldest = await captures.find({}, { projection: { _id: 1 } }).sort({ createdAt: 1 }).limit(overflow).toArray();
+      await captures.deleteMany({ _id: { $in: oldest.map((capture) => capture._id) } });
+    }
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    console.error("[debug-capture] Failed to record capture:", message);
+  }
+}
+
+export async function listDebugCaptures(tenantId: string, limit: number) {
+  const captures = await getDebugCapturesCollection();
+  return captures.find({ tenantId }).sort({ createdAt: -1 }).limit(limit).toArray();
+}
@@ -0,0 +1,70 @@
+import { parseNumberEnv } from "./utils/env";
+import { outboundFetch } from "./utils/http";
+
+export type EmailMessage = {
+  to: string;
+  subject: string;
+  text: string;
+};
+
+export interface EmailSender {
+  // False when messages are not actually delivered; flows that only work by
+  // email (password reset) are refused instead of silently doing nothing.
+  readonly enabled: boolean;
+  send(message: EmailMessage): Promise<void>;
+}
+
+// Default sender until a real transport is configured: records that a message
+// would have gone out without logging its body.
+export class LogEmailSender implements EmailSender {
+  readonly enabled = false;
+
+  async send(message: EmailMessage) {
+    console.log("[email] Would send:", { subject: message.subject });
+  }
+}
+
+// Hands each message to an HTTP mail relay (a provider's send API or an
+// internal mail service) as a JSON POST of { from, to, subject, text }.
+export class HttpEmailSender implements EmailSender {
+  readonly enabled = true;
+
+  constructor(
+    private readonly url: string,
+    private readonly from: string | undefined,
+    private readonly token: string | undefined,
+    private readonly timeoutMs: number,
+  ) {}
+
+  async send(message: EmailMessage) {
+    const response = await outboundFetch(
+      this.url,
+      {
+        method: "POST",
+        headers: {
+          "Content-Type": "application/json",
+          ...(this.token ? { Authorization: `Bearer ${this.token}` } : {}),
+        },
+        body: JSON.stringify({ ...(this.from ? { from: this.from } : {}), ...message }),
+      },
+      { timeoutMs: this.timeoutMs },
+    );
+    if (!response.ok) {
+      throw new Error(`Email relay returned status ${response.status}`);
+    }
+  }
+}
+
+export const EMAIL_HTTP_URL = process.env.EMAIL_HTTP_URL?.trim() || null;
+export const EMAIL_FROM = process.env.EMAIL_FROM?.trim() || undefined;
+export const EMAIL_HTTP_TOKEN = process.env.EMAIL_HTTP_TOKEN || undefined;
+export const EMAIL_HTTP_TIMEOUT_MS = parseNumberEnv("EMAIL_HTTP_TIMEOUT_MS", 5000);
+
+function createEmailSender(): EmailSender {
+  if (!EMAIL_HTTP_URL) {
+    return new LogEmailSender();
+  }
+  return new HttpEmailSender(EMAIL_HTTP_URL, EMAIL_FROM, EMAIL_HTTP_TOKEN, EMAIL_HTTP_TIMEOUT_MS);
+}
+
+export const emailSender: EmailSender = createEmailSender();
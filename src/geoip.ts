@@ -0,0 +1,59 @@
+import fs from "fs";
+import { MmdbReader } from "./utils/mmdb";
+
+export type GeoLocation = {
+  country?: string;
+  city?: string;
+};
+
+export interface GeoIpLookup {
+  lookup(ip: string): Promise<GeoLocation | null>;
+}
+
+// Default lookup: location is unknown, so only new devices (not new countries)
+// are detected.
+export class NoopGeoIpLookup implements GeoIpLookup {
+  async lookup(): Promise<GeoLocation | null> {
+    return null;
+  }
+}
+
+type MaxMindRecord = {
+  country?: { iso_code?: unknown };
+  city?: { names?: { en?: unknown } };
+};
+
+// Looks addresses up in a MaxMind GeoLite2/GeoIP2 Country or City database,
+// loaded into memory once at startup.
+export class MaxMindGeoIpLookup implements GeoIpLookup {
+  private readonly reader: MmdbReader;
+
+  constructor(path: string) {
+    this.reader = new MmdbReader(fs.readFileSync(path));
+  }
+
+  async lookup(ip: string): Promise<GeoLocation | null> {
+    const record = this.reader.get(ip) as MaxMindRecord | null;
+    const country = record?.country?.iso_code;
+    if (typeof country !== "string") {
+      return null;
+    }
+    const city = record?.city?.names?.en;
+    return { country, ...(typeof city === "string" ? { city } : {}) };
+  }
+}
+
+export const GEOIP_DB_PATH = process.env.GEOIP_DB_PATH?.trim() || null;
+
+function createGeoIpLookup(): GeoIpLookup {
+  return GEOIP_DB_PATH ? new MaxMindGeoIpLookup(GEOIP_DB_PATH) : new NoopGeoIpLookup();
+}
+
+export const geoIp: GeoIpLookup = createGeoIpLookup();
+
+export function describeLocation(location: GeoLocation | null) {
+  if (!location?.country) {
+    return "Unknown location";
+  }
+  return location.city ? `${location.city}, ${location.country}` : location.country;
+}
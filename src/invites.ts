@@ -0,0 +1,87 @@
+import crypto from "crypto";
+import { getDatabaseName, getMongoClient } from "./db";
+import { nowDate } from "./utils/clock";
+import { parseNumberEnv } from "./utils/env";
+import type { UserRole } from "./users";
+
+export const INVITE_TTL_SECONDS = parseNumberEnv("INVITE_TTL_SECONDS", 7 * 24 * 60 * 60);
+
+export type InviteRecord = {
+  // Only the SHA-256 of the invite token is stored.
+  tokenHash: string;
+  tenantId: string;
+  // When set, the invite can only be redeemed for this email.
+  email?: string;
+  roles: UserRole[];
+  createdBy: string;
+  createdAt: Date;
+  expiresAt: Date;
+  usedAt?: Date;
+};
+
+let indexesReady: Promise<unknown> | null = null;
+
+export async function getInvitesCollection() {
+  const client = await getMongoClient();
+  const invites = client.db(getDatabaseName()).collection<InviteRecord>("invites");
+  indexesReady ??= Promise.all([
+    invites.createIndex({ tokenHash: 1 }, { unique: true }),
+    invites.createIndex({ expiresAt: 1 }, { expireAfterSeconds: 0 }),
+  ]).catch((error) => {
+    indexesReady = null;
+    throw error;
+  });
+  await indexesReady;
+  return invites;
+}
+
+function hashInviteToken(token: string) {
+  return crypto.createHash("sha256").update(token).digest("hex");
+}
+
+export async function createInvite(invite: {
+  tenantId: string;
+  email?: string;
+  roles: UserRole[];
+  createdBy: string;
+  ttlSeconds?: number;
+}) {
+  const token = crypto.randomBytes(32).toString("base64url");
+  const now = nowDate();
+  const { ttlSeconds = INVITE_TTL_SECONDS, ...fields } = invite;
+  const record: InviteRecord = {
+    ...fields,
+    tokenHash: hashInviteToken(token),
+    createdAt: now,
+    expiresAt: new Date(now.getTime() + ttlSeconds * 1000),
+  };
+  const invites = await getInvitesCollection();
+  await invites.insertOne(record);
+  return { token, invite: record };
+}
+
+// Marks the invite used in a single atomic update, so two registrations racing
+// on the same token cannot both succeed. Returns null for unknown, used,
+// expired, other-tenant, or other-email invites alike.
+export async function consumeInvite(token: string, tenantId: string, email: string) {
+  const invites = await getInvitesCollection();
+  const now = nowDate();
+  return invites.findOneAndUpdate(
+    {
+      tokenHash: hashInviteToken(token),
+      tenantId,
+      usedAt: { $exists: false },
+      expiresAt: { $gt: now },
+      // null also matches invites stored without an email.
+      $or: [{ email: null }, { email }],
+    },
+    { $set: { usedAt: now } },
+    { returnDocument: "after" },
+  );
+}
+
+// Gives a consumed invite back when the registration it was used for failed.
+export async function releaseInvite(token: string) {
+  const invites = await getInvitesCollection();
+  await invites.updateOne({ tokenHash: hashInviteToken(token) }, { $unset: { usedAt: "" } });
+}
@@ -0,0 +1,52 @@
+import type { ObjectId } from "mongodb";
+import { recordAuditEvent } from "./audit";
+import { nowDate } from "./utils/clock";
+import { parseBooleanEnv, parseNumberEnv } from "./utils/env";
+import { getUsersCollection, type UserRecord } from "./users";
+
+// Per-account lockout after repeated wrong passwords, off by default. Unlike
+// the per-IP CAPTCHA threshold it also stops attacks spread over many IPs.
+export const LOGIN_LOCKOUT_ENABLED = parseBooleanEnv(process.env.LOGIN_LOCKOUT_ENABLED) ?? false;
+export const LOGIN_LOCKOUT_THRESHOLD = parseNumberEnv("LOGIN_LOCKOUT_THRESHOLD", 5);
+export const LOGIN_LOCKOUT_DURATION_SECONDS = parseNumberEnv("LOGIN_LOCKOUT_DURATION_SECONDS", 15 * 60);
+// Answer locked accounts with the ordinary invalid-credentials 401 instead of
+// 423, so the lockout does not confirm the account exists or is under attack.
+export const STEALTH_LOCKOUT = parseBooleanEnv(process.env.STEALTH_LOCKOUT) ?? false;
+
+export function isLockedOut(user: Pick<UserRecord, "lockedUntil">) {
+  return LOGIN_LOCKOUT_ENABLED && user.lockedUntil !== undefined && user.lockedUntil > nowDate();
+}
+
+// Counts a wrong password and locks the account once the threshold is hit.
+// The counter restarts after each lock.
+export async function recordFailedLogin(user: UserRecord & { _id: ObjectId }, ip: string | undefined) {
+  if (!LOGIN_LOCKOUT_ENABLED) {
+    return;
+  }
+  const users = await getUsersCollection();
+  const updated = await users.findOneAndUpdate(
+    { _id: user._id },
+    { $inc: { failedLoginCount: 1 } },
+    { returnDocument: "after", projection: { failedLoginCount: 1 } },
+  );
+  if (!updated || (updated.failedLoginCount ?? 0) < LOGIN_LOCKOUT_THRESHOLD) {
+    return;
+  }
+  const lockedUntil = new Date(nowDate().getTime() + LOGIN_LOCKOUT_DURATION_SECONDS * 1000);
+  await users.updateOne({ _id: user._id }, { $set: { lockedUntil, failedLoginCount: 0 } });
+  await recordAuditEvent({
+    type: "account.locked",
+    tenantId: user.tenantId,
+    userId: user._id.toHexString(),
+    ip,
+    details: { lockedUntil },
+  });
+}
+
+export async function clearFailedLogins(user: UserRecord & { _id: ObjectId }) {
+  if (user.failedLoginCount === undefined && user.lockedUntil === undefined) {
+    return;
+  }
+  const users = await getUsersCollection();
+  await users.updateOne({ _id: user._id }, { $unset: { failedLoginCount: "", lockedUntil: "" } });
+}
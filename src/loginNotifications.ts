@@ -0,0 +1,98 @@
+import { recordAuditEvent } from "./audit";
+import { emailSender } from "./email";
+import { describeLocation, geoIp } from "./geoip";
+import type { SessionRecord } from "./sessions";
+import { nowDate } from "./utils/clock";
+import { parseBooleanEnv } from "./utils/env";
+import { getUsersCollection, type UserRecord } from "./users";
+
+export const NEW_DEVICE_NOTIFICATIONS = parseBooleanEnv(process.env.NEW_DEVICE_NOTIFICATIONS) ?? true;
+const NOTIFY_INTERVAL_MS = 24 * 60 * 60 * 1000;
+
+export type KnownDevice = {
+  // DeviceDescriptor.description, e.g. "Firefox on Linux".
+  device: string;
+  firstSeenAt: Date;
+  lastNotifiedAt?: Date;
+};
+
+type LoginUser = Pick<UserRecord, "_id" | "email" | "tenantId" | "knownDevices" | "knownCountries" | "preferences">;
+
+async function checkLogin(user: LoginUser, session: SessionRecord) {
+  const users = await getUsersCollection();
+  const now = nowDate();
+  const device = session.device.description;
+  const location = session.ip ? await geoIp.lookup(session.ip) : null;
+  const country = location?.country;
+  const knownDevices = user.knownDevices ?? [];
+  const knownCountries = user.knownCountries ?? [];
+
+  // The first login after this feature (or after registration) only records
+  // what is normal for the user.
+  if (knownDevices.length === 0) {
+    await users.updateOne(
+      { _id: user._id },
+      {
+        $set: { knownDevices: [{ device, firstSeenAt: now }] },
+        ...(country ? { $addToSet: { knownCountries: country } } : {}),
+      },
+    );
+    return;
+  }
+
+  const knownDevice = knownDevices.find((entry) => entry.device === device);
+  const isNewCountry = country !== undefined && !knownCountries.includes(country);
+  if (knownDevice && !isNewCountry) {
+    return;
+  }
+
+  await recordAuditEvent({
+    type: "login.new_device",
+    tenantId: session.tenantId,
+    userId: session.userId,
+    ip: session.ip,
+    details: { device, country: country ?? null, newDevice: !knownDevice, newCountry: isNewCountry },
+  });
+
+  const recentlyNotified =
+    knownDevice?.lastNotifiedAt !== undefined && now.getTime() - knownDevice.lastNotifiedAt.getTime() < NOTIFY_INTERVAL_MS;
+  const shouldEmail = user.preferences?.notifyNewDevice !== false && !recentlyNotified;
+  if (shouldEmail) {
+    await emailSender.send({
+      to: user.email,
+      subject: "New sign-in to your account",
+      text: [
+        "Your account was just used to sign in from a new device or location.",
+        "",
+        `Time: ${now.toISOString()}`,
+        `Device: ${device}`,
+        `IP address: ${session.ip ?? "unknown"}`,
+        `Approximate location: ${describeLocation(location)}`,
+        "",
+        "If this wasn't you, review your active sessions and revoke any you don't recognize, then change your password.",
+      ].join("\n"),
+    });
+  }
+
+  const updatedDevices = knownDevice
+    ? knownDevices.map((entry) => (entry === knownDevice && shouldEmail ? { ...entry, lastNotifiedAt: now } : entry))
+    : [...knownDevices, { device, firstSeenAt: now, ...(shouldEmail ? { lastNotifiedAt: now } : {}) }];
+  await users.updateOne(
+    { _id: user._id },
+    {
+      $set: { knownDevices: updatedDevices },
+      ...(country ? { $addToSet: { knownCountries: country } } : {}),
+    },
+  );
+}
+
+// Runs after the login response has been sent; failures are only logged.
+export function notifyOnNewDevice(user: LoginUser, session: SessionRecord) {
+  if (!NEW_DEVICE_NOTIFICATIONS) {
+    return;
+  }
+  checkLogin(user, session).catch((error) => {
+    const message = error instanceof Error ? error.message : String(error);
+    console.error("[login-notifications] New device check failed:", message);
+  });
+}
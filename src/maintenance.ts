@@ -0,0 +1,47 @@
+import { getAuditCollection } from "./audit";
+import { revokeAllUserSessions } from "./sessions";
+import { nowDate } from "./utils/clock";
+import { parseNumberEnv } from "./utils/env";
+import { getUsersCollection, getUserTenant } from "./users";
+
+export const MAINTENANCE_INTERVAL_MS = parseNumberEnv("MAINTENANCE_INTERVAL_MS", 60 * 60 * 1000);
+
+// Removes accounts whose deletion grace period has ended, together with their
+// sessions and audit trail. The user record goes last and only while it is
+// still scheduled, so a run that fails halfway (or a second run) simply picks
+// the account up again, and a cancellation that races the purge keeps the user.
+export async function purgeDeletedAccounts(now = nowDate()) {
+  const users = await getUsersCollection();
+  const due = await users
+    .find({ deletionScheduledAt: { $lte: now } }, { projection: { _id: 1, tenantId: 1 } })
+    .toArray();
+  const audit = await getAuditCollection();
+  let purged = 0;
+  for (const user of due) {
+    const userId = user._id.toHexString();
+    await revokeAllUserSessions(userId, getUserTenant(user));
+    await audit.deleteMany({ userId });
+    const result = await users.deleteOne({ _id: user._id, deletionScheduledAt: { $lte: now } });
+    purged += result.deletedCount;
+  }
+  return purged;
+}
+
+async function runMaintenance() {
+  try {
+    const purged = await purgeDeletedAccounts();
+    if (purged > 0) {
+      console.log("[maintenance] Purged deleted accounts:", purged);
+    }
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    console.error("[maintenance] Account purge failed:", message);
+  }
+}
+
+// Only started by the long-running server; serverless deployments need an
+// external scheduler to call purgeDeletedAccounts.
+export function startMaintenance() {
+  void runMaintenance();
+  setInterval(() => void runMaintenance(), MAINTENANCE_INTERVAL_MS).unref();
+}
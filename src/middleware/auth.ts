@@ -1,36 +1,182 @@
 import type { Request, Response, NextFunction } from "express";
-import jwt from "jsonwebtoken";
-import rateLimit from "express-rate-limit";
-import { parseNumberEnv } from "../utils/env";
-import { type AuthPayload, getJwtSecret, parseAuthPayload } from "../utils/jwt";
+import { parseBooleanEnv, parseNumberEnv } from "../utils/env";
+import { recordAuditEvent } from "../audit";
+import { deliverToken, readRequestToken } from "../authCookie";
+import {
+  canExtendSession,
+  degradedSessionLookup,
+  extendSession,
+  findActiveSession,
+  SESSION_DEGRADATION_POLICY,
+  SESSION_EXTEND_WINDOW_SECONDS,
+  SESSION_SLIDING,
+  touchSession,
+  type SessionRecord,
+} from "../sessions";
+import { nowUnix } from "../utils/clock";
+import { sendError } from "../utils/errors";
+import { isSameNetwork } from "../utils/ip";
+import { type AuthPayload, verifyToken } from "../utils/jwt";
+import { routeRateLimiter } from "./rateLimit";
+import { getRequestTenant } from "./tenant";
+
+export const IP_BINDING_PREFIX_V4 = parseNumberEnv("TOKEN_IP_BINDING_PREFIX_V4", 32);
+export const IP_BINDING_PREFIX_V6 = parseNumberEnv("TOKEN_IP_BINDING_PREFIX_V6", 128);
+// Impersonation tokens are read-only unless a deployment opts in to writes.
+export const IMPERSONATION_ALLOW_WRITES = parseBooleanEnv(process.env.IMPERSONATION_ALLOW_WRITES) ?? false;
+const READ_METHODS = new Set(["GET", "HEAD", "OPTIONS"]);
 
 export type AuthenticatedRequest = Request & {
   user?: AuthPayload;
+  session?: SessionRecord;
 };
 
-export function requireAuth(req: AuthenticatedRequest, res: Response, next: NextFunction) {
-  const authHeader = req.headers.authorization;
-  if (!authHeader || !authHeader.startsWith("Bearer ")) {
-    res.status(401).json({ ok: false, error: "Missing bearer token" });
+// Sliding sessions: a token inside its extend window gets a successor on the
+// same session, sent as X-Refreshed-Token (or as the auth cookie in cookie-only
+// mode). The presented token stays valid until its own expiry.
+async function renewSlidingSession(res: Response, session: SessionRecord, user: AuthPayload) {
+  const inWindow = user.exp !== undefined && user.exp - nowUnix() <= SESSION_EXTEND_WINDOW_SECONDS;
+  if (!inWindow || !canExtendSession(session, user)) {
     return;
   }
-  const token = authHeader.slice("Bearer ".length);
   try {
-    const decoded = jwt.verify(token, getJwtSecret());
-    req.user = parseAuthPayload(decoded);
-    next();
+    const { token, expiresAt } = await extendSession(session, user);
+    const { token: headerToken } = deliverToken(res, token, expiresAt);
+    if (headerToken) {
+      res.setHeader("X-Refreshed-Token", headerToken);
+    }
+  } catch (error) {
+    // The current token still works, so the request goes ahead without renewal.
+    console.error("[auth] Sliding session renewal failed:", error instanceof Error ? error.message : error);
+  }
+}
+
+export async function requireAuth(req: AuthenticatedRequest, res: Response, next: NextFunction) {
+  const token = readRequestToken(req);
+  if (!token) {
+    sendError(res, 401, "missing_token", "Missing bearer token");
+    return;
+  }
+  let user: AuthPayload;
+  try {
+    user = verifyToken(token);
   } catch (error) {
     const message = error instanceof Error ? error.message : "Invalid token";
-    res.status(401).json({ ok: false, error: message });
+    sendError(res, 401, "invalid_token", message);
+    return;
   }
+  if (user.audience) {
+    // Exchanged tokens are scoped to a downstream audience, not to this API.
+    sendError(res, 401, "invalid_token", "Token audience is not accepted by this service");
+    return;
+  }
+  if (user.tenantId !== getRequestTenant(res)) {
+    console.log("[auth] Cross-tenant token rejected");
+    sendError(res, 403, "tenant_mismatch", "Token does not belong to this tenant");
+    return;
+  }
+  if (!user.jti) {
+    sendError(res, 401, "invalid_token", "Token is not bound to a session");
+    return;
+  }
+
+  // undefined: accepted in degraded mode without a known session record.
+  let session: SessionRecord | null | undefined;
+  let degraded = false;
+  try {
+    session = await findActiveSession(user.jti, user.exp);
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Session lookup failed";
+    console.error("[auth] Session lookup failed:", message);
+    // Only reads may degrade; writes always need a confirmed session.
+    const fallback = READ_METHODS.has(req.method) ? degradedSessionLookup(user.jti) : null;
+    if (!fallback) {
+      sendError(res, 503, "dependency_unavailable", "Session store is unavailable");
+      return;
+    }
+    console.warn(`[auth] Accepting token without session check (policy: ${SESSION_DEGRADATION_POLICY})`);
+    res.setHeader("X-Degraded-Auth", "true");
+    session = fallback.session;
+    degraded = true;
+  }
+  if (session === null || (session && session.userId !== user.sub)) {
+    sendError(res, 401, "session_revoked", "Session has been revoked or has expired");
+    return;
+  }
+
+  // req.ip is resolved through the "trust proxy" setting, so only forwarded
+  // addresses appended by trusted proxies are considered. Without a session
+  // record (degraded mode) the binding is read from the token's bip claim.
+  const boundIp = session ? (session.ipBound ? (session.ip ?? "") : undefined) : user.boundIp;
+  if (boundIp !== undefined && !isSameNetwork(req.ip ?? "", boundIp, IP_BINDING_PREFIX_V4, IP_BINDING_PREFIX_V6)) {
+    console.log("[auth] IP-bound token used from a different network");
+    void recordAuditEvent({
+      type: "token.ip_mismatch",
+      tenantId: user.tenantId,
+      userId: user.sub,
+      ip: req.ip,
+      details: { sessionIp: boundIp, jti: user.jti },
+    });
+    sendError(res, 401, "token_ip_mismatch", "Token cannot be used from this network");
+    return;
+  }
+
+  // Every request made while impersonating is audited, including refused writes.
+  if (user.actor) {
+    const allowed = READ_METHODS.has(req.method) || IMPERSONATION_ALLOW_WRITES;
+    void recordAuditEvent({
+      type: "impersonation.request",
+      tenantId: user.tenantId,
+      userId: user.sub,
+      actorId: user.actor,
+      ip: req.ip,
+      details: { method: req.method, path: req.originalUrl, allowed },
+    });
+    if (!allowed) {
+      sendError(res, 403, "impersonation_read_only", "Impersonation tokens cannot modify data");
+      return;
+    }
+  }
+
+  // Impersonation sessions are deliberately short and never slide.
+  if (SESSION_SLIDING && session && !degraded && !user.actor) {
+    await renewSlidingSession(res, session, user);
+  }
+
+  if (session && !degraded) {
+    touchSession(session).catch((error) => {
+      console.error("[auth] Failed to update session activity:", error instanceof Error ? error.message : error);
+    });
+  }
+  req.user = user;
+  req.session = session;
+  next();
 }
 
-export const authRateLimiter = rateLimit({
-  windowMs: parseNumberEnv("AUTH_RATE_LIMIT_WINDOW_MS", 60_000),
-  limit: parseNumberEnv("AUTH_RATE_LIMIT_MAX", 20),
-  standardHeaders: "draft-7",
-  legacyHeaders: false,
-  handler: (_req, res) => {
-    res.status(429).json({ ok: false, error: "Too many requests, try again later" });
-  },
-});
+export function requireRole(role: string) {
+  return (req: AuthenticatedRequest, res: Response, next: NextFunction) => {
+    if (!req.user) {
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+    if (!req.user.roles.includes(role)) {
+      sendError(res, 403, "forbidden", "Forbidden");
+      return;
+    }
+    next();
+  };
+}
+
+export const authRateLimiter = routeRateLimiter("AUTH", { windowMs: 60_000, limit: 20 });
+
+// Password guessing happens here, so login gets its own, tighter budget
+// instead of sharing the general auth limit.
+export const loginRateLimiter = routeRateLimiter("LOGIN", { windowMs: 60_000, limit: 5 });
+
+// Forgot-password sends email, so it is limited per hour. The per-account cap
+// lives in passwordResets.ts and never answers differently from a success.
+export const passwordResetRateLimiter = routeRateLimiter("PASSWORD_RESET", { windowMs: 60 * 60_000, limit: 10 });
+
+// Email availability checks reveal which accounts exist, so each client IP gets
+// a small hourly budget.
+export const emailCheckRateLimiter = routeRateLimiter("EMAIL_CHECK", { windowMs: 60 * 60_000, limit: 20 });
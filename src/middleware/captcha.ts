@@ -0,0 +1,33 @@
+import type { Request, Response, NextFunction } from "express";
+import { CAPTCHA_FAIL_OPEN, captchaVerifier } from "../captcha";
+import { sendError } from "../utils/errors";
+
+// Checks the captchaToken body field; run it after jsonBody. `when` limits the
+// challenge to some requests (e.g. logins from IPs with recent failures).
+// Nothing is checked while no provider is configured.
+export function requireCaptcha(when: (req: Request) => boolean = () => true) {
+  return async (req: Request, res: Response, next: NextFunction) => {
+    if (!captchaVerifier.enabled || !when(req)) {
+      next();
+      return;
+    }
+    const { captchaToken } = req.body ?? {};
+    if (typeof captchaToken !== "string" || !captchaToken) {
+      sendError(res, 403, "captcha_required", "CAPTCHA verification is required");
+      return;
+    }
+    const outcome = await captchaVerifier.verify(captchaToken, req.ip, { requestId: res.locals.requestId });
+    if (outcome === "failed") {
+      sendError(res, 403, "captcha_failed", "CAPTCHA verification failed");
+      return;
+    }
+    if (outcome === "unavailable" && !CAPTCHA_FAIL_OPEN) {
+      sendError(res, 503, "captcha_unavailable", "CAPTCHA verification is temporarily unavailable");
+      return;
+    }
+    if (outcome === "unavailable") {
+      console.log("[captcha] Provider unavailable, letting request through (CAPTCHA_FAIL_OPEN=true)");
+    }
+    next();
+  };
+}
@@ -0,0 +1,85 @@
+import type { Request, Response, NextFunction } from "express";
+
+type AllowedOrigins = Set<string> | "*";
+
+type RoutePolicy = {
+  prefix: string;
+  origins: AllowedOrigins;
+};
+
+const ALLOWED_METHODS = "GET, HEAD, POST, PUT, PATCH, DELETE";
+const EXPOSED_HEADERS = "X-Request-Id, X-Response-Time-Ms, Retry-After, X-Refreshed-Token";
+const PREFLIGHT_MAX_AGE_SECONDS = 600;
+
+function parseOrigins(raw: string): AllowedOrigins {
+  const origins = raw.split(",").map((origin) => origin.trim()).filter(Boolean);
+  return origins.includes("*") ? "*" : new Set(origins);
+}
+
+// CORS_ROUTE_ORIGINS="/admin=;/partner=https://partner.example.com,https://x.example.com"
+// An empty list refuses cross-origin access under that prefix.
+function parseRoutePolicies(raw: string | undefined): RoutePolicy[] {
+  const policies: RoutePolicy[] = [];
+  for (const entry of (raw ?? "").split(";").map((value) => value.trim()).filter(Boolean)) {
+    const separator = entry.indexOf("=");
+    const prefix = separator === -1 ? "" : entry.slice(0, separator).trim();
+    if (!prefix.startsWith("/")) {
+      console.error("[cors] Ignoring invalid CORS_ROUTE_ORIGINS entry:", entry);
+      continue;
+    }
+    policies.push({ prefix, origins: parseOrigins(entry.slice(separator + 1)) });
+  }
+  // Longest prefix first, so the most specific route wins.
+  return policies.sort((a, b) => b.prefix.length - a.prefix.length);
+}
+
+export const DEFAULT_ORIGINS = process.env.CORS_ALLOWED_ORIGINS?.trim()
+  ? parseOrigins(process.env.CORS_ALLOWED_ORIGINS)
+  : null;
+export const ROUTE_POLICIES = parseRoutePolicies(process.env.CORS_ROUTE_ORIGINS);
+
+export const CORS_ENABLED = DEFAULT_ORIGINS !== null || ROUTE_POLICIES.length > 0;
+
+function matchesPrefix(path: string, prefix: string) {
+  return path === prefix || path.startsWith(prefix.endsWith("/") ? prefix : `${prefix}/`);
+}
+
+function originsFor(path: string): AllowedOrigins | null {
+  return ROUTE_POLICIES.find((policy) => matchesPrefix(path, policy.prefix))?.origins ?? DEFAULT_ORIGINS;
+}
+
+// Applies the policy of the most specific CORS_ROUTE_ORIGINS prefix matching
+// the path, or CORS_ALLOWED_ORIGINS otherwise. Preflights are answered here
+// with the same policy and never reach the routes.
+export function cors(req: Request, res: Response, next: NextFunction) {
+  const origins = originsFor(req.path);
+  const origin = req.get("origin");
+  if (origins instanceof Set) {
+    res.append("Vary", "Origin");
+  }
+  const allowedOrigin =
+    origin === undefined || origins === null ? null : origins === "*" ? "*" : origins.has(origin) ? origin : null;
+  if (allowedOrigin) {
+    res.setHeader("Access-Control-Allow-Origin", allowedOrigin);
+    res.setHeader("Access-Control-Expose-Headers", EXPOSED_HEADERS);
+    // Credentialed requests (cookies) are only allowed for listed origins;
+    // browsers refuse them with a wildcard anyway.
+    if (allowedOrigin !== "*") {
+      res.setHeader("Access-Control-Allow-Credentials", "true");
+    }
+  }
+
+  if (req.method === "OPTIONS" && req.get("access-control-request-method")) {
+    if (allowedOrigin) {
+      res.setHeader("Access-Control-Allow-Methods", ALLOWED_METHODS);
+      const requestedHeaders = req.get("access-control-request-headers");
+      if (requestedHeaders) {
+        res.setHeader("Access-Control-Allow-Headers", requestedHeaders);
+      }
+      res.setHeader("Access-Control-Max-Age", String(PREFLIGHT_MAX_AGE_SECONDS));
+    }
+    res.status(204).end();
+    return;
+  }
+  next();
+}
@@ -0,0 +1,75 @@
+import type { Response, NextFunction } from "express";
+import {
+  captureBody,
+  DEBUG_CAPTURE_PATH_PREFIX,
+  DEBUG_CAPTURE_USER_ID,
+  recordDebugCapture,
+  redactHeaders,
+} from "../debugCapture";
+import { parseBooleanEnv } from "../utils/env";
+import type { AuthenticatedRequest } from "./auth";
+import { getRequestTenant } from "./tenant";
+
+const TEXTUAL_CONTENT_TYPE = /^(text\/|application\/(json|xml|x-www-form-urlencoded|[\w.+-]+\+(json|xml)))/i;
+
+// Only textual bodies are decoded; anything else is described, never converted.
+function decodeResponseBody(body: unknown, contentType: string | undefined): unknown {
+  if (Buffer.isBuffer(body)) {
+    return contentType && TEXTUAL_CONTENT_TYPE.test(contentType)
+      ? body.toString("utf8")
+      : `[binary ${body.length} bytes]`;
+  }
+  if (typeof body === "string" && contentType?.includes("json")) {
+    try {
+      return JSON.parse(body);
+    } catch {
+      return body;
+    }
+  }
+  return body;
+}
+
+// The user filter is evaluated once the response is done, after requireAuth
+// has attached the verified claims to the request.
+function shouldCapture(req: AuthenticatedRequest) {
+  if (parseBooleanEnv(req.get("x-debug-capture")) === true) {
+    return true;
+  }
+  if (DEBUG_CAPTURE_PATH_PREFIX && req.path.startsWith(DEBUG_CAPTURE_PATH_PREFIX)) {
+    return true;
+  }
+  return DEBUG_CAPTURE_USER_ID !== null && req.user?.sub === DEBUG_CAPTURE_USER_ID;
+}
+
+// Mounted only when DEBUG_CAPTURE=true (see app.ts).
+export function debugCapture(req: AuthenticatedRequest, res: Response, next: NextFunction) {
+  const startedAt = Date.now();
+  let responseBody: unknown;
+  const originalSend = res.send;
+  res.send = function (this: Response, body?: unknown) {
+    responseBody = body;
+    return originalSend.call(this, body);
+  } as Response["send"];
+
+  res.on("finish", () => {
+    if (!shouldCapture(req)) {
+      return;
+    }
+    const responseHeaders = res.getHeaders();
+    void recordDebugCapture({
+      tenantId: getRequestTenant(res),
+      userId: req.user?.sub,
+      requestId: res.locals.requestId,
+      method: req.method,
+      path: req.originalUrl,
+      status: res.statusCode,
+      durationMs: Date.now() - startedAt,
+      request: { headers: redactHeaders(req.headers), ...captureBody(req.body) },
+      response: {
+        headers: redactHeaders(responseHeaders),
+        ...captureBody(decodeResponseBody(responseBody, res.get("content-type"))),
+      },
+    });
+  });
+  next();
+}
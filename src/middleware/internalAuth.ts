@@ -0,0 +1,54 @@
+import type { Request, Response, NextFunction } from "express";
+import jwt from "jsonwebtoken";
+import { nowUnix } from "../utils/clock";
+import { sendError } from "../utils/errors";
+import { isJwtSecret } from "../utils/jwt";
+import { readSecretList } from "../utils/secrets";
+
+// Service identity for calls from our own services (the gateway), separate
+// from end-user tokens. With INTERNAL_JWT_SECRET set, internal-only endpoints
+// need a short-lived HS256 token with audience "internal" in the
+// X-Internal-Authorization header, since Authorization carries the user's.
+export const INTERNAL_JWT_SECRETS = readSecretList("INTERNAL_JWT_SECRET");
+const INTERNAL_TOKEN_AUDIENCE = "internal";
+// Longest lifetime accepted for an internal token, whatever its exp says.
+const INTERNAL_TOKEN_MAX_AGE_SECONDS = 300;
+
+if (INTERNAL_JWT_SECRETS.some(isJwtSecret)) {
+  throw new Error("INTERNAL_JWT_SECRET must differ from JWT_SECRET, or user tokens could pass as internal ones");
+}
+
+function verifyInternalToken(token: string) {
+  for (const secret of INTERNAL_JWT_SECRETS) {
+    try {
+      return jwt.verify(token, secret, {
+        algorithms: ["HS256"],
+        audience: INTERNAL_TOKEN_AUDIENCE,
+        maxAge: INTERNAL_TOKEN_MAX_AGE_SECONDS,
+        clockTimestamp: nowUnix(),
+      });
+    } catch {
+      // Try the next key; any failure on the last one rejects the token.
+    }
+  }
+  return null;
+}
+
+// Does nothing until INTERNAL_JWT_SECRET is set, so existing callers keep
+// working until the gateway starts minting tokens.
+export function requireInternalToken(req: Request, res: Response, next: NextFunction) {
+  if (INTERNAL_JWT_SECRETS.length === 0) {
+    next();
+    return;
+  }
+  const header = req.get("x-internal-authorization");
+  const token = header?.startsWith("Bearer ") ? header.slice("Bearer ".length) : null;
+  const claims = token ? verifyInternalToken(token) : null;
+  if (!claims || typeof claims === "string" || typeof claims.sub !== "string") {
+    console.log(`[internal-auth] Rejected ${req.method} ${req.path} without a valid internal token`);
+    sendError(res, 401, "internal_auth_required", "A valid internal service token is required");
+    return;
+  }
+  res.locals.internalCaller = claims.sub;
+  next();
+}
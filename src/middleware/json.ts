@@ -0,0 +1,121 @@
+import express, { type Request, type Response, type NextFunction } from "express";
+import type { IncomingMessage } from "http";
+import { parseBooleanEnv, parseNumberEnv } from "../utils/env";
+import { ApiError, sendError } from "../utils/errors";
+
+export const DENY_UNKNOWN_FIELDS = parseBooleanEnv(process.env.JSON_DENY_UNKNOWN_FIELDS) ?? false;
+export const JSON_MAX_DEPTH = parseNumberEnv("JSON_MAX_DEPTH", 32);
+export const AUTH_BODY_MAX_BYTES = parseNumberEnv("AUTH_BODY_MAX_BYTES", 4096);
+
+// Upper bounds for credential fields, checked before any of them reach scrypt.
+const MAX_FIELD_LENGTHS: Record<string, number> = {
+  email: 254,
+  password: 1024,
+  currentPassword: 1024,
+  newPassword: 1024,
+};
+
+const OPEN_BRACE = 0x7b;
+const CLOSE_BRACE = 0x7d;
+const OPEN_BRACKET = 0x5b;
+const CLOSE_BRACKET = 0x5d;
+const QUOTE = 0x22;
+const BACKSLASH = 0x5c;
+
+// Scans the raw bytes for container nesting before JSON.parse sees them, so a
+// pathologically nested body is refused without building the object graph.
+function exceedsJsonDepth(body: Buffer, maxDepth: number) {
+  let depth = 0;
+  let inString = false;
+  let escaped = false;
+  for (const byte of body) {
+    if (inString) {
+      if (escaped) {
+        escaped = false;
+      } else if (byte === BACKSLASH) {
+        escaped = true;
+      } else if (byte === QUOTE) {
+        inString = false;
+      }
+    } else if (byte === QUOTE) {
+      inString = true;
+    } else if (byte === OPEN_BRACE || byte === OPEN_BRACKET) {
+      depth++;
+      if (depth > maxDepth) {
+        return true;
+      }
+    } else if (byte === CLOSE_BRACE || byte === CLOSE_BRACKET) {
+      depth--;
+    }
+  }
+  return false;
+}
+
+// body-parser keeps the status of errors thrown from verify, so this surfaces
+// as a 422 through the error handler.
+function verifyJsonDepth(_req: unknown, _res: unknown, body: Buffer) {
+  if (exceedsJsonDepth(body, JSON_MAX_DEPTH)) {
+    throw new ApiError(422, "json_too_complex", "Request body is nested too deeply", { maxDepth: JSON_MAX_DEPTH });
+  }
+}
+
+// The user import streams its own NDJSON body once the caller is
+// authenticated (see routes/admin.ts); a JSON body sent there is refused
+// rather than parsed.
+const SELF_PARSED_PATHS = new Set(["/admin/users/import"]);
+
+function isDefaultJsonRequest(req: IncomingMessage) {
+  const request = req as Request;
+  return !SELF_PARSED_PATHS.has(request.path) && Boolean(request.is("application/json"));
+}
+
+export const parseJson = express.json({ type: isDefaultJsonRequest, verify: verifyJsonDepth });
+
+// Auth bodies are a few short fields; the smaller cap is enforced while the
+// body streams in, so an oversized one is never buffered whole or parsed.
+export const parseAuthJson = express.json({ limit: AUTH_BODY_MAX_BYTES, verify: verifyJsonDepth });
+
+// SCIM clients send application/scim+json, which the default parser skips.
+export const parseScimJson = express.json({
+  type: ["application/json", "application/scim+json"],
+  verify: verifyJsonDepth,
+});
+
+// Guards a JSON endpoint: the body must be sent as application/json (a charset
+// parameter is fine), parse to an object, keep credential fields within their
+// length limits, and only contain the listed fields when
+// JSON_DENY_UNKNOWN_FIELDS is enabled. Malformed, oversized, and overly nested
+// JSON is rejected earlier by the parsers above and reported by the error handler.
+export function jsonBody(fields: readonly string[]) {
+  return (req: Request, res: Response, next: NextFunction) => {
+    if (!req.is("application/json")) {
+      sendError(res, 415, "unsupported_media_type", "Content-Type must be application/json", {
+        contentType: req.get("content-type") ?? null,
+      });
+      return;
+    }
+    const body: unknown = req.body;
+    if (typeof body !== "object" || body === null || Array.isArray(body)) {
+      sendError(res, 422, "invalid_request", "Request body must be a JSON object");
+      return;
+    }
+    for (const [field, maxLength] of Object.entries(MAX_FIELD_LENGTHS)) {
+      const value = (body as Record<string, unknown>)[field];
+      if (typeof value === "string" && value.length > maxLength) {
+        sendError(res, 422, "field_too_long", `Field ${field} is too long`, { field, maxLength });
+        return;
+      }
+    }
+    if (DENY_UNKNOWN_FIELDS) {
+      const unknownFields = Object.keys(body).filter((field) => !fields.includes(field));
+      if (unknownFields.length > 0) {
+        sendError(res, 422, "unknown_fields", "Request body contains unknown fields", {
+          fields: unknownFields,
+          allowed: fields,
+        });
+        return;
+      }
+    }
+    next();
+  };
+}
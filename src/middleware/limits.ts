@@ -0,0 +1,14 @@
+import type { Request, Response, NextFunction } from "express";
+import { parseNumberEnv } from "../utils/env";
+import { sendError } from "../utils/errors";
+
+export const MAX_URL_LEN = parseNumberEnv("MAX_URL_LEN", 8192);
+
+// Rejects absurdly long URLs before any routing, parsing, or logging work.
+export function limitUrlLength(req: Request, res: Response, next: NextFunction) {
+  if (req.originalUrl.length > MAX_URL_LEN) {
+    sendError(res, 414, "uri_too_long", "Request URL is too long", { maxLength: MAX_URL_LEN });
+    return;
+  }
+  next();
+}
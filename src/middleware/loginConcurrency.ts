@@ -0,0 +1,46 @@
+import type { Request, Response, NextFunction } from "express";
+import { normalizeEmail } from "../users";
+import { parseNumberEnv } from "../utils/env";
+import { sendError } from "../utils/errors";
+import { getRequestTenant } from "./tenant";
+
+// Attempts for one account that may be in progress at once. Unlike the rate
+// limiters, which count attempts over time per IP, this caps simultaneous
+// attempts per email, which is what a distributed credential-stuffing run
+// against one account looks like. Counts are per process.
+export const LOGIN_MAX_CONCURRENT_PER_EMAIL = parseNumberEnv("LOGIN_MAX_CONCURRENT_PER_EMAIL", 3);
+
+const inFlight = new Map<string, number>();
+
+// Run after jsonBody; requests without a usable email are left to the handler.
+export function limitConcurrentLogins(req: Request, res: Response, next: NextFunction) {
+  const { email } = req.body ?? {};
+  if (typeof email !== "string" || !normalizeEmail(email)) {
+    next();
+    return;
+  }
+  const key = `${getRequestTenant(res)}:${normalizeEmail(email)}`;
+  const current = inFlight.get(key) ?? 0;
+  if (current >= LOGIN_MAX_CONCURRENT_PER_EMAIL) {
+    console.log("[login-concurrency] Too many concurrent logins for one account");
+    sendError(
+      res,
+      429,
+      "too_many_concurrent_logins",
+      "Another login for this account is in progress, try again shortly",
+    );
+    return;
+  }
+  inFlight.set(key, current + 1);
+
+  // close fires once the response is sent or the client goes away.
+  res.once("close", () => {
+    const remaining = (inFlight.get(key) ?? 1) - 1;
+    if (remaining > 0) {
+      inFlight.set(key, remaining);
+    } else {
+      inFlight.delete(key);
+    }
+  });
+  next();
+}
@@ -0,0 +1,29 @@
+import type { Request } from "express";
+import rateLimit, { type RateLimitInfo } from "express-rate-limit";
+import { parseNumberEnv } from "../utils/env";
+import { sendError } from "../utils/errors";
+
+// Effective window and limit of every limiter built below, by prefix.
+export const RATE_LIMIT_SETTINGS = new Map<string, { windowMs: number; limit: number }>();
+
+// Builds a per-client-IP limiter with its own counters, tunable through
+// <PREFIX>_RATE_LIMIT_WINDOW_MS and <PREFIX>_RATE_LIMIT_MAX. Each limiter keeps
+// a separate store, so the state is effectively keyed by (limiter, IP).
+export function routeRateLimiter(prefix: string, defaults: { windowMs: number; limit: number }) {
+  const windowMs = parseNumberEnv(`${prefix}_RATE_LIMIT_WINDOW_MS`, defaults.windowMs);
+  const limit = parseNumberEnv(`${prefix}_RATE_LIMIT_MAX`, defaults.limit);
+  RATE_LIMIT_SETTINGS.set(prefix, { windowMs, limit });
+  return rateLimit({
+    windowMs,
+    limit,
+    standardHeaders: "draft-7",
+    legacyHeaders: false,
+    handler: (req, res) => {
+      const resetTime = (req as Request & { rateLimit?: RateLimitInfo }).rateLimit?.resetTime;
+      const retryAfterMs = resetTime ? resetTime.getTime() - Date.now() : windowMs;
+      const retryAfterSeconds = Math.max(1, Math.ceil(retryAfterMs / 1000));
+      res.setHeader("Retry-After", String(retryAfterSeconds));
+      sendError(res, 429, "rate_limited", "Too many requests, try again later", { retryAfterSeconds });
+    },
+  });
+}
@@ -0,0 +1,12 @@
+import crypto from "crypto";
+import type { Request, Response, NextFunction } from "express";
+
+const REQUEST_ID_PATTERN = /^[\w.-]{1,128}$/;
+
+export function requestId(req: Request, res: Response, next: NextFunction) {
+  const incoming = req.get("x-request-id");
+  const id = incoming && REQUEST_ID_PATTERN.test(incoming) ? incoming : crypto.randomUUID();
+  res.locals.requestId = id;
+  res.setHeader("X-Request-Id", id);
+  next();
+}
@@ -0,0 +1,26 @@
+import type { Request, Response, NextFunction } from "express";
+
+// Response headers that never leave the service, e.g. framework banners.
+// RESPONSE_HEADER_DENYLIST replaces the default list; set it to an empty
+// string to send everything.
+export const RESPONSE_HEADER_DENYLIST = (process.env.RESPONSE_HEADER_DENYLIST ?? "x-powered-by,server")
+  .split(",")
+  .map((name) => name.trim().toLowerCase())
+  .filter(Boolean);
+
+// Removes denylisted headers right before they are written, so headers set
+// late by handlers or other middleware are caught too.
+export function stripResponseHeaders(_req: Request, res: Response, next: NextFunction) {
+  if (RESPONSE_HEADER_DENYLIST.length === 0) {
+    next();
+    return;
+  }
+  const originalWriteHead = res.writeHead as (...args: unknown[]) => Response;
+  res.writeHead = function (this: Response, ...args: unknown[]) {
+    for (const name of RESPONSE_HEADER_DENYLIST) {
+      this.removeHeader(name);
+    }
+    return originalWriteHead.apply(this, args);
+  } as Response["writeHead"];
+  next();
+}
@@ -0,0 +1,34 @@
+import type { Request, Response, NextFunction } from "express";
+import { parseNumberEnv } from "../utils/env";
+
+// Requests that take longer than this, start to finish, are logged as warnings.
+// Event streams are long-lived by design and never counted as slow.
+export const SLOW_REQUEST_MS = parseNumberEnv("SLOW_REQUEST_MS", 1000);
+
+// Adds X-Response-Time-Ms (time until headers are written) to every response.
+// The header is set from a writeHead hook because by then the handler has
+// finished deciding the response but nothing has been sent yet.
+export function responseTime(req: Request, res: Response, next: NextFunction) {
+  const startedAt = process.hrtime.bigint();
+  const originalWriteHead = res.writeHead as (...args: unknown[]) => Response;
+  res.writeHead = function (this: Response, ...args: unknown[]) {
+    const elapsedMs = Number(process.hrtime.bigint() - startedAt) / 1e6;
+    this.setHeader("X-Response-Time-Ms", elapsedMs.toFixed(3));
+    return originalWriteHead.apply(this, args);
+  } as Response["writeHead"];
+
+  res.on("finish", () => {
+    const durationMs = Number(process.hrtime.bigint() - startedAt) / 1e6;
+    const streaming = String(res.getHeader("content-type") ?? "").startsWith("text/event-stream");
+    if (durationMs > SLOW_REQUEST_MS && !streaming) {
+      console.warn("[slow-request]", {
+        method: req.method,
+        path: req.originalUrl.split("?")[0],
+        status: res.statusCode,
+        durationMs: Math.round(durationMs),
+        requestId: res.locals.requestId,
+      });
+    }
+  });
+  next();
+}
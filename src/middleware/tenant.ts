@@ -0,0 +1,59 @@
+import type { Request, Response, NextFunction } from "express";
+import { sendError } from "../utils/errors";
+
+export const DEFAULT_TENANT_ID = process.env.DEFAULT_TENANT_ID?.trim() || "default";
+
+const TENANT_ID_PATTERN = /^[a-z0-9][a-z0-9-]{0,62}$/;
+
+export function isValidTenantId(value: string) {
+  return TENANT_ID_PATTERN.test(value);
+}
+
+export function getRequestTenant(res: Response): string {
+  return res.locals.tenantId ?? DEFAULT_TENANT_ID;
+}
+
+export const TENANT_BASE_DOMAIN = process.env.TENANT_BASE_DOMAIN?.trim().toLowerCase().replace(/^\./, "") || undefined;
+
+// When TENANTS is set, only the listed tenants (and the default tenant) are served.
+export const KNOWN_TENANTS = process.env.TENANTS
+  ? new Set(
+      process.env.TENANTS.split(",")
+        .map((tenant) => tenant.trim().toLowerCase())
+        .filter(Boolean),
+    )
+  : undefined;
+
+function tenantFromHost(hostname: string): string | undefined {
+  if (!TENANT_BASE_DOMAIN) {
+    return undefined;
+  }
+  const host = hostname.toLowerCase();
+  const suffix = `.${TENANT_BASE_DOMAIN}`;
+  if (!host.endsWith(suffix)) {
+    return undefined;
+  }
+  const subdomain = host.slice(0, -suffix.length);
+  // Only a single label directly under the base domain names a tenant.
+  return subdomain.includes(".") ? undefined : subdomain;
+}
+
+export function resolveTenant(req: Request, res: Response, next: NextFunction) {
+  const candidate = tenantFromHost(req.hostname) ?? req.get("x-tenant-id")?.trim().toLowerCase();
+  if (candidate === undefined || candidate === "") {
+    res.locals.tenantId = DEFAULT_TENANT_ID;
+    next();
+    return;
+  }
+  if (!isValidTenantId(candidate)) {
+    sendError(res, 400, "invalid_tenant", "Tenant identifier is malformed");
+    return;
+  }
+  if (KNOWN_TENANTS && candidate !== DEFAULT_TENANT_ID && !KNOWN_TENANTS.has(candidate)) {
+    console.log("[tenant] Unknown tenant rejected:", candidate);
+    sendError(res, 404, "unknown_tenant", "Unknown tenant");
+    return;
+  }
+  res.locals.tenantId = candidate;
+  next();
+}
@@ -0,0 +1,116 @@
+import crypto from "crypto";
+import { recordAuditEvent } from "./audit";
+import { getDatabaseName, getMongoClient } from "./db";
+import { emailSender } from "./email";
+import { nowDate } from "./utils/clock";
+import { parseNumberEnv } from "./utils/env";
+import { getUsersCollection, tenantFilter } from "./users";
+
+export const PASSWORD_RESET_TTL_SECONDS = parseNumberEnv("PASSWORD_RESET_TTL_SECONDS", 60 * 60);
+// Reset emails per account per hour, so a victim's inbox cannot be flooded.
+export const PASSWORD_RESETS_PER_EMAIL_PER_HOUR = parseNumberEnv("PASSWORD_RESETS_PER_EMAIL_PER_HOUR", 3);
+// Frontend page the emailed link points to; the token is appended as ?token=.
+export const PASSWORD_RESET_URL = process.env.PASSWORD_RESET_URL?.trim() || null;
+const HOUR_MS = 60 * 60 * 1000;
+
+export type PasswordResetRecord = {
+  // Only the SHA-256 of the reset token is stored.
+  tokenHash: string;
+  userId: string;
+  tenantId: string;
+  createdAt: Date;
+  expiresAt: Date;
+  usedAt?: Date;
+};
+
+let indexesReady: Promise<unknown> | null = null;
+
+// Records outlive their token by up to an hour because the per-account cap
+// counts them; the TTL index cleans them up after that.
+export async function getPasswordResetsCollection() {
+  const client = await getMongoClient();
+  const resets = client.db(getDatabaseName()).collection<PasswordResetRecord>("password_resets");
+  indexesReady ??= Promise.all([
+    resets.createIndex({ tokenHash: 1 }, { unique: true }),
+    resets.createIndex({ userId: 1, createdAt: -1 }),
+    resets.createIndex(
+      { createdAt: 1 },
+      { expireAfterSeconds: Math.max(PASSWORD_RESET_TTL_SECONDS, HOUR_MS / 1000) },
+    ),
+  ]).catch((error) => {
+    indexesReady = null;
+    throw error;
+  });
+  await indexesReady;
+  return resets;
+}
+
+function hashResetToken(token: string) {
+  return crypto.createHash("sha256").update(token).digest("hex");
+}
+
+// Emails a reset link when the account exists and is under its hourly cap.
+// Callers answer the same way whatever happens here, and run it without
+// awaiting so the response time does not reveal whether the account exists.
+export async function requestPasswordReset(email: string, tenantId: string, ip: string | undefined) {
+  const users = await getUsersCollection();
+  const user = await users.findOne({ email, ...tenantFilter(tenantId) });
+  if (!user || user.disabled) {
+    return;
+  }
+  const userId = user._id.toHexString();
+  const resets = await getPasswordResetsCollection();
+  const now = nowDate();
+  const recent = await resets.countDocuments({ userId, createdAt: { $gt: new Date(now.getTime() - HOUR_MS) } });
+  if (recent >= PASSWORD_RESETS_PER_EMAIL_PER_HOUR) {
+    await recordAuditEvent({ type: "password_reset.throttled", tenantId, userId, ip });
+    return;
+  }
+
+  const token = crypto.randomBytes(32).toString("base64url");
+  await resets.insertOne({
+    tokenHash: hashResetToken(token),
+    userId,
+    tenantId,
+    createdAt: now,
+    expiresAt: new Date(now.getTime() + PASSWORD_RESET_TTL_SECONDS * 1000),
+  });
+  const link = PASSWORD_RESET_URL ? `${PASSWORD_RESET_URL}?token=${encodeURIComponent(token)}` : token;
+  await emailSender.send({
+    to: user.email,
+    subject: "Reset your password",
+    text: [
+      "Someone asked to reset the password of your account.",
+      `Use this within ${Math.round(PASSWORD_RESET_TTL_SECONDS / 60)} minutes to choose a new one: ${link}`,
+      "If this wasn't you, ignore this email; your password has not changed.",
+    ].join("\n"),
+  });
+  await recordAuditEvent({ type: "password_reset.requested", tenantId, userId, ip });
+}
+
+function pendingResetFilter(token: string, tenantId: string) {
+  return { tokenHash: hashResetToken(token), tenantId, usedAt: { $exists: false }, expiresAt: { $gt: nowDate() } };
+}
+
+// Looks a token up without using it, so the new password can be checked first.
+export async function findPasswordReset(token: string, tenantId: string) {
+  const resets = await getPasswordResetsCollection();
+  return resets.findOne(pendingResetFilter(token, tenantId));
+}
+
+// Marks the token used in one atomic update and invalidates the user's other
+// outstanding reset tokens. Returns null for unknown, used, expired, or
+// other-tenant tokens alike.
+export async function consumePasswordReset(token: string, tenantId: string) {
+  const resets = await getPasswordResetsCollection();
+  const now = nowDate();
+  const reset = await resets.findOneAndUpdate(
+    pendingResetFilter(token, tenantId),
+    { $set: { usedAt: now } },
+    { returnDocument: "after" },
+  );
+  if (reset) {
+    await resets.updateMany({ userId: reset.userId, usedAt: { $exists: false } }, { $set: { usedAt: now } });
+  }
+  return reset;
+}
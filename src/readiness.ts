@@ -0,0 +1,11 @@
+// Serverless invocations never run the startup phase, so the service counts as
+// ready unless server.ts explicitly defers readiness (STARTUP_MODE=lazy).
+let ready = true;
+
+export function setReady(value: boolean) {
+  ready = value;
+}
+
+export function isReady() {
+  return ready;
+}
@@ -0,0 +1,716 @@
+import { Router, type Response } from "express";
+import { ObjectId } from "mongodb";
+import { StringDecoder } from "string_decoder";
+import { getAuditCollection, recordAuditEvent } from "../audit";
+import { listDebugCaptures } from "../debugCapture";
+import { createInvite } from "../invites";
+import { requireAuth, requireRole, type AuthenticatedRequest } from "../middleware/auth";
+import { jsonBody } from "../middleware/json";
+import { getRequestTenant } from "../middleware/tenant";
+import {
+  getSessionsCollection,
+  listUserSessions,
+  revokeAllUserSessions,
+  startSession,
+  toSessionView,
+} from "../sessions";
+import { TtlCache } from "../utils/cache";
+import { nowDate } from "../utils/clock";
+import { mapWithConcurrency } from "../utils/concurrency";
+import { parseNumberEnv } from "../utils/env";
+import { ApiError, sendError, sendRouteError } from "../utils/errors";
+import { parseExpiresIn } from "../utils/jwt";
+import { createPasswordHash } from "../utils/password";
+import { checkPasswordPolicy, WEAK_PASSWORD_MESSAGE } from "../utils/passwordPolicy";
+import { isValidEmail } from "../utils/validation";
+import {
+  DEFAULT_USER_ROLES,
+  getUserRoles,
+  getUsersCollection,
+  isDuplicateKeyError,
+  isUserRole,
+  normalizeEmail,
+  tenantFilter,
+  type UserRole,
+} from "../users";
+
+export const BULK_USERS_MAX = parseNumberEnv("ADMIN_BULK_USERS_MAX", 100);
+export const PASSWORD_HASH_CONCURRENCY = parseNumberEnv("PASSWORD_HASH_CONCURRENCY", 4);
+const DEBUG_CAPTURES_PAGE_MAX = 200;
+export const IMPERSONATION_EXPIRES_IN = parseExpiresIn(process.env.IMPERSONATION_EXPIRES_IN, "15m");
+export const IMPORT_USERS_MAX = parseNumberEnv("ADMIN_IMPORT_USERS_MAX", 10_000);
+// Longest accepted import row; the whole upload may be IMPORT_USERS_MAX of them.
+export const IMPORT_ROW_MAX_BYTES = parseNumberEnv("ADMIN_IMPORT_ROW_MAX_BYTES", 2048);
+const IMPORT_BODY_MAX_BYTES = IMPORT_USERS_MAX * IMPORT_ROW_MAX_BYTES;
+// NDJSON imports are processed in chunks of this many rows as they stream in.
+const IMPORT_CHUNK_SIZE = 100;
+// Dashboards poll the stats; each tenant's counts are reused for this long.
+const STATS_CACHE_TTL_MS = 30_000;
+const STATS_WINDOW_MS = 24 * 60 * 60 * 1000;
+// Precomputed hashes must be in this service's format: hex scrypt key (64 bytes) plus its salt.
+const SCRYPT_HASH_PATTERN = /^[0-9a-f]{128}$/i;
+
+type BulkUserEntry = {
+  email: string;
+  password: string;
+  roles: UserRole[];
+};
+
+type BulkUserResult =
+  | { index: number; status: "created"; id: string; email: string }
+  | { index: number; status: "conflict"; email: string; error: string }
+  | { index: number; status: "invalid"; email?: string; error: string };
+
+type ImportUserEntry = {
+  email: string;
+  roles: UserRole[];
+  credentials: { password: string } | { hash: string; salt: string };
+};
+
+type AdminStats = {
+  totalUsers: number;
+  activeSessions: number;
+  registrationsLast24h: number;
+  failedLoginsLast24h: number;
+  generatedAt: Date;
+};
+
+const statsCache = new TtlCache<AdminStats>(1_000);
+
+async function computeAdminStats(tenantId: string): Promise<AdminStats> {
+  const generatedAt = nowDate();
+  const since = new Date(generatedAt.getTime() - STATS_WINDOW_MS);
+  const [users, sessions, audit] = await Promise.all([
+    getUsersCollection(),
+    getSessionsCollection(),
+    getAuditCollection(),
+  ]);
+  const [totalUsers, activeSessions, registrationsLast24h, failedLoginsLast24h] = await Promise.all([
+    users.countDocuments(tenantFilter(tenantId)),
+    sessions.countDocuments({ tenantId, expiresAt: { $gt: generatedAt } }),
+    users.countDocuments({ ...tenantFilter(tenantId), createdAt: { $gte: since } }),
+    audit.countDocuments({ type: "login.failed", tenantId, createdAt: { $gte: since } }),
+  ]);
+  return { totalUsers, activeSessions, registrationsLast24h, failedLoginsLast24h, generatedAt };
+}
+
+function parseRoles(roles: unknown): UserRole[] | string {
+  if (roles === undefined) {
+    return DEFAULT_USER_ROLES;
+  }
+  if (!Array.isArray(roles) || !roles.every(isUserRole)) {
+    return "Roles must be an array of known roles";
+  }
+  const entryRoles: UserRole[] = Array.from(new Set<UserRole>(roles));
+  return entryRoles.length > 0 ? entryRoles : DEFAULT_USER_ROLES;
+}
+
+function validateBulkEntry(entry: unknown): BulkUserEntry | string {
+  if (typeof entry !== "object" || entry === null) {
+    return "Entry must be an object";
+  }
+  const { email, password, roles } = entry as Record<string, unknown>;
+  if (typeof email !== "string" || typeof password !== "string") {
+    return "Email and password are required";
+  }
+  const normalizedEmail = normalizeEmail(email);
+  if (!normalizedEmail || !isValidEmail(normalizedEmail)) {
+    return "Valid email is required";
+  }
+  if (!checkPasswordPolicy(password, normalizedEmail).accepted) {
+    return WEAK_PASSWORD_MESSAGE;
+  }
+  const entryRoles = parseRoles(roles);
+  if (typeof entryRoles === "string") {
+    return entryRoles;
+  }
+  return { email: normalizedEmail, password, roles: entryRoles };
+}
+
+// Imported plaintext passwords are existing credentials, so they are not held
+// to the registration strength policy.
+function validateImportEntry(entry: unknown): ImportUserEntry | string {
+  if (typeof entry !== "object" || entry === null) {
+    return "Entry must be an object";
+  }
+  const { email, password, passwordHash, passwordSalt, roles } = entry as Record<string, unknown>;
+  if (typeof email !== "string") {
+    return "Email is required";
+  }
+  const normalizedEmail = normalizeEmail(email);
+  if (!normalizedEmail || !isValidEmail(normalizedEmail)) {
+    return "Valid email is required";
+  }
+  let credentials: ImportUserEntry["credentials"];
+  if (passwordHash !== undefined) {
+    if (typeof passwordHash !== "string" || !SCRYPT_HASH_PATTERN.test(passwordHash)) {
+      return "passwordHash must be a hex-encoded 64-byte scrypt key";
+    }
+    if (typeof passwordSalt !== "string" || !passwordSalt) {
+      return "passwordSalt is required with passwordHash";
+    }
+    credentials = { hash: passwordHash.toLowerCase(), salt: passwordSalt };
+  } else if (typeof password === "string" && password) {
+    credentials = { password };
+  } else {
+    return "Either password or passwordHash and passwordSalt are required";
+  }
+  const entryRoles = parseRoles(roles);
+  if (typeof entryRoles === "string") {
+    return entryRoles;
+  }
+  return { email: normalizedEmail, roles: entryRoles, credentials };
+}
+
+type ImportState = {
+  tenantId: string;
+  results: BulkUserResult[];
+  seenEmails: Set<string>;
+};
+
+async function importChunk(state: ImportState, rows: { index: number; entry: unknown }[]) {
+  const { tenantId, results, seenEmails } = state;
+  const pending: { index: number; entry: ImportUserEntry }[] = [];
+  for (const { index, entry: rawEntry } of rows) {
+    const entry = validateImportEntry(rawEntry);
+    if (typeof entry === "string") {
+      results.push({ index, status: "invalid", error: entry });
+    } else if (seenEmails.has(entry.email)) {
+      results.push({ index, status: "conflict", email: entry.email, error: "Email is duplicated in import" });
+    } else {
+      seenEmails.add(entry.email);
+      pending.push({ index, entry });
+    }
+  }
+  if (pending.length === 0) {
+    return;
+  }
+
+  const users = await getUsersCollection();
+  const existing = await users
+    .find(
+      { email: { $in: pending.map(({ entry }) => entry.email) }, ...tenantFilter(tenantId) },
+      { projection: { email: 1 } },
+    )
+    .toArray();
+  const existingEmails = new Set(existing.map((user) => user.email));
+
+  await mapWithConcurrency(pending, PASSWORD_HASH_CONCURRENCY, async ({ index, entry }) => {
+    if (existingEmails.has(entry.email)) {
+      results.push({ index, status: "conflict", email: entry.email, error: "Email is already registered" });
+      return;
+    }
+    const { salt, hash } =
+      "password" in entry.credentials ? await createPasswordHash(entry.credentials.password) : entry.credentials;
+    try {
+      const result = await users.insertOne({
+        tenantId,
+        email: entry.email,
+        passwordHash: hash,
+        passwordSalt: salt,
+        roles: entry.roles,
+        createdAt: nowDate(),
+      });
+      results.push({ index, status: "created", id: result.insertedId.toHexString(), email: entry.email });
+    } catch (error) {
+      if (!isDuplicateKeyError(error)) {
+        throw error;
+      }
+      results.push({ index, status: "conflict", email: entry.email, error: "Email is already registered" });
+    }
+  });
+}
+
+// Splits the request stream into lines without ever holding more than one
+// row: the total upload is capped at IMPORT_BODY_MAX_BYTES and each line at
+// IMPORT_ROW_MAX_BYTES, and either limit ends the import with a 413.
+async function* readNdjsonLines(req: AuthenticatedRequest) {
+  const decoder = new StringDecoder("utf8");
+  let received = 0;
+  let pending = "";
+  // Leaving the loop early must not destroy the request, or the response
+  // could no longer be written.
+  for await (const chunk of req.iterator({ destroyOnReturn: false })) {
+    received += (chunk as Buffer).length;
+    if (received > IMPORT_BODY_MAX_BYTES) {
+      throw new ApiError(413, "payload_too_large", "Request body is too large", { maxBytes: IMPORT_BODY_MAX_BYTES });
+    }
+    pending += decoder.write(chunk as Buffer);
+    let newline = pending.indexOf("\n");
+    while (newline !== -1) {
+      yield checkedRow(pending.slice(0, newline));
+      pending = pending.slice(newline + 1);
+      newline = pending.indexOf("\n");
+    }
+    checkedRow(pending);
+  }
+  pending += decoder.end();
+  if (pending) {
+    yield checkedRow(pending);
+  }
+}
+
+function checkedRow(line: string) {
+  if (Buffer.byteLength(line) > IMPORT_ROW_MAX_BYTES) {
+    throw new ApiError(413, "row_too_large", "An import row is too large", { maxBytes: IMPORT_ROW_MAX_BYTES });
+  }
+  return line;
+}
+
+// Reads NDJSON rows straight off the request stream, importing them in chunks
+// so large files are never buffered whole. Returns true if the row cap cut the
+// import short.
+async function importNdjson(req: AuthenticatedRequest, state: ImportState) {
+  let chunk: { index: number; entry: unknown }[] = [];
+  let index = 0;
+  let truncated = false;
+  try {
+    for await (const line of readNdjsonLines(req)) {
+      if (!line.trim()) {
+        continue;
+      }
+      if (index >= IMPORT_USERS_MAX) {
+        truncated = true;
+        break;
+      }
+      try {
+        chunk.push({ index, entry: JSON.parse(line) });
+      } catch {
+        state.results.push({ index, status: "invalid", error: "Row is not valid JSON" });
+      }
+      index++;
+      if (chunk.length >= IMPORT_CHUNK_SIZE) {
+        await importChunk(state, chunk);
+        chunk = [];
+      }
+    }
+  } catch (error) {
+    // Close the connection after the 413 instead of reading the rest.
+    req.res?.setHeader("Connection", "close");
+    throw error;
+  }
+  await importChunk(state, chunk);
+  if (truncated) {
+    // Drain the rest of the upload so the response can still be written.
+    req.resume();
+  }
+  return truncated;
+}
+
+// Shared by both admin revocation routes so they revoke and audit the same way.
+async function revokeSessionsForAdmin(req: AuthenticatedRequest, actorId: string, userId: string, tenantId: string) {
+  const revoked = await revokeAllUserSessions(userId, tenantId);
+  await recordAuditEvent({
+    type: "admin.sessions_revoked",
+    tenantId,
+    userId,
+    actorId,
+    ip: req.ip,
+    details: { revoked },
+  });
+  return revoked;
+}
+
+const router = Router();
+
+router.post(
+  "/admin/users/bulk",
+  requireAuth,
+  requireRole("admin"),
+  jsonBody(["users"]),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /admin/users/bulk] Bulk registration requested");
+  try {
+    const { users: entries } = req.body ?? {};
+    if (!Array.isArray(entries) || entries.length === 0) {
+      console.log("[POST /admin/users/bulk] Missing users array");
+      sendError(res, 400, "invalid_request", "A non-empty users array is required");
+      return;
+    }
+    if (entries.length > BULK_USERS_MAX) {
+      console.log("[POST /admin/users/bulk] Batch too large:", entries.length);
+      sendError(res, 413, "batch_too_large", `At most ${BULK_USERS_MAX} users can be created per request`, {
+        max: BULK_USERS_MAX,
+      });
+      return;
+    }
+
+    const results: (BulkUserResult | undefined)[] = new Array(entries.length);
+    const pending: { index: number; entry: BulkUserEntry }[] = [];
+    const seenEmails = new Set<string>();
+
+    entries.forEach((rawEntry: unknown, index: number) => {
+      const entry = validateBulkEntry(rawEntry);
+      if (typeof entry === "string") {
+        results[index] = { index, status: "invalid", error: entry };
+        return;
+      }
+      if (seenEmails.has(entry.email)) {
+        results[index] = { index, status: "conflict", email: entry.email, error: "Email is duplicated in batch" };
+        return;
+      }
+      seenEmails.add(entry.email);
+      pending.push({ index, entry });
+    });
+
+    const tenantId = getRequestTenant(res);
+    const users = await getUsersCollection();
+    const existing = await users
+      .find(
+        { email: { $in: pending.map(({ entry }) => entry.email) }, ...tenantFilter(tenantId) },
+        { projection: { email: 1 } },
+      )
+      .toArray();
+    const existingEmails = new Set(existing.map((user) => user.email));
+
+    const toCreate = pending.filter(({ index, entry }) => {
+      if (existingEmails.has(entry.email)) {
+        results[index] = { index, status: "conflict", email: entry.email, error: "Email is already registered" };
+        return false;
+      }
+      return true;
+    });
+
+    // scrypt runs on the libuv threadpool; bounding the fan-out keeps a large
+    // batch from queueing ahead of every other request's password check.
+    await mapWithConcurrency(toCreate, PASSWORD_HASH_CONCURRENCY, async ({ index, entry }) => {
+      const { salt, hash } = await createPasswordHash(entry.password);
+      try {
+        const result = await users.insertOne({
+          tenantId,
+          email: entry.email,
+          passwordHash: hash,
+          passwordSalt: salt,
+          roles: entry.roles,
+          createdAt: nowDate(),
+        });
+        results[index] = { index, status: "created", id: result.insertedId.toHexString(), email: entry.email };
+      } catch (error) {
+        if (!isDuplicateKeyError(error)) {
+          throw error;
+        }
+        results[index] = { index, status: "conflict", email: entry.email, error: "Email is already registered" };
+      }
+    });
+
+    const finalResults = results as BulkUserResult[];
+    const summary = {
+      created: finalResults.filter((result) => result.status === "created").length,
+      conflict: finalResults.filter((result) => result.status === "conflict").length,
+      invalid: finalResults.filter((result) => result.status === "invalid").length,
+    };
+    console.log("[POST /admin/users/bulk] Bulk registration finished:", summary);
+    res.status(200).json({ ok: true, results: finalResults, summary });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Bulk registration failed";
+    console.error("[POST /admin/users/bulk] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.post(
+  "/admin/users/:id/revoke-sessions",
+  requireAuth,
+  requireRole("admin"),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /admin/users/:id/revoke-sessions] Session revocation requested");
+  try {
+    if (!req.user) {
+      console.log("[POST /admin/users/:id/revoke-sessions] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const targetUserId = req.params.id;
+    const tenantId = getRequestTenant(res);
+    const dryRun = req.query.dry_run === "true";
+
+    if (dryRun) {
+      const sessions = await listUserSessions(targetUserId, tenantId);
+      console.log("[POST /admin/users/:id/revoke-sessions] Dry run:", sessions.length);
+      res.status(200).json({
+        ok: true,
+        dryRun: true,
+        revoked: sessions.length,
+        sessions: sessions.map((session) => toSessionView(session)),
+      });
+      return;
+    }
+
+    const revoked = await revokeSessionsForAdmin(req, req.user.sub, targetUserId, tenantId);
+    console.log("[POST /admin/users/:id/revoke-sessions] Sessions revoked:", revoked);
+    res.status(200).json({ ok: true, dryRun: false, revoked });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Session revocation failed";
+    console.error("[POST /admin/users/:id/revoke-sessions] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.get(
+  "/admin/users/:id/sessions",
+  requireAuth,
+  requireRole("admin"),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[GET /admin/users/:id/sessions] Session list requested");
+  try {
+    const sessions = await listUserSessions(req.params.id, getRequestTenant(res));
+    console.log("[GET /admin/users/:id/sessions] Sessions listed:", sessions.length);
+    res.status(200).json({ ok: true, sessions: sessions.map((session) => toSessionView(session)) });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Failed to list sessions";
+    console.error("[GET /admin/users/:id/sessions] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// Same as POST /admin/users/:id/revoke-sessions without the dry run.
+router.delete(
+  "/admin/users/:id/sessions",
+  requireAuth,
+  requireRole("admin"),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[DELETE /admin/users/:id/sessions] Session revocation requested");
+  try {
+    if (!req.user) {
+      console.log("[DELETE /admin/users/:id/sessions] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const revoked = await revokeSessionsForAdmin(req, req.user.sub, req.params.id, getRequestTenant(res));
+    console.log("[DELETE /admin/users/:id/sessions] Sessions revoked:", revoked);
+    res.status(200).json({ ok: true, revoked });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Session revocation failed";
+    console.error("[DELETE /admin/users/:id/sessions] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.post(
+  "/admin/users/import",
+  requireAuth,
+  requireRole("admin"),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /admin/users/import] User import requested");
+  try {
+    if (!req.user) {
+      console.log("[POST /admin/users/import] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    if (!req.is("application/x-ndjson")) {
+      console.log("[POST /admin/users/import] Unsupported content type");
+      sendError(res, 415, "unsupported_media_type", "Content-Type must be application/x-ndjson", {
+        contentType: req.get("content-type") ?? null,
+      });
+      return;
+    }
+
+    const state: ImportState = { tenantId: getRequestTenant(res), results: [], seenEmails: new Set() };
+    const truncated = await importNdjson(req, state);
+
+    const results = state.results.sort((a, b) => a.index - b.index);
+    const summary = {
+      created: results.filter((result) => result.status === "created").length,
+      conflict: results.filter((result) => result.status === "conflict").length,
+      invalid: results.filter((result) => result.status === "invalid").length,
+      truncated,
+    };
+    await recordAuditEvent({
+      type: "admin.users_imported",
+      tenantId: state.tenantId,
+      actorId: req.user.sub,
+      ip: req.ip,
+      details: summary,
+    });
+    console.log("[POST /admin/users/import] User import finished:", summary);
+    res.status(200).json({ ok: true, results, summary });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "User import failed";
+    console.error("[POST /admin/users/import] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.post(
+  "/admin/invites",
+  requireAuth,
+  requireRole("admin"),
+  jsonBody(["email", "roles", "expiresInSeconds"]),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /admin/invites] Invite creation requested");
+  try {
+    if (!req.user) {
+      console.log("[POST /admin/invites] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const { email, roles, expiresInSeconds } = req.body ?? {};
+    let inviteEmail: string | undefined;
+    if (email !== undefined) {
+      inviteEmail = typeof email === "string" ? normalizeEmail(email) : "";
+      if (!inviteEmail || !isValidEmail(inviteEmail)) {
+        console.log("[POST /admin/invites] Invalid email format");
+        sendError(res, 400, "invalid_email", "Valid email is required");
+        return;
+      }
+    }
+    const inviteRoles = parseRoles(roles);
+    if (typeof inviteRoles === "string") {
+      console.log("[POST /admin/invites] Invalid roles");
+      sendError(res, 400, "invalid_request", inviteRoles);
+      return;
+    }
+    if (
+      expiresInSeconds !== undefined &&
+      (typeof expiresInSeconds !== "number" || !Number.isInteger(expiresInSeconds) || expiresInSeconds <= 0)
+    ) {
+      console.log("[POST /admin/invites] Invalid expiry");
+      sendError(res, 400, "invalid_request", "expiresInSeconds must be a positive integer");
+      return;
+    }
+
+    const tenantId = getRequestTenant(res);
+    const { token, invite } = await createInvite({
+      tenantId,
+      ...(inviteEmail ? { email: inviteEmail } : {}),
+      roles: inviteRoles,
+      createdBy: req.user.sub,
+      ttlSeconds: expiresInSeconds,
+    });
+    await recordAuditEvent({
+      type: "admin.invite_created",
+      tenantId,
+      actorId: req.user.sub,
+      ip: req.ip,
+      details: { email: invite.email ?? null, roles: invite.roles, expiresAt: invite.expiresAt },
+    });
+    console.log("[POST /admin/invites] Invite created");
+    res.status(201).json({
+      ok: true,
+      invite: { token, email: invite.email ?? null, roles: invite.roles, expiresAt: invite.expiresAt },
+    });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Invite creation failed";
+    console.error("[POST /admin/invites] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// Issues a short-lived token for the target user carrying act/impersonation
+// claims. requireAuth audits every request made with it and refuses writes
+// unless IMPERSONATION_ALLOW_WRITES is set; the session shows up in the
+// target's session list with impersonatedBy.
+router.post(
+  "/admin/impersonate/:id",
+  requireAuth,
+  requireRole("admin"),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /admin/impersonate/:id] Impersonation requested");
+  try {
+    if (!req.user) {
+      console.log("[POST /admin/impersonate/:id] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+    if (req.user.actor) {
+      console.log("[POST /admin/impersonate/:id] Nested impersonation rejected");
+      sendError(res, 403, "forbidden", "Forbidden");
+      return;
+    }
+
+    const tenantId = getRequestTenant(res);
+    const users = await getUsersCollection();
+    const target = ObjectId.isValid(req.params.id)
+      ? await users.findOne({ _id: new ObjectId(req.params.id), ...tenantFilter(tenantId) })
+      : null;
+    if (!target) {
+      console.log("[POST /admin/impersonate/:id] Target user not found");
+      sendError(res, 404, "user_not_found", "User not found");
+      return;
+    }
+
+    const targetId = target._id.toHexString();
+    const roles = getUserRoles(target);
+    const { token, session } = await startSession(
+      req,
+      { sub: targetId, email: target.email, roles, tenantId, actor: req.user.sub },
+      target.claims,
+      { expiresIn: IMPERSONATION_EXPIRES_IN, bindIp: false },
+    );
+    await recordAuditEvent({
+      type: "admin.impersonation_started",
+      tenantId,
+      userId: targetId,
+      actorId: req.user.sub,
+      ip: req.ip,
+      details: { sessionId: session._id.toHexString(), expiresAt: session.expiresAt },
+    });
+    console.log("[POST /admin/impersonate/:id] Impersonation token issued");
+    res.status(201).json({
+      ok: true,
+      token,
+      expiresAt: session.expiresAt,
+      user: { id: targetId, email: target.email, roles },
+    });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Impersonation failed";
+    console.error("[POST /admin/impersonate/:id] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.get(
+  "/admin/stats",
+  requireAuth,
+  requireRole("admin"),
+  async (_req: AuthenticatedRequest, res: Response) => {
+  console.log("[GET /admin/stats] Stats requested");
+  try {
+    const tenantId = getRequestTenant(res);
+    let stats = statsCache.get(tenantId);
+    if (!stats) {
+      stats = await computeAdminStats(tenantId);
+      statsCache.set(tenantId, stats, STATS_CACHE_TTL_MS);
+    }
+    res.status(200).json({ ok: true, stats });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Failed to compute stats";
+    console.error("[GET /admin/stats] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.get(
+  "/admin/debug/captures",
+  requireAuth,
+  requireRole("admin"),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[GET /admin/debug/captures] Debug captures requested");
+  try {
+    const requested = Number(req.query.limit);
+    const limit = Number.isInteger(requested) && requested > 0 ? Math.min(requested, DEBUG_CAPTURES_PAGE_MAX) : 50;
+    const captures = await listDebugCaptures(getRequestTenant(res), limit);
+    console.log("[GET /admin/debug/captures] Captures listed:", captures.length);
+    res.status(200).json({ ok: true, captures });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Failed to list debug captures";
+    console.error("[GET /admin/debug/captures] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+export default router;
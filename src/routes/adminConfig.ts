@@ -0,0 +1,27 @@
+import { Router, type Response } from "express";
+import { describeSettings } from "../config";
+import { requireAuth, requireRole, type AuthenticatedRequest } from "../middleware/auth";
+import { sendRouteError } from "../utils/errors";
+
+// Process-wide settings, so this is mounted on the admin listener only (see
+// adminApp.ts) rather than next to the tenant-scoped admin routes.
+const router = Router();
+
+router.get(
+  "/admin/config",
+  requireAuth,
+  requireRole("admin"),
+  (_req: AuthenticatedRequest, res: Response) => {
+    console.log("[GET /admin/config] Effective configuration requested");
+    try {
+      res.setHeader("Cache-Control", "no-store");
+      res.status(200).json({ ok: true, settings: describeSettings() });
+    } catch (error) {
+      const message = error instanceof Error ? error.message : "Failed to describe configuration";
+      console.error("[GET /admin/config] Error:", message);
+      sendRouteError(res, error, message);
+    }
+  },
+);
+
+export default router;
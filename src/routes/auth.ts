@@ -1,131 +1,332 @@
-import { Router, type Request, type Response } from "express";
+import { Router, type NextFunction, type Request, type Response } from "express";
 import { ObjectId } from "mongodb";
-import { getMongoClient } from "../db";
-import { authRateLimiter, requireAuth, type AuthenticatedRequest } from "../middleware/auth";
-import { createToken } from "../utils/jwt";
-import { createPasswordHash, verifyPassword } from "../utils/password";
-import { isValidEmail, isStrongPassword, PASSWORD_MIN_LENGTH } from "../utils/validation";
-
-type UserRecord = {
-  _id?: ObjectId;
-  email: string;
-  passwordHash: string;
-  passwordSalt: string;
-  createdAt: Date;
-};
-
-async function getUsersCollection() {
-  const client = await getMongoClient();
-  const dbName = process.env.MONGODB_DB ?? "adventure";
-  return client.db(dbName).collection<UserRecord>("users");
-}
+import { recordAuditEvent } from "../audit";
+import { deliverToken } from "../authCookie";
+import { clearLoginFailures, loginNeedsCaptcha, recordLoginFailure } from "../captcha";
+import { consumeInvite, releaseInvite } from "../invites";
+import { clearFailedLogins, isLockedOut, recordFailedLogin, STEALTH_LOCKOUT } from "../lockout";
+import { emailSender } from "../email";
+import { notifyOnNewDevice } from "../loginNotifications";
+import { consumePasswordReset, findPasswordReset, requestPasswordReset } from "../passwordResets";
+import {
+  authRateLimiter,
+  emailCheckRateLimiter,
+  loginRateLimiter,
+  passwordResetRateLimiter,
+  requireAuth,
+  type AuthenticatedRequest,
+} from "../middleware/auth";
+import { nowDate } from "../utils/clock";
+import { sendError, sendRouteError } from "../utils/errors";
+import { createToken, parseExpiresIn } from "../utils/jwt";
+import { createPasswordHash, matchesAnyPassword, verifyPassword } from "../utils/password";
+import { grantedScopes, intersectScopes, parseScope } from "../utils/scope";
+import { checkPasswordPolicy, WEAK_PASSWORD_MESSAGE, weakPasswordDetails } from "../utils/passwordPolicy";
+import { isValidEmail } from "../utils/validation";
+import { parseBooleanEnv } from "../utils/env";
+import { requireCaptcha } from "../middleware/captcha";
+import { requireInternalToken } from "../middleware/internalAuth";
+import { jsonBody } from "../middleware/json";
+import { limitConcurrentLogins } from "../middleware/loginConcurrency";
+import { getRequestTenant } from "../middleware/tenant";
+import { revokeAllUserSessions, startSession } from "../sessions";
+import { isTosAcceptanceRequired, newTosAcceptance, TOS_CURRENT_VERSION, TOS_STRICT, TOS_URL } from "../tos";
+import {
+  ACCOUNT_DELETION_GRACE_DAYS,
+  DEFAULT_USER_ROLES,
+  getPasswordHistory,
+  getUserRoles,
+  getUsersCollection,
+  isDuplicateKeyError,
+  normalizeEmail,
+  PASSWORD_HISTORY_SIZE,
+  tenantFilter,
+} from "../users";
+
+export const TOKEN_EXCHANGE_EXPIRES_IN = parseExpiresIn(process.env.TOKEN_EXCHANGE_EXPIRES_IN, "5m");
+// Self-service registration; admins can still create users when it is off.
+export const REGISTRATION_ENABLED = parseBooleanEnv(process.env.REGISTRATION_ENABLED) ?? true;
+export const EMAIL_CHECK_ENABLED = parseBooleanEnv(process.env.EMAIL_CHECK_ENABLED) ?? true;
 
 const router = Router();
 
-router.post("/auth/register", authRateLimiter, async (req: Request, res: Response) => {
+router.post(
+  "/auth/register",
+  authRateLimiter,
+  jsonBody(["email", "password", "invite", "tosVersion", "captchaToken"]),
+  requireCaptcha(),
+  async (req: Request, res: Response) => {
   console.log("[POST /auth/register] Registration attempt");
   try {
-    const { email, password } = req.body ?? {};
+    const { email, password, invite, tosVersion } = req.body ?? {};
+    if (invite !== undefined && (typeof invite !== "string" || !invite)) {
+      console.log("[POST /auth/register] Malformed invite");
+      sendError(res, 400, "invalid_request", "Invite must be a non-empty string");
+      return;
+    }
+    // An invite lets a user in even when open registration is turned off.
+    if (!REGISTRATION_ENABLED && invite === undefined) {
+      console.log("[POST /auth/register] Registration is disabled");
+      sendError(res, 403, "registration_disabled", "Registration is disabled");
+      return;
+    }
     if (typeof email !== "string" || typeof password !== "string") {
       console.log("[POST /auth/register] Missing email or password");
-      res.status(400).json({ ok: false, error: "Email and password are required" });
+      sendError(res, 400, "missing_credentials", "Email and password are required");
       return;
     }
-    const normalizedEmail = email.trim().toLowerCase();
+    const normalizedEmail = normalizeEmail(email);
     if (!normalizedEmail || !isValidEmail(normalizedEmail)) {
       console.log("[POST /auth/register] Invalid email format");
-      res.status(400).json({ ok: false, error: "Valid email is required" });
+      sendError(res, 400, "invalid_email", "Valid email is required");
       return;
     }
-    if (!isStrongPassword(password)) {
+    const passwordCheck = checkPasswordPolicy(password, normalizedEmail);
+    if (!passwordCheck.accepted) {
       console.log("[POST /auth/register] Weak password rejected");
-      res.status(400).json({
-        ok: false,
-        error: `Password must be at least ${PASSWORD_MIN_LENGTH} chars and include upper/lower/number/symbol`,
+      sendError(res, 400, "weak_password", WEAK_PASSWORD_MESSAGE, weakPasswordDetails(passwordCheck));
+      return;
+    }
+    if (TOS_CURRENT_VERSION !== null && tosVersion !== TOS_CURRENT_VERSION) {
+      console.log("[POST /auth/register] Current terms of service not accepted");
+      sendError(res, 400, "tos_acceptance_required", "The current terms of service must be accepted", {
+        currentVersion: TOS_CURRENT_VERSION,
+        url: TOS_URL,
       });
       return;
     }
 
+    const tenantId = getRequestTenant(res);
     const users = await getUsersCollection();
-    const existing = await users.findOne({ email: normalizedEmail });
+    const existing = await users.findOne({ email: normalizedEmail, ...tenantFilter(tenantId) });
     if (existing) {
       console.log("[POST /auth/register] Duplicate email rejected");
-      res.status(409).json({ ok: false, error: "Email is already registered" });
+      sendError(res, 409, "email_taken", "Email is already registered");
       return;
     }
 
+    let roles = DEFAULT_USER_ROLES;
+    if (invite !== undefined) {
+      const consumed = await consumeInvite(invite, tenantId, normalizedEmail);
+      if (!consumed) {
+        console.log("[POST /auth/register] Invalid, used, or expired invite");
+        sendError(res, 403, "invalid_invite", "Invite is invalid, expired, or already used");
+        return;
+      }
+      roles = consumed.roles;
+    }
+
     const { salt, hash } = await createPasswordHash(password);
-    const result = await users.insertOne({
+    const result = await users
+      .insertOne({
+        tenantId,
+        email: normalizedEmail,
+        passwordHash: hash,
+        passwordSalt: salt,
+        roles,
+        ...(TOS_CURRENT_VERSION !== null ? { tosAcceptances: [newTosAcceptance(TOS_CURRENT_VERSION, req.ip)] } : {}),
+        createdAt: nowDate(),
+      })
+      .catch(async (error: unknown) => {
+        if (invite !== undefined) {
+          await releaseInvite(invite);
+        }
+        // A concurrent registration for the same email won the unique index.
+        if (isDuplicateKeyError(error)) {
+          return null;
+        }
+        throw error;
+      });
+    if (!result) {
+      console.log("[POST /auth/register] Duplicate email rejected");
+      sendError(res, 409, "email_taken", "Email is already registered");
+      return;
+    }
+    const { token, session } = await startSession(req, {
+      sub: result.insertedId.toHexString(),
       email: normalizedEmail,
-      passwordHash: hash,
-      passwordSalt: salt,
-      createdAt: new Date(),
+      roles,
+      tenantId,
     });
-    const token = createToken({ sub: result.insertedId.toHexString(), email: normalizedEmail });
+    // Seeds the known devices so the first login from the same device is quiet.
+    notifyOnNewDevice({ _id: result.insertedId, email: normalizedEmail, tenantId }, session);
     console.log("[POST /auth/register] User registered successfully");
     res.status(201).json({
       ok: true,
-      token,
-      user: { id: result.insertedId.toHexString(), email: normalizedEmail },
+      ...deliverToken(res, token, session.expiresAt),
+      user: { id: result.insertedId.toHexString(), email: normalizedEmail, roles },
     });
   } catch (error) {
     const message = error instanceof Error ? error.message : "Registration failed";
     console.error("[POST /auth/register] Error:", message);
-    res.status(500).json({ ok: false, error: message });
+    sendRouteError(res, error, message);
   }
-});
+  },
+);
+
+// Tells signup forms whether an email is taken. That is an enumeration
+// vector, so it has its own tight limit, answers nothing while open
+// registration is off, and can be turned off with EMAIL_CHECK_ENABLED=false.
+router.get(
+  "/auth/register/check-email",
+  emailCheckRateLimiter,
+  async (req: Request, res: Response) => {
+  console.log("[GET /auth/register/check-email] Availability check");
+  try {
+    if (!EMAIL_CHECK_ENABLED || !REGISTRATION_ENABLED) {
+      console.log("[GET /auth/register/check-email] Email check is disabled");
+      sendError(res, 403, "email_check_disabled", "Email availability checks are disabled");
+      return;
+    }
+    const email = typeof req.query.email === "string" ? normalizeEmail(req.query.email) : "";
+    if (!email || !isValidEmail(email)) {
+      console.log("[GET /auth/register/check-email] Invalid email format");
+      sendError(res, 400, "invalid_email", "Valid email is required");
+      return;
+    }
+
+    const users = await getUsersCollection();
+    const existing = await users.findOne({ email, ...tenantFilter(getRequestTenant(res)) }, { projection: { _id: 1 } });
+    res.setHeader("Cache-Control", "no-store");
+    res.status(200).json({ ok: true, available: !existing });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Email check failed";
+    console.error("[GET /auth/register/check-email] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
 
-router.post("/auth/login", authRateLimiter, async (req: Request, res: Response) => {
+router.post(
+  "/auth/login",
+  loginRateLimiter,
+  jsonBody(["email", "password", "bindIp", "rememberMe", "tosVersion", "captchaToken"]),
+  limitConcurrentLogins,
+  // Adaptive challenge: only IPs with repeated failed logins must solve one.
+  requireCaptcha((req) => loginNeedsCaptcha(req.ip)),
+  async (req: Request, res: Response) => {
   console.log("[POST /auth/login] Login attempt");
   try {
-    const { email, password } = req.body ?? {};
+    const { email, password, bindIp, rememberMe, tosVersion } = req.body ?? {};
     if (typeof email !== "string" || typeof password !== "string") {
       console.log("[POST /auth/login] Missing email or password");
-      res.status(400).json({ ok: false, error: "Email and password are required" });
+      sendError(res, 400, "missing_credentials", "Email and password are required");
       return;
     }
 
-    const normalizedEmail = email.trim().toLowerCase();
+    const normalizedEmail = normalizeEmail(email);
     if (!normalizedEmail || !isValidEmail(normalizedEmail)) {
       console.log("[POST /auth/login] Invalid email format");
-      res.status(400).json({ ok: false, error: "Valid email is required" });
+      sendError(res, 400, "invalid_email", "Valid email is required");
       return;
     }
+    const tenantId = getRequestTenant(res);
     const users = await getUsersCollection();
-    const user = await users.findOne({ email: normalizedEmail });
+    const user = await users.findOne({ email: normalizedEmail, ...tenantFilter(tenantId) });
     if (!user) {
       console.log("[POST /auth/login] Authentication failed");
-      res.status(401).json({ ok: false, error: "Invalid credentials" });
+      recordLoginFailure(req.ip);
+      void recordAuditEvent({ type: "login.failed", tenantId, ip: req.ip, details: { reason: "unknown_email" } });
+      sendError(res, 401, "invalid_credentials", "Invalid credentials");
+      return;
+    }
+
+    if (isLockedOut(user)) {
+      console.log("[POST /auth/login] Account locked");
+      recordLoginFailure(req.ip);
+      void recordAuditEvent({
+        type: "login.failed",
+        tenantId,
+        userId: user._id.toHexString(),
+        ip: req.ip,
+        details: { reason: "account_locked" },
+      });
+      if (STEALTH_LOCKOUT) {
+        // Same work and response as a wrong password; the result is ignored.
+        await verifyPassword(password, user.passwordSalt, user.passwordHash);
+        sendError(res, 401, "invalid_credentials", "Invalid credentials");
+        return;
+      }
+      sendError(res, 423, "account_locked", "Account is temporarily locked", { lockedUntil: user.lockedUntil });
       return;
     }
 
     const passwordMatches = await verifyPassword(password, user.passwordSalt, user.passwordHash);
     if (!passwordMatches) {
       console.log("[POST /auth/login] Authentication failed");
-      res.status(401).json({ ok: false, error: "Invalid credentials" });
+      recordLoginFailure(req.ip);
+      void recordAuditEvent({
+        type: "login.failed",
+        tenantId,
+        userId: user._id.toHexString(),
+        ip: req.ip,
+        details: { reason: "wrong_password" },
+      });
+      await recordFailedLogin(user, req.ip);
+      sendError(res, 401, "invalid_credentials", "Invalid credentials");
+      return;
+    }
+    // Only revealed to callers who know the password.
+    if (user.disabled) {
+      console.log("[POST /auth/login] Account disabled");
+      sendError(res, 403, "account_disabled", "Account is disabled");
       return;
     }
 
     const userId = user._id?.toHexString();
     if (!userId) {
       console.error("[POST /auth/login] User record missing id");
-      res.status(500).json({ ok: false, error: "User record is missing an id" });
+      sendError(res, 500, "internal_error", "User record is missing an id");
+      return;
+    }
+
+    // Passing the current version at login accepts it, which is the only way
+    // through in strict mode since no token is issued before acceptance.
+    let tosAcceptanceRequired = isTosAcceptanceRequired(user);
+    if (tosAcceptanceRequired && TOS_CURRENT_VERSION !== null && tosVersion === TOS_CURRENT_VERSION) {
+      await users.updateOne(
+        { _id: user._id },
+        { $push: { tosAcceptances: newTosAcceptance(TOS_CURRENT_VERSION, req.ip) } },
+      );
+      tosAcceptanceRequired = false;
+    }
+    if (tosAcceptanceRequired && TOS_STRICT) {
+      console.log("[POST /auth/login] Terms of service acceptance required");
+      sendError(res, 403, "tos_acceptance_required", "The current terms of service must be accepted", {
+        currentVersion: TOS_CURRENT_VERSION,
+        url: TOS_URL,
+      });
       return;
     }
 
-    const token = createToken({ sub: userId, email: user.email });
+    const roles = getUserRoles(user);
+    const { token, session } = await startSession(
+      req,
+      { sub: userId, email: user.email, roles, tenantId },
+      user.claims,
+      { bindIp: bindIp === true ? true : undefined, rememberMe: rememberMe === true },
+    );
+    clearLoginFailures(req.ip);
+    await clearFailedLogins(user);
+    notifyOnNewDevice(user, session);
     console.log("[POST /auth/login] Login successful");
     res.status(200).json({
       ok: true,
-      token,
-      user: { id: userId, email: user.email },
+      ...deliverToken(res, token, session.expiresAt),
+      expiresAt: session.expiresAt,
+      user: { id: userId, email: user.email, roles },
+      tosAcceptanceRequired,
+      ...(user.deletionScheduledAt
+        ? { warning: "pending_deletion", deletionScheduledAt: user.deletionScheduledAt }
+        : {}),
     });
   } catch (error) {
     const message = error instanceof Error ? error.message : "Login failed";
     console.error("[POST /auth/login] Error:", message);
-    res.status(500).json({ ok: false, error: message });
+    sendRouteError(res, error, message);
   }
-});
+  },
+);
 
 router.get(
   "/auth/me",
@@ -136,34 +337,518 @@ router.get(
   try {
     if (!req.user) {
       console.log("[GET /auth/me] Unauthorized - no user in request");
-      res.status(401).json({ ok: false, error: "Unauthorized" });
+      sendError(res, 401, "unauthorized", "Unauthorized");
       return;
     }
 
     const users = await getUsersCollection();
     const user = await users.findOne(
-      { _id: new ObjectId(req.user.sub) },
-      { projection: { passwordHash: 0, passwordSalt: 0 } },
+      { _id: new ObjectId(req.user.sub), ...tenantFilter(req.user.tenantId) },
+      { projection: { passwordHash: 0, passwordSalt: 0, passwordHistory: 0, knownDevices: 0, knownCountries: 0 } },
     );
     if (!user) {
       console.log("[GET /auth/me] User not found in database");
-      res.status(404).json({ ok: false, error: "User not found" });
+      sendError(res, 404, "user_not_found", "User not found");
       return;
     }
 
     const userId = user._id?.toHexString();
     if (!userId) {
       console.error("[GET /auth/me] User record missing id");
-      res.status(500).json({ ok: false, error: "User record is missing an id" });
+      sendError(res, 500, "internal_error", "User record is missing an id");
       return;
     }
 
     console.log("[GET /auth/me] User profile retrieved successfully");
-    res.status(200).json({ ok: true, user: { id: userId, email: user.email } });
+    res.status(200).json({
+      ok: true,
+      user: { id: userId, email: user.email, roles: getUserRoles(user), tenantId: req.user.tenantId },
+      claims: req.user.extra ?? {},
+      preferences: { notifyNewDevice: user.preferences?.notifyNewDevice !== false },
+      tos: {
+        currentVersion: TOS_CURRENT_VERSION,
+        acceptanceRequired: isTosAcceptanceRequired(user),
+        acceptances: user.tosAcceptances ?? [],
+      },
+    });
   } catch (error) {
     const message = error instanceof Error ? error.message : "Failed to load user";
     console.error("[GET /auth/me] Error:", message);
-    res.status(500).json({ ok: false, error: message });
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// Cheap token check for other services: signature, expiry, tenant, and session
+// are verified by requireAuth without touching the users collection. Not rate
+// limited per IP since callers are typically a few gateway instances, which
+// identify themselves with an internal token once INTERNAL_JWT_SECRET is set.
+router.get("/auth/validate", requireInternalToken, requireAuth, (req: AuthenticatedRequest, res: Response) => {
+  if (!req.user) {
+    sendError(res, 401, "unauthorized", "Unauthorized");
+    return;
+  }
+  res.setHeader("Cache-Control", "no-store");
+  res.status(200).json({ ok: true, sub: req.user.sub, email: req.user.email, roles: req.user.roles });
+});
+
+router.patch(
+  "/auth/me/preferences",
+  authRateLimiter,
+  requireAuth,
+  jsonBody(["notifyNewDevice"]),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[PATCH /auth/me/preferences] Preference update requested");
+  try {
+    if (!req.user) {
+      console.log("[PATCH /auth/me/preferences] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const { notifyNewDevice } = req.body ?? {};
+    if (typeof notifyNewDevice !== "boolean") {
+      console.log("[PATCH /auth/me/preferences] Invalid preferences");
+      sendError(res, 400, "invalid_request", "notifyNewDevice must be a boolean");
+      return;
+    }
+
+    const users = await getUsersCollection();
+    const result = await users.updateOne(
+      { _id: new ObjectId(req.user.sub), ...tenantFilter(req.user.tenantId) },
+      { $set: { "preferences.notifyNewDevice": notifyNewDevice } },
+    );
+    if (result.matchedCount === 0) {
+      console.log("[PATCH /auth/me/preferences] User not found in database");
+      sendError(res, 404, "user_not_found", "User not found");
+      return;
+    }
+    console.log("[PATCH /auth/me/preferences] Preferences updated");
+    res.status(200).json({ ok: true, preferences: { notifyNewDevice } });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Preference update failed";
+    console.error("[PATCH /auth/me/preferences] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// Deletion is deferred: the account keeps working (and its email stays taken)
+// until the grace period ends and the maintenance task purges it.
+router.delete(
+  "/auth/me",
+  authRateLimiter,
+  requireAuth,
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[DELETE /auth/me] Account deletion requested");
+  try {
+    if (!req.user) {
+      console.log("[DELETE /auth/me] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const users = await getUsersCollection();
+    const filter = { _id: new ObjectId(req.user.sub), ...tenantFilter(req.user.tenantId) };
+    const user = await users.findOne(filter, { projection: { deletionScheduledAt: 1 } });
+    if (!user) {
+      console.log("[DELETE /auth/me] User not found in database");
+      sendError(res, 404, "user_not_found", "User not found");
+      return;
+    }
+    if (user.deletionScheduledAt) {
+      console.log("[DELETE /auth/me] Deletion already scheduled");
+      res.status(200).json({ ok: true, deletionScheduledAt: user.deletionScheduledAt });
+      return;
+    }
+
+    const now = nowDate();
+    const deletionScheduledAt = new Date(now.getTime() + ACCOUNT_DELETION_GRACE_DAYS * 24 * 60 * 60 * 1000);
+    await users.updateOne(filter, { $set: { deletionRequestedAt: now, deletionScheduledAt } });
+    await recordAuditEvent({
+      type: "account.deletion_requested",
+      tenantId: req.user.tenantId,
+      userId: req.user.sub,
+      ip: req.ip,
+      details: { deletionScheduledAt },
+    });
+    console.log("[DELETE /auth/me] Deletion scheduled");
+    res.status(200).json({ ok: true, deletionScheduledAt });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Account deletion failed";
+    console.error("[DELETE /auth/me] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.post(
+  "/auth/me/cancel-deletion",
+  authRateLimiter,
+  requireAuth,
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /auth/me/cancel-deletion] Deletion cancellation requested");
+  try {
+    if (!req.user) {
+      console.log("[POST /auth/me/cancel-deletion] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const users = await getUsersCollection();
+    const result = await users.updateOne(
+      {
+        _id: new ObjectId(req.user.sub),
+        ...tenantFilter(req.user.tenantId),
+        deletionScheduledAt: { $exists: true },
+      },
+      { $unset: { deletionRequestedAt: "", deletionScheduledAt: "" } },
+    );
+    if (result.matchedCount === 0) {
+      console.log("[POST /auth/me/cancel-deletion] No pending deletion");
+      sendError(res, 409, "deletion_not_pending", "Account is not pending deletion");
+      return;
+    }
+
+    await recordAuditEvent({
+      type: "account.deletion_cancelled",
+      tenantId: req.user.tenantId,
+      userId: req.user.sub,
+      ip: req.ip,
+    });
+    console.log("[POST /auth/me/cancel-deletion] Deletion cancelled");
+    res.status(200).json({ ok: true });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Deletion cancellation failed";
+    console.error("[POST /auth/me/cancel-deletion] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.get("/tos", (_req: Request, res: Response) => {
+  res.status(200).json({ ok: true, version: TOS_CURRENT_VERSION, url: TOS_URL, strict: TOS_STRICT });
+});
+
+router.post(
+  "/auth/me/accept-tos",
+  authRateLimiter,
+  requireAuth,
+  jsonBody(["version"]),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /auth/me/accept-tos] Terms of service acceptance requested");
+  try {
+    if (!req.user) {
+      console.log("[POST /auth/me/accept-tos] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+    if (TOS_CURRENT_VERSION === null) {
+      console.log("[POST /auth/me/accept-tos] Terms of service not configured");
+      sendError(res, 404, "tos_not_configured", "Terms of service are not configured");
+      return;
+    }
+
+    const { version } = req.body ?? {};
+    if (version !== TOS_CURRENT_VERSION) {
+      console.log("[POST /auth/me/accept-tos] Version mismatch");
+      sendError(res, 400, "tos_version_mismatch", "Only the current terms of service version can be accepted", {
+        currentVersion: TOS_CURRENT_VERSION,
+      });
+      return;
+    }
+
+    // Acceptances are appended so the full history (version, time, IP) is kept.
+    const users = await getUsersCollection();
+    const result = await users.updateOne(
+      { _id: new ObjectId(req.user.sub), ...tenantFilter(req.user.tenantId) },
+      { $push: { tosAcceptances: newTosAcceptance(TOS_CURRENT_VERSION, req.ip) } },
+    );
+    if (result.matchedCount === 0) {
+      console.log("[POST /auth/me/accept-tos] User not found in database");
+      sendError(res, 404, "user_not_found", "User not found");
+      return;
+    }
+    console.log("[POST /auth/me/accept-tos] Terms of service accepted");
+    res.status(200).json({ ok: true, version: TOS_CURRENT_VERSION });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Terms of service acceptance failed";
+    console.error("[POST /auth/me/accept-tos] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.post(
+  "/auth/change-password",
+  authRateLimiter,
+  requireAuth,
+  jsonBody(["currentPassword", "newPassword"]),
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /auth/change-password] Password change requested");
+  try {
+    if (!req.user) {
+      console.log("[POST /auth/change-password] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const { currentPassword, newPassword } = req.body ?? {};
+    if (typeof currentPassword !== "string" || typeof newPassword !== "string") {
+      console.log("[POST /auth/change-password] Missing current or new password");
+      sendError(res, 400, "invalid_request", "Current and new password are required");
+      return;
+    }
+    const passwordCheck = checkPasswordPolicy(newPassword, req.user.email);
+    if (!passwordCheck.accepted) {
+      console.log("[POST /auth/change-password] Weak password rejected");
+      sendError(res, 400, "weak_password", WEAK_PASSWORD_MESSAGE, weakPasswordDetails(passwordCheck));
+      return;
+    }
+
+    const users = await getUsersCollection();
+    const user = await users.findOne({ _id: new ObjectId(req.user.sub), ...tenantFilter(req.user.tenantId) });
+    if (!user) {
+      console.log("[POST /auth/change-password] User not found in database");
+      sendError(res, 404, "user_not_found", "User not found");
+      return;
+    }
+
+    const currentMatches = await verifyPassword(currentPassword, user.passwordSalt, user.passwordHash);
+    if (!currentMatches) {
+      console.log("[POST /auth/change-password] Current password mismatch");
+      sendError(res, 401, "invalid_credentials", "Invalid credentials");
+      return;
+    }
+
+    const history = getPasswordHistory(user);
+    if (PASSWORD_HISTORY_SIZE > 0 && (await matchesAnyPassword(newPassword, history))) {
+      console.log("[POST /auth/change-password] Reused password rejected");
+      sendError(res, 422, "password_reused", "Password was used recently, choose a different one", {
+        historySize: PASSWORD_HISTORY_SIZE,
+      });
+      return;
+    }
+
+    const { salt, hash } = await createPasswordHash(newPassword);
+    await users.updateOne(
+      { _id: user._id },
+      {
+        $set: {
+          passwordHash: hash,
+          passwordSalt: salt,
+          passwordHistory: history.slice(0, PASSWORD_HISTORY_SIZE),
+        },
+      },
+    );
+    console.log("[POST /auth/change-password] Password changed successfully");
+    res.status(200).json({ ok: true });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Password change failed";
+    console.error("[POST /auth/change-password] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// Password reset only works by email, so without a transport that actually
+// delivers (EMAIL_HTTP_URL) both steps are refused rather than faking success.
+function requireEmailDelivery(_req: Request, res: Response, next: NextFunction) {
+  if (!emailSender.enabled) {
+    sendError(res, 501, "email_not_configured", "Password reset is unavailable: email delivery is not configured");
+    return;
+  }
+  next();
+}
+
+// Same answer whether or not the account exists; the lookup and email happen
+// after the response so timing does not tell either.
+router.post(
+  "/auth/forgot-password",
+  requireEmailDelivery,
+  passwordResetRateLimiter,
+  jsonBody(["email"]),
+  (req: Request, res: Response) => {
+  console.log("[POST /auth/forgot-password] Password reset requested");
+  try {
+    const { email } = req.body ?? {};
+    const normalizedEmail = typeof email === "string" ? normalizeEmail(email) : "";
+    if (!normalizedEmail || !isValidEmail(normalizedEmail)) {
+      console.log("[POST /auth/forgot-password] Invalid email format");
+      sendError(res, 400, "invalid_email", "Valid email is required");
+      return;
+    }
+
+    requestPasswordReset(normalizedEmail, getRequestTenant(res), req.ip).catch((error: unknown) => {
+      const message = error instanceof Error ? error.message : String(error);
+      console.error("[POST /auth/forgot-password] Reset failed:", message);
+    });
+    res.status(200).json({ ok: true });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Password reset request failed";
+    console.error("[POST /auth/forgot-password] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+router.post(
+  "/auth/reset-password",
+  requireEmailDelivery,
+  authRateLimiter,
+  jsonBody(["token", "newPassword"]),
+  async (req: Request, res: Response) => {
+  console.log("[POST /auth/reset-password] Password reset attempt");
+  try {
+    const { token, newPassword } = req.body ?? {};
+    if (typeof token !== "string" || !token || typeof newPassword !== "string") {
+      console.log("[POST /auth/reset-password] Missing token or new password");
+      sendError(res, 400, "invalid_request", "Token and new password are required");
+      return;
+    }
+
+    // The token is only looked up here and consumed once the new password has
+    // passed every check, so a rejected password does not burn it.
+    const tenantId = getRequestTenant(res);
+    const pending = await findPasswordReset(token, tenantId);
+    const users = await getUsersCollection();
+    const user = pending
+      ? await users.findOne({ _id: new ObjectId(pending.userId), ...tenantFilter(tenantId) })
+      : null;
+    if (!user) {
+      console.log("[POST /auth/reset-password] Invalid reset token");
+      sendError(res, 400, "invalid_reset_token", "Reset token is invalid, expired, or already used");
+      return;
+    }
+
+    const passwordCheck = checkPasswordPolicy(newPassword, user.email);
+    if (!passwordCheck.accepted) {
+      console.log("[POST /auth/reset-password] Weak password rejected");
+      sendError(res, 400, "weak_password", WEAK_PASSWORD_MESSAGE, weakPasswordDetails(passwordCheck));
+      return;
+    }
+
+    // Provisioned users have no password yet; skip their empty entry.
+    const history = getPasswordHistory(user).filter((entry) => entry.hash);
+    if (PASSWORD_HISTORY_SIZE > 0 && (await matchesAnyPassword(newPassword, history))) {
+      console.log("[POST /auth/reset-password] Reused password rejected");
+      sendError(res, 422, "password_reused", "Password was used recently, choose a different one", {
+        historySize: PASSWORD_HISTORY_SIZE,
+      });
+      return;
+    }
+
+    // A concurrent request may have used the token since the lookup.
+    const reset = await consumePasswordReset(token, tenantId);
+    if (!reset) {
+      console.log("[POST /auth/reset-password] Reset token used concurrently");
+      sendError(res, 400, "invalid_reset_token", "Reset token is invalid, expired, or already used");
+      return;
+    }
+
+    const { salt, hash } = await createPasswordHash(newPassword);
+    await users.updateOne(
+      { _id: user._id },
+      {
+        $set: {
+          passwordHash: hash,
+          passwordSalt: salt,
+          passwordHistory: history.slice(0, PASSWORD_HISTORY_SIZE),
+        },
+        $unset: { failedLoginCount: "", lockedUntil: "" },
+      },
+    );
+    await revokeAllUserSessions(reset.userId, tenantId);
+    await recordAuditEvent({ type: "password_reset.completed", tenantId, userId: reset.userId, ip: req.ip });
+    console.log("[POST /auth/reset-password] Password reset successfully");
+    res.status(200).json({ ok: true });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Password reset failed";
+    console.error("[POST /auth/reset-password] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// Lets the frontend show live feedback from the real policy. The candidate
+// password is neither stored nor logged.
+router.post(
+  "/auth/password/strength",
+  authRateLimiter,
+  jsonBody(["password", "email"]),
+  (req: Request, res: Response) => {
+  try {
+    const { password, email } = req.body ?? {};
+    if (typeof password !== "string" || (email !== undefined && typeof email !== "string")) {
+      sendError(res, 400, "invalid_request", "Password must be a string, and email a string if given");
+      return;
+    }
+    const result = checkPasswordPolicy(password, email === undefined ? undefined : normalizeEmail(email));
+    res.setHeader("Cache-Control", "no-store");
+    res.status(200).json({ ok: true, ...result });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Password check failed";
+    console.error("[POST /auth/password/strength] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// RFC 8693-style exchange: trade the presented access token for a short-lived
+// token bound to one audience and a subset of the original scopes.
+router.post(
+  "/auth/token/exchange",
+  authRateLimiter,
+  requireAuth,
+  jsonBody(["audience", "scope"]),
+  (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /auth/token/exchange] Token exchange requested");
+  try {
+    if (!req.user) {
+      console.log("[POST /auth/token/exchange] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const { audience, scope } = req.body ?? {};
+    if (typeof audience !== "string" || !audience.trim()) {
+      console.log("[POST /auth/token/exchange] Missing audience");
+      sendError(res, 400, "invalid_request", "Audience is required");
+      return;
+    }
+    const granted = grantedScopes(req.user);
+    const requested = scope === undefined ? granted : parseScope(scope);
+    if (!requested || requested.length === 0) {
+      console.log("[POST /auth/token/exchange] Invalid scope format");
+      sendError(res, 400, "invalid_request", "Scope must be a non-empty space-separated string or array");
+      return;
+    }
+
+    const { allowed, denied } = intersectScopes(granted, requested);
+    if (denied.length > 0) {
+      console.log("[POST /auth/token/exchange] Scope escalation rejected:", denied);
+      sendError(res, 403, "invalid_scope", "Requested scope exceeds the presented token", { denied });
+      return;
+    }
+
+    const token = createToken(
+      { ...req.user, scope: allowed, audience: audience.trim() },
+      {},
+      { expiresIn: TOKEN_EXCHANGE_EXPIRES_IN },
+    );
+    console.log("[POST /auth/token/exchange] Token issued for audience:", audience.trim());
+    res.status(200).json({
+      ok: true,
+      access_token: token,
+      issued_token_type: "urn:ietf:params:oauth:token-type:jwt",
+      token_type: "Bearer",
+      audience: audience.trim(),
+      scope: allowed.join(" "),
+    });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Token exchange failed";
+    console.error("[POST /auth/token/exchange] Error:", message);
+    sendRouteError(res, error, message);
   }
   },
 );
@@ -0,0 +1,103 @@
+import { Router, type Request, type Response } from "express";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { jsonBody } from "../middleware/json";
+import { findActiveSession } from "../sessions";
+import { nowUnix } from "../utils/clock";
+import { parseBooleanEnv } from "../utils/env";
+import { sendError, sendRouteError } from "../utils/errors";
+import { parseAuthPayload, verifyJwt } from "../utils/jwt";
+
+type TokenValidation =
+  | { valid: true }
+  | { valid: false; reason: string; message: string; expiredAt?: string };
+
+function validateToken(token: string): TokenValidation {
+  let decoded: string | JwtPayload;
+  try {
+    decoded = verifyJwt(token, { clockTimestamp: nowUnix() });
+  } catch (error) {
+    if (error instanceof jwt.TokenExpiredError) {
+      return { valid: false, reason: "expired", message: error.message, expiredAt: error.expiredAt.toISOString() };
+    }
+    if (error instanceof jwt.NotBeforeError) {
+      return { valid: false, reason: "not_yet_valid", message: error.message };
+    }
+    if (error instanceof jwt.JsonWebTokenError) {
+      const reason = error.message === "invalid signature" ? "bad_signature" : "malformed";
+      return { valid: false, reason, message: error.message };
+    }
+    throw error;
+  }
+
+  try {
+    parseAuthPayload(decoded);
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Invalid token payload";
+    return { valid: false, reason: "missing_claim", message };
+  }
+  return { valid: true };
+}
+
+type SessionStatus = "active" | "revoked" | "unknown";
+
+// Only looked up for tokens that verified and name a session (jti); a failed
+// lookup is reported as unknown rather than failing the inspection.
+async function lookupSessionStatus(claims: string | JwtPayload, validation: TokenValidation): Promise<SessionStatus> {
+  if (!validation.valid || typeof claims === "string" || typeof claims.jti !== "string") {
+    return "unknown";
+  }
+  try {
+    return (await findActiveSession(claims.jti, claims.exp)) ? "active" : "revoked";
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    console.error("[POST /debug/token] Session lookup failed:", message);
+    return "unknown";
+  }
+}
+
+// Off unless explicitly enabled; when off the routes are not registered at all.
+export const DEBUG_ENDPOINTS_ENABLED = parseBooleanEnv(process.env.DEBUG_ENDPOINTS) === true;
+
+const router = Router();
+
+// Only mounted when DEBUG_ENDPOINTS=true (see app.ts). The token is never
+// logged; only the outcome of the inspection is.
+router.post("/debug/token", jsonBody(["token"]), async (req: Request, res: Response) => {
+  console.log("[POST /debug/token] Token inspection requested");
+  const { token } = req.body ?? {};
+  if (typeof token !== "string" || !token) {
+    console.log("[POST /debug/token] Missing token");
+    sendError(res, 400, "invalid_request", "Token is required");
+    return;
+  }
+
+  const decoded = jwt.decode(token, { complete: true });
+  if (!decoded) {
+    console.log("[POST /debug/token] Token could not be decoded");
+    res.status(200).json({
+      ok: true,
+      header: null,
+      claims: null,
+      validation: { valid: false, reason: "malformed", message: "Token could not be decoded" },
+      session: "unknown",
+    });
+    return;
+  }
+
+  try {
+    const validation = validateToken(token);
+    const session = await lookupSessionStatus(decoded.payload, validation);
+    console.log("[POST /debug/token] Token inspected:", {
+      valid: validation.valid,
+      reason: validation.valid ? undefined : validation.reason,
+      session,
+    });
+    res.status(200).json({ ok: true, header: decoded.header, claims: decoded.payload, validation, session });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Token inspection failed";
+    console.error("[POST /debug/token] Error:", message);
+    sendRouteError(res, error, message);
+  }
+});
+
+export default router;
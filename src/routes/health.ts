@@ -1,5 +1,8 @@
 import { Router, type Request, type Response } from "express";
-import { checkMongoHealth, isTlsError } from "../db";
+import { checkMongoHealth, getDbCommandStats, getDbPoolStats, isTlsError } from "../db";
+import { isReady } from "../readiness";
+import { sendError } from "../utils/errors";
+import { getOutboundStats } from "../utils/http";
 
 const router = Router();
 
@@ -8,7 +11,39 @@ router.get("/", (_req: Request, res: Response) => {
   res.status(200).json({
     ok: true,
     service: "adventure-api",
-    endpoints: ["/healthz", "/db/healthz", "/auth/register", "/auth/login", "/auth/me"],
+    endpoints: [
+      "/healthz",
+      "/healthz/ready",
+      "/db/healthz",
+      "/auth/register",
+      "/auth/register/check-email",
+      "/auth/login",
+      "/auth/me",
+      "/auth/validate",
+      "/auth/me/cancel-deletion",
+      "/auth/me/accept-tos",
+      "/auth/me/preferences",
+      "/tos",
+      "/auth/change-password",
+      "/auth/forgot-password",
+      "/auth/reset-password",
+      "/auth/password/strength",
+      "/auth/token/exchange",
+      "/auth/sessions",
+      "/auth/sessions/current",
+      "/auth/session/extend",
+      "/auth/token-age",
+      "/auth/status",
+      "/auth/heartbeat",
+      "/admin/users/bulk",
+      "/admin/users/import",
+      "/admin/users/:id/sessions",
+      "/admin/invites",
+      "/admin/impersonate/:id",
+      "/admin/stats",
+      "/admin/config",
+      "/admin/debug/captures",
+    ],
   });
 });
 
@@ -18,7 +53,16 @@ router.get("/favicon.ico", (_req: Request, res: Response) => {
 
 router.get("/healthz", (_req: Request, res: Response) => {
   console.log("[GET /healthz] Health check requested");
-  res.status(200).json({ ok: true, service: "api", uptime: process.uptime() });
+  res.status(200).json({ ok: true, service: "api", uptime: process.uptime(), outbound: getOutboundStats() });
+});
+
+router.get("/healthz/ready", (_req: Request, res: Response) => {
+  if (!isReady()) {
+    console.log("[GET /healthz/ready] Not ready yet");
+    sendError(res, 503, "not_ready", "Service is still starting up");
+    return;
+  }
+  res.status(200).json({ ok: true, ready: true });
 });
 
 router.get("/db/healthz", async (_req: Request, res: Response) => {
@@ -26,7 +70,7 @@ router.get("/db/healthz", async (_req: Request, res: Response) => {
   try {
     await checkMongoHealth();
     console.log("[GET /db/healthz] DB health check passed");
-    res.status(200).json({ ok: true, db: "mongodb" });
+    res.status(200).json({ ok: true, db: "mongodb", commands: getDbCommandStats(), pool: getDbPoolStats() });
   } catch (error) {
     const err = error instanceof Error ? error : new Error(String(error));
     const code = (err as NodeJS.ErrnoException).code ?? undefined;
@@ -40,22 +84,18 @@ router.get("/db/healthz", async (_req: Request, res: Response) => {
       stack: err.stack,
     });
 
-    const body: Record<string, unknown> = {
-      ok: false,
-      db: "mongodb",
-      error: err.message,
-    };
+    const details: Record<string, unknown> = { db: "mongodb" };
 
     if (code) {
-      body.code = code;
+      details.driverCode = code;
     }
 
     if (tlsError) {
-      body.hint =
+      details.hint =
         "TLS/SSL error — check MONGODB_TLS_ALLOW_INVALID_CERTIFICATES, MONGODB_TLS_ALLOW_INVALID_HOSTNAMES, and MONGODB_TLS_CA_FILE env vars";
     }
 
-    res.status(503).json(body);
+    sendError(res, 503, "db_unavailable", err.message, details);
   }
 });
 
@@ -0,0 +1,282 @@
+import { Router, type Request, type Response, type NextFunction } from "express";
+import { ObjectId, type WithId } from "mongodb";
+import { recordAuditEvent } from "../audit";
+import { getRequestTenant } from "../middleware/tenant";
+import { revokeAllUserSessions } from "../sessions";
+import { nowDate } from "../utils/clock";
+import { matchesAnySecret, readSecretList } from "../utils/secrets";
+import { isValidEmail } from "../utils/validation";
+import {
+  DEFAULT_USER_ROLES,
+  getUsersCollection,
+  isDuplicateKeyError,
+  normalizeEmail,
+  tenantFilter,
+  type UserRecord,
+} from "../users";
+
+// Minimal SCIM 2.0 (RFC 7643/7644) user provisioning for enterprise IdPs.
+// Only mounted when SCIM_TOKEN is set (see app.ts); the IdP authenticates
+// with one of its tokens as its bearer token.
+export const SCIM_TOKENS = readSecretList("SCIM_TOKEN");
+
+const USER_SCHEMA = "urn:ietf:params:scim:schemas:core:2.0:User";
+const LIST_SCHEMA = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+const PATCH_SCHEMA = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+const ERROR_SCHEMA = "urn:ietf:params:scim:api:messages:2.0:Error";
+const PAGE_SIZE_DEFAULT = 100;
+const PAGE_SIZE_MAX = 200;
+// The one filter supported: userName eq "value".
+const USERNAME_FILTER_PATTERN = /^userName\s+eq\s+"([^"]*)"$/i;
+
+const router = Router();
+
+// SCIM clients expect the protocol's own error shape instead of our envelope.
+function sendScimError(res: Response, status: number, detail: string, scimType?: string) {
+  res
+    .status(status)
+    .type("application/scim+json")
+    .json({ schemas: [ERROR_SCHEMA], status: String(status), detail, ...(scimType ? { scimType } : {}) });
+}
+
+function sendScim(res: Response, status: number, body: unknown) {
+  res.status(status).type("application/scim+json").json(body);
+}
+
+function requireScimToken(req: Request, res: Response, next: NextFunction) {
+  const authHeader = req.headers.authorization;
+  const presented = authHeader?.startsWith("Bearer ") ? authHeader.slice("Bearer ".length) : null;
+  if (!presented || !matchesAnySecret(presented, SCIM_TOKENS)) {
+    sendScimError(res, 401, "Invalid provisioning token");
+    return;
+  }
+  next();
+}
+
+router.use("/scim/v2", requireScimToken);
+
+function toScimUser(req: Request, user: WithId<UserRecord>) {
+  const id = user._id.toHexString();
+  return {
+    schemas: [USER_SCHEMA],
+    id,
+    ...(user.externalId ? { externalId: user.externalId } : {}),
+    userName: user.email,
+    active: user.disabled !== true,
+    emails: [{ value: user.email, primary: true }],
+    meta: {
+      resourceType: "User",
+      created: user.createdAt,
+      lastModified: user.updatedAt ?? user.createdAt,
+      location: `${req.protocol}://${req.get("host")}/scim/v2/Users/${id}`,
+    },
+  };
+}
+
+async function findScimUser(id: string, tenantId: string) {
+  if (!ObjectId.isValid(id)) {
+    return null;
+  }
+  const users = await getUsersCollection();
+  return users.findOne({ _id: new ObjectId(id), ...tenantFilter(tenantId) });
+}
+
+// Okta sends either { path: "active", value: false } or { value: { active: false } }.
+function readActiveOperation(operation: unknown): boolean | null {
+  if (typeof operation !== "object" || operation === null) {
+    return null;
+  }
+  const { op, path, value } = operation as { op?: unknown; path?: unknown; value?: unknown };
+  if (typeof op !== "string" || !["replace", "add"].includes(op.toLowerCase())) {
+    return null;
+  }
+  if (path === "active") {
+    return typeof value === "boolean" ? value : null;
+  }
+  if (path === undefined && typeof value === "object" && value !== null) {
+    const active = (value as { active?: unknown }).active;
+    return typeof active === "boolean" ? active : null;
+  }
+  return null;
+}
+
+router.post("/scim/v2/Users", async (req: Request, res: Response) => {
+  console.log("[POST /scim/v2/Users] Provisioning requested");
+  try {
+    const { userName, externalId, active } = req.body ?? {};
+    const email = typeof userName === "string" ? normalizeEmail(userName) : "";
+    if (!email || !isValidEmail(email)) {
+      sendScimError(res, 400, "userName must be a valid email address", "invalidValue");
+      return;
+    }
+    if (externalId !== undefined && typeof externalId !== "string") {
+      sendScimError(res, 400, "externalId must be a string", "invalidValue");
+      return;
+    }
+
+    const tenantId = getRequestTenant(res);
+    const users = await getUsersCollection();
+    // Fast path; the unique email index settles races between parallel retries.
+    if (await users.findOne({ email, ...tenantFilter(tenantId) })) {
+      sendScimError(res, 409, "User already exists", "uniqueness");
+      return;
+    }
+    // No password: the user sets one through the password reset flow.
+    const record: UserRecord = {
+      tenantId,
+      email,
+      passwordHash: "",
+      passwordSalt: "",
+      roles: DEFAULT_USER_ROLES,
+      ...(externalId ? { externalId } : {}),
+      ...(active === false ? { disabled: true } : {}),
+      createdAt: nowDate(),
+    };
+    const result = await users.insertOne(record).catch((error: unknown) => {
+      if (isDuplicateKeyError(error)) {
+        return null;
+      }
+      throw error;
+    });
+    if (!result) {
+      sendScimError(res, 409, "User already exists", "uniqueness");
+      return;
+    }
+    const userId = result.insertedId.toHexString();
+    await recordAuditEvent({ type: "scim.user_created", tenantId, userId, ip: req.ip, details: { externalId } });
+    console.log("[POST /scim/v2/Users] User provisioned");
+    sendScim(res, 201, toScimUser(req, { ...record, _id: result.insertedId }));
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Provisioning failed";
+    console.error("[POST /scim/v2/Users] Error:", message);
+    sendScimError(res, 500, "Internal server error");
+  }
+});
+
+router.get("/scim/v2/Users", async (req: Request, res: Response) => {
+  console.log("[GET /scim/v2/Users] User list requested");
+  try {
+    const filterParam = typeof req.query.filter === "string" ? req.query.filter.trim() : undefined;
+    const match = filterParam ? USERNAME_FILTER_PATTERN.exec(filterParam) : null;
+    if (filterParam && !match) {
+      sendScimError(res, 400, 'Only filters of the form userName eq "value" are supported', "invalidFilter");
+      return;
+    }
+    const startIndex = Math.max(1, Number.parseInt(String(req.query.startIndex ?? "1"), 10) || 1);
+    const requestedCount = Number.parseInt(String(req.query.count ?? PAGE_SIZE_DEFAULT), 10);
+    const count = Math.min(Math.max(0, Number.isNaN(requestedCount) ? PAGE_SIZE_DEFAULT : requestedCount), PAGE_SIZE_MAX);
+
+    const tenantId = getRequestTenant(res);
+    const filter = match ? { email: normalizeEmail(match[1]), ...tenantFilter(tenantId) } : tenantFilter(tenantId);
+    const users = await getUsersCollection();
+    // count=0 asks for the total only; limit(0) would mean no limit.
+    const [totalResults, page] = await Promise.all([
+      users.countDocuments(filter),
+      count === 0
+        ? Promise.resolve([])
+        : users
+            .find(filter)
+            .sort({ _id: 1 })
+            .skip(startIndex - 1)
+            .limit(count)
+            .toArray(),
+    ]);
+    sendScim(res, 200, {
+      schemas: [LIST_SCHEMA],
+      totalResults,
+      startIndex,
+      itemsPerPage: page.length,
+      Resources: page.map((user) => toScimUser(req, user)),
+    });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Failed to list users";
+    console.error("[GET /scim/v2/Users] Error:", message);
+    sendScimError(res, 500, "Internal server error");
+  }
+});
+
+router.get("/scim/v2/Users/:id", async (req: Request, res: Response) => {
+  try {
+    const user = await findScimUser(req.params.id, getRequestTenant(res));
+    if (!user) {
+      sendScimError(res, 404, "User not found");
+      return;
+    }
+    sendScim(res, 200, toScimUser(req, user));
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Failed to load user";
+    console.error("[GET /scim/v2/Users/:id] Error:", message);
+    sendScimError(res, 500, "Internal server error");
+  }
+});
+
+// Only the active flag can be patched. Deactivating revokes every session.
+router.patch("/scim/v2/Users/:id", async (req: Request, res: Response) => {
+  console.log("[PATCH /scim/v2/Users/:id] Patch requested");
+  try {
+    const { schemas, Operations } = req.body ?? {};
+    if (!Array.isArray(schemas) || !schemas.includes(PATCH_SCHEMA) || !Array.isArray(Operations)) {
+      sendScimError(res, 400, "Body must be a PatchOp request", "invalidSyntax");
+      return;
+    }
+    const values = Operations.map(readActiveOperation);
+    if (values.some((value) => value === null)) {
+      sendScimError(res, 400, "Only replacing the active attribute is supported", "invalidPath");
+      return;
+    }
+    const active = values.at(-1) ?? undefined;
+
+    const tenantId = getRequestTenant(res);
+    const user = await findScimUser(req.params.id, tenantId);
+    if (!user) {
+      sendScimError(res, 404, "User not found");
+      return;
+    }
+    if (active !== undefined && active !== (user.disabled !== true)) {
+      const users = await getUsersCollection();
+      const updatedAt = nowDate();
+      await users.updateOne({ _id: user._id }, { $set: { disabled: !active, updatedAt } });
+      user.disabled = !active;
+      user.updatedAt = updatedAt;
+      const userId = user._id.toHexString();
+      if (!active) {
+        await revokeAllUserSessions(userId, tenantId);
+      }
+      await recordAuditEvent({
+        type: active ? "scim.user_activated" : "scim.user_deactivated",
+        tenantId,
+        userId,
+        ip: req.ip,
+      });
+    }
+    sendScim(res, 200, toScimUser(req, user));
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Patch failed";
+    console.error("[PATCH /scim/v2/Users/:id] Error:", message);
+    sendScimError(res, 500, "Internal server error");
+  }
+});
+
+router.delete("/scim/v2/Users/:id", async (req: Request, res: Response) => {
+  console.log("[DELETE /scim/v2/Users/:id] Deprovisioning requested");
+  try {
+    const tenantId = getRequestTenant(res);
+    const user = await findScimUser(req.params.id, tenantId);
+    if (!user) {
+      sendScimError(res, 404, "User not found");
+      return;
+    }
+    const userId = user._id.toHexString();
+    const users = await getUsersCollection();
+    await users.deleteOne({ _id: user._id });
+    await revokeAllUserSessions(userId, tenantId);
+    await recordAuditEvent({ type: "scim.user_deleted", tenantId, userId, ip: req.ip });
+    res.status(204).end();
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Deprovisioning failed";
+    console.error("[DELETE /scim/v2/Users/:id] Error:", message);
+    sendScimError(res, 500, "Internal server error");
+  }
+});
+
+export default router;
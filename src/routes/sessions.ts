@@ -0,0 +1,306 @@
+import { Router, type Request, type Response } from "express";
+import { deliverToken, readAuthCookie, readRequestToken } from "../authCookie";
+import { authRateLimiter, requireAuth, type AuthenticatedRequest } from "../middleware/auth";
+import { getRequestTenant } from "../middleware/tenant";
+import {
+  canExtendSession,
+  extendSession,
+  findActiveSession,
+  listUserSessions,
+  revokeUserSession,
+  SESSION_EXTEND_WINDOW_SECONDS,
+  toSessionView,
+} from "../sessions";
+import { nowUnix } from "../utils/clock";
+import { parseNumberEnv } from "../utils/env";
+import { sendError, sendRouteError } from "../utils/errors";
+import { verifyToken, type AuthPayload } from "../utils/jwt";
+
+export const HEARTBEAT_INTERVAL_MS = parseNumberEnv("HEARTBEAT_INTERVAL_MS", 15_000);
+
+const router = Router();
+
+router.get(
+  "/auth/sessions",
+  authRateLimiter,
+  requireAuth,
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[GET /auth/sessions] Session list requested");
+  try {
+    if (!req.user) {
+      console.log("[GET /auth/sessions] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const sessions = await listUserSessions(req.user.sub, req.user.tenantId);
+    console.log("[GET /auth/sessions] Sessions listed:", sessions.length);
+    res.status(200).json({
+      ok: true,
+      sessions: sessions.map((session) => toSessionView(session, req.user?.jti)),
+    });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Failed to list sessions";
+    console.error("[GET /auth/sessions] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// Reads the presenting token's own session; requireAuth has already rejected
+// tokens whose session is gone.
+router.get(
+  "/auth/sessions/current",
+  authRateLimiter,
+  requireAuth,
+  (req: AuthenticatedRequest, res: Response) => {
+  console.log("[GET /auth/sessions/current] Current session requested");
+  if (!req.user || !req.session) {
+    console.log("[GET /auth/sessions/current] Unauthorized - no session in request");
+    sendError(res, 401, "unauthorized", "Unauthorized");
+    return;
+  }
+  res.status(200).json({ ok: true, session: toSessionView(req.session, req.user.jti) });
+  },
+);
+
+router.delete(
+  "/auth/sessions/:id",
+  authRateLimiter,
+  requireAuth,
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[DELETE /auth/sessions/:id] Session revocation requested");
+  try {
+    if (!req.user) {
+      console.log("[DELETE /auth/sessions/:id] Unauthorized - no user in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const revoked = await revokeUserSession(req.user.sub, req.user.tenantId, req.params.id);
+    if (!revoked) {
+      console.log("[DELETE /auth/sessions/:id] Session not found");
+      sendError(res, 404, "session_not_found", "Session not found");
+      return;
+    }
+    console.log("[DELETE /auth/sessions/:id] Session revoked");
+    res.status(200).json({ ok: true });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Failed to revoke session";
+    console.error("[DELETE /auth/sessions/:id] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+// Lightweight sliding session: a token close to expiry can be swapped for a
+// fresh one on the same session. Expired tokens never reach this handler
+// (requireAuth rejects them) and have to log in again.
+router.post(
+  "/auth/session/extend",
+  authRateLimiter,
+  requireAuth,
+  async (req: AuthenticatedRequest, res: Response) => {
+  console.log("[POST /auth/session/extend] Session extension requested");
+  try {
+    if (!req.user || !req.session || req.user.exp === undefined) {
+      console.log("[POST /auth/session/extend] Unauthorized - no session in request");
+      sendError(res, 401, "unauthorized", "Unauthorized");
+      return;
+    }
+
+    const secondsRemaining = req.user.exp - nowUnix();
+    if (secondsRemaining > SESSION_EXTEND_WINDOW_SECONDS) {
+      console.log("[POST /auth/session/extend] Too early to extend:", secondsRemaining);
+      sendError(res, 400, "session_extend_too_early", "Session can only be extended close to expiry", {
+        secondsRemaining,
+        windowSeconds: SESSION_EXTEND_WINDOW_SECONDS,
+      });
+      return;
+    }
+
+    if (!canExtendSession(req.session, req.user)) {
+      console.log("[POST /auth/session/extend] Session reached its maximum lifetime");
+      sendError(res, 400, "session_lifetime_exceeded", "Session has reached its maximum lifetime, log in again");
+      return;
+    }
+
+    const { token, expiresAt } = await extendSession(req.session, req.user);
+    console.log("[POST /auth/session/extend] Session extended");
+    res.status(200).json({ ok: true, ...deliverToken(res, token, expiresAt), expiresAt });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Session extension failed";
+    console.error("[POST /auth/session/extend] Error:", message);
+    sendRouteError(res, error, message);
+  }
+  },
+);
+
+function tokenAgeBucket(secondsRemaining: number) {
+  if (secondsRemaining <= 0) {
+    return "expired";
+  }
+  if (secondsRemaining < 5 * 60) {
+    return "<5m";
+  }
+  return secondsRemaining <= 30 * 60 ? "5-30m" : ">30m";
+}
+
+// Tells clients when to refresh. Unlike requireAuth this still answers for an
+// expired token (bucket "expired"), so the signature is checked with expiry
+// ignored and the session is only looked up for tokens that are still live.
+router.get("/auth/token-age", authRateLimiter, async (req: Request, res: Response) => {
+  const token = readRequestToken(req);
+  if (!token) {
+    sendError(res, 401, "missing_token", "Missing bearer token");
+    return;
+  }
+  let user: AuthPayload;
+  try {
+    user = verifyToken(token, { ignoreExpiration: true });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Invalid token";
+    sendError(res, 401, "invalid_token", message);
+    return;
+  }
+  if (user.tenantId !== getRequestTenant(res)) {
+    sendError(res, 403, "tenant_mismatch", "Token does not belong to this tenant");
+    return;
+  }
+  if (user.exp === undefined || !user.jti) {
+    sendError(res, 401, "invalid_token", "Token is not bound to a session");
+    return;
+  }
+
+  try {
+    const secondsRemaining = Math.max(0, user.exp - nowUnix());
+    if (secondsRemaining > 0) {
+      const session = await findActiveSession(user.jti, user.exp);
+      if (!session || session.userId !== user.sub) {
+        sendError(res, 401, "session_revoked", "Session has been revoked or has expired");
+        return;
+      }
+    }
+    res.status(200).json({
+      ok: true,
+      bucket: tokenAgeBucket(secondsRemaining),
+      secondsRemaining,
+      expiresAt: new Date(user.exp * 1000),
+    });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Token age check failed";
+    console.error("[GET /auth/token-age] Error:", message);
+    sendRouteError(res, error, message);
+  }
+});
+
+type LoginStatusReason = "valid" | "anonymous" | "expired" | "invalid" | "revoked";
+
+function sendLoginStatus(res: Response, reason: LoginStatusReason, extra: Record<string, unknown> = {}) {
+  res.setHeader("Cache-Control", "no-store");
+  res.status(200).json({ ok: true, loggedIn: reason === "valid", reason, ...extra });
+}
+
+// Login state for the frontend. Every well-formed request gets a 200 with a
+// reason, so clients branch on the body; only a broken Authorization header
+// (wrong scheme, no token) is a 400.
+router.get("/auth/status", authRateLimiter, async (req: Request, res: Response) => {
+  const authHeader = req.headers.authorization;
+  const token = authHeader === undefined ? readAuthCookie(req) : /^Bearer +(\S+)$/.exec(authHeader.trim())?.[1];
+  if (authHeader === undefined && token === undefined) {
+    sendLoginStatus(res, "anonymous");
+    return;
+  }
+  if (!token) {
+    sendError(res, 400, "malformed_authorization", "Authorization header must be \"Bearer <token>\"");
+    return;
+  }
+  let user: AuthPayload;
+  try {
+    user = verifyToken(token, { ignoreExpiration: true });
+  } catch {
+    sendLoginStatus(res, "invalid");
+    return;
+  }
+  if (user.tenantId !== getRequestTenant(res) || user.exp === undefined || !user.jti) {
+    sendLoginStatus(res, "invalid");
+    return;
+  }
+  if (user.exp <= nowUnix()) {
+    sendLoginStatus(res, "expired", { expiredAt: new Date(user.exp * 1000) });
+    return;
+  }
+
+  try {
+    const session = await findActiveSession(user.jti, user.exp);
+    if (!session || session.userId !== user.sub) {
+      sendLoginStatus(res, "revoked");
+      return;
+    }
+    sendLoginStatus(res, "valid", {
+      user: { id: user.sub, email: user.email, roles: user.roles },
+      expiresAt: new Date(user.exp * 1000),
+    });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Login status check failed";
+    console.error("[GET /auth/status] Error:", message);
+    sendRouteError(res, error, message);
+  }
+});
+
+// Server-sent events stream for connectivity and auth liveness: a "ping" with
+// secondsRemaining every HEARTBEAT_INTERVAL_MS, then a final "expired" or
+// "revoked" event before the stream is closed. A session lookup that fails
+// skips the tick rather than ending the stream.
+router.get(
+  "/auth/heartbeat",
+  authRateLimiter,
+  requireAuth,
+  (req: AuthenticatedRequest, res: Response) => {
+  if (!req.user || req.user.exp === undefined || !req.user.jti) {
+    console.log("[GET /auth/heartbeat] Unauthorized - no session in request");
+    sendError(res, 401, "unauthorized", "Unauthorized");
+    return;
+  }
+  const { sub, jti, exp } = req.user;
+  res.status(200);
+  res.setHeader("Content-Type", "text/event-stream");
+  res.setHeader("Cache-Control", "no-store");
+  res.flushHeaders();
+
+  const send = (event: string, data: Record<string, unknown>) => {
+    if (!res.writableEnded) {
+      res.write(`event: ${event}\ndata: ${JSON.stringify(data)}\n\n`);
+    }
+  };
+  const close = (event: string, data: Record<string, unknown>) => {
+    clearInterval(timer);
+    send(event, data);
+    res.end();
+  };
+  const tick = async () => {
+    const secondsRemaining = exp - nowUnix();
+    if (secondsRemaining <= 0) {
+      close("expired", { expiredAt: new Date(exp * 1000) });
+      return;
+    }
+    try {
+      const session = await findActiveSession(jti, exp);
+      if (!session || session.userId !== sub) {
+        close("revoked", {});
+        return;
+      }
+    } catch (error) {
+      console.error("[GET /auth/heartbeat] Session lookup failed:", error instanceof Error ? error.message : error);
+      return;
+    }
+    send("ping", { secondsRemaining });
+  };
+
+  const timer = setInterval(() => void tick(), HEARTBEAT_INTERVAL_MS);
+  req.on("close", () => clearInterval(timer));
+  void tick();
+  },
+);
+
+export default router;
@@ -1,7 +1,62 @@
+import "./threadpool";
+import type { Server } from "http";
+import { ADMIN_PORT, adminApp } from "./adminApp";
 import { app } from "./app";
+import { startMaintenance } from "./maintenance";
+import { setReady } from "./readiness";
+import { PORT, runStartupSelfCheck, STARTUP_EXIT_ON_FAILURE, STARTUP_MODE } from "./startup";
 
-const port = Number(process.env.PORT) || 3000;
+const servers: Server[] = [];
 
-app.listen(port, () => {
-  console.log(`API server listening on port ${port}`);
+function listen() {
+  servers.push(
+    app.listen(PORT, () => {
+      console.log(`API server listening on port ${PORT} (startup mode: ${STARTUP_MODE})`);
+    }),
+  );
+  if (ADMIN_PORT !== null) {
+    servers.push(
+      adminApp.listen(ADMIN_PORT, () => {
+        console.log(`Admin server listening on port ${ADMIN_PORT}`);
+      }),
+    );
+  }
+  startMaintenance();
+}
+
+// Stops accepting connections on every listener and exits once in-flight
+// requests on all of them have finished.
+function shutdown(signal: string) {
+  console.log(`[server] ${signal} received, draining ${servers.length} listener(s)`);
+  Promise.all(servers.map((server) => new Promise((resolve) => server.close(resolve)))).then(() => {
+    console.log("[server] All listeners closed");
+    process.exit(0);
+  });
+}
+
+// Background work (maintenance, best-effort audit and notification writes)
+// runs outside any request; a rejection there is logged instead of taking the
+// whole process down.
+process.on("unhandledRejection", (reason) => {
+  console.error("[server] Unhandled promise rejection:", reason instanceof Error ? reason.stack : reason);
 });
+
+process.once("SIGTERM", () => shutdown("SIGTERM"));
+process.once("SIGINT", () => shutdown("SIGINT"));
+
+async function selfCheck() {
+  const healthy = await runStartupSelfCheck();
+  if (!healthy && STARTUP_EXIT_ON_FAILURE) {
+    console.error("[startup] Exiting because a critical check failed (STARTUP_EXIT_ON_FAILURE=true)");
+    process.exit(1);
+  }
+  setReady(healthy);
+}
+
+if (STARTUP_MODE === "lazy") {
+  setReady(false);
+  listen();
+  void selfCheck();
+} else {
+  selfCheck().then(listen);
+}
@@ -0,0 +1,299 @@
+import crypto from "crypto";
+import type { Request } from "express";
+import jwt from "jsonwebtoken";
+import { ObjectId } from "mongodb";
+import { getDatabaseName, getMongoClient } from "./db";
+import { TtlCache } from "./utils/cache";
+import { now, nowDate, nowUnix } from "./utils/clock";
+import { parseBooleanEnv, parseNumberEnv } from "./utils/env";
+import { createToken, type AuthPayload, type ExtraClaims, type TokenOptions } from "./utils/jwt";
+import { tokenLifetimeForRoles } from "./users";
+import { parseUserAgent, type DeviceDescriptor } from "./utils/userAgent";
+
+const LAST_ACTIVE_RESOLUTION_MS = 60_000;
+
+// Optional positive cache for session lookups (0 disables it). A cached session
+// stays valid for up to this long after being revoked, and revocations only
+// evict the cache of the instance that handled them, so keep the TTL short
+// (a few seconds) when running more than one instance.
+export const TOKEN_CACHE_TTL_MS = parseNumberEnv("TOKEN_CACHE_TTL_MS", 0);
+// When enabled every session is bound to the IP it was created from; otherwise
+// clients can opt in per login.
+export const TOKEN_IP_BINDING_DEFAULT = parseBooleanEnv(process.env.TOKEN_IP_BINDING) ?? false;
+// Token lifetime for "remember me" logins, instead of JWT_EXPIRES_IN.
+export const REMEMBER_ME_TTL_SECONDS = parseNumberEnv("REMEMBER_ME_TTL_SECONDS", 30 * 24 * 60 * 60);
+// How close to expiry a token may be extended, explicitly or by sliding renewal.
+export const SESSION_EXTEND_WINDOW_SECONDS = parseNumberEnv("SESSION_EXTEND_WINDOW_SECONDS", 300);
+// Opt-in sliding sessions: requireAuth renews tokens inside the extend window.
+export const SESSION_SLIDING = parseBooleanEnv(process.env.SESSION_SLIDING) ?? false;
+// No extension moves a session's expiry past this long after the login.
+export const SESSION_MAX_LIFETIME_SECONDS = parseNumberEnv("SESSION_MAX_LIFETIME_SECONDS", 30 * 24 * 60 * 60);
+
+export const TOKEN_CACHE_MAX_ENTRIES = parseNumberEnv("TOKEN_CACHE_MAX_ENTRIES", 10_000);
+export const REVOKED_TOKEN_CACHE_MAX_ENTRIES = parseNumberEnv("REVOKED_TOKEN_CACHE_MAX_ENTRIES", 10_000);
+
+const sessionCache = new TtlCache<SessionRecord>(TOKEN_CACHE_MAX_ENTRIES);
+
+export type SessionDegradationPolicy = "strict" | "allow_with_warning" | "allow_cached";
+
+// What requireAuth does for read requests when the session store cannot be
+// reached: refuse (strict), accept any signature-valid token (allow_with_warning),
+// or accept only tokens validated recently by this instance (allow_cached).
+export const SESSION_DEGRADATION_POLICY: SessionDegradationPolicy = (() => {
+  const raw = process.env.SESSION_DEGRADATION_POLICY?.trim().toLowerCase();
+  return raw === "allow_with_warning" || raw === "allow_cached" ? raw : "strict";
+})();
+
+// Sessions this instance has confirmed against the database, kept until they
+// expire. Only consulted while the database is unreachable.
+const validatedSessions = new TtlCache<SessionRecord>(TOKEN_CACHE_MAX_ENTRIES);
+
+// Tokens whose session was not found. A deleted session never comes back, so
+// the entry can live until the token itself expires; this lets clients that
+// keep retrying with a logged-out token fail without a database round trip.
+const revokedCache = new TtlCache<true>(REVOKED_TOKEN_CACHE_MAX_ENTRIES);
+
+export type SessionRecord = {
+  _id?: ObjectId;
+  jti: string;
+  userId: string;
+  tenantId: string;
+  createdAt: Date;
+  expiresAt: Date;
+  lastActiveAt: Date;
+  ip?: string;
+  // Only usable from the creating IP's network (see TOKEN_IP_BINDING_PREFIX_*).
+  ipBound?: boolean;
+  userAgent?: string;
+  device: DeviceDescriptor;
+  // Admin who opened this session to act as the user (see /admin/impersonate).
+  impersonatedBy?: string;
+  rememberMe?: boolean;
+};
+
+export type SessionOptions = TokenOptions & {
+  bindIp?: boolean;
+  rememberMe?: boolean;
+};
+
+let indexesReady: Promise<unknown> | null = null;
+
+export async function getSessionsCollection() {
+  const client = await getMongoClient();
+  const sessions = client.db(getDatabaseName()).collection<SessionRecord>("sessions");
+  // Expired sessions are removed by MongoDB's TTL monitor.
+  indexesReady ??= Promise.all([
+    sessions.createIndex({ jti: 1 }, { unique: true }),
+    sessions.createIndex({ userId: 1, tenantId: 1 }),
+    sessions.createIndex({ expiresAt: 1 }, { expireAfterSeconds: 0 }),
+  ]).catch((error) => {
+    indexesReady = null;
+    throw error;
+  });
+  await indexesReady;
+  return sessions;
+}
+
+function getTokenExpiry(token: string): Date {
+  const decoded = jwt.decode(token);
+  if (!decoded || typeof decoded === "string" || typeof decoded.exp !== "number") {
+    throw new Error("Issued token has no expiry");
+  }
+  return new Date(decoded.exp * 1000);
+}
+
+// A role lifetime (JWT_EXPIRY_BY_ROLE) takes precedence over remember-me, so
+// privileged sessions stay short; undefined falls back to JWT_EXPIRES_IN.
+function sessionLifetime(roles: readonly string[], rememberMe: boolean | undefined) {
+  return tokenLifetimeForRoles(roles) ?? (rememberMe ? REMEMBER_ME_TTL_SECONDS : undefined);
+}
+
+// Issues an access token backed by a session record. The token's jti names the
+// session, so deleting the record revokes the token.
+export async function startSession(
+  req: Request,
+  payload: AuthPayload,
+  extraClaims: ExtraClaims = {},
+  options: SessionOptions = {},
+) {
+  const { bindIp = TOKEN_IP_BINDING_DEFAULT, rememberMe = false, ...tokenOptions } = options;
+  tokenOptions.expiresIn ??= sessionLifetime(payload.roles, rememberMe);
+  const jti = crypto.randomUUID();
+  const ipBound = bindIp && req.ip !== undefined;
+  const token = createToken({ ...payload, jti, ...(ipBound ? { boundIp: req.ip } : {}) }, extraClaims, tokenOptions);
+  const now = nowDate();
+  const userAgent = req.get("user-agent");
+  const session: SessionRecord = {
+    jti,
+    userId: payload.sub,
+    tenantId: payload.tenantId,
+    createdAt: now,
+    expiresAt: getTokenExpiry(token),
+    lastActiveAt: now,
+    ip: req.ip,
+    ipBound,
+    userAgent,
+    device: parseUserAgent(userAgent),
+    ...(payload.actor ? { impersonatedBy: payload.actor } : {}),
+    ...(rememberMe ? { rememberMe: true } : {}),
+  };
+  const sessions = await getSessionsCollection();
+  const result = await sessions.insertOne(session);
+  return { token, session: { ...session, _id: result.insertedId } };
+}
+
+// Login time of the session; tokens from before the auth_time claim fall back
+// to the session's creation.
+function sessionAuthTime(session: SessionRecord, payload: AuthPayload) {
+  return payload.authTime ?? Math.floor(session.createdAt.getTime() / 1000);
+}
+
+// Whether the session may still be extended past the token's current expiry
+// without exceeding SESSION_MAX_LIFETIME_SECONDS.
+export function canExtendSession(session: SessionRecord, payload: AuthPayload) {
+  const deadline = sessionAuthTime(session, payload) + SESSION_MAX_LIFETIME_SECONDS;
+  return payload.exp === undefined || payload.exp < deadline;
+}
+
+// Re-issues a token for an existing session (same jti) with a fresh expiry and
+// moves the session's expiry along with it, keeping the session's lifetime but
+// never going past SESSION_MAX_LIFETIME_SECONDS from the login.
+export async function extendSession(session: SessionRecord, payload: AuthPayload, options: TokenOptions = {}) {
+  const authTime = sessionAuthTime(session, payload);
+  const deadline = authTime + SESSION_MAX_LIFETIME_SECONDS;
+  const claims = { ...payload, jti: session.jti, authTime };
+  const expiresIn = options.expiresIn ?? sessionLifetime(payload.roles, session.rememberMe);
+  let token = createToken(claims, {}, { ...options, expiresIn });
+  if (getTokenExpiry(token).getTime() > deadline * 1000) {
+    token = createToken(claims, {}, { ...options, expiresIn: Math.max(deadline - nowUnix(), 1) });
+  }
+  const expiresAt = getTokenExpiry(token);
+  const sessions = await getSessionsCollection();
+  await sessions.updateOne({ jti: session.jti }, { $set: { expiresAt, lastActiveAt: nowDate() } });
+  forgetSession(session.jti);
+  return { token, expiresAt };
+}
+
+// Lookups in flight per jti, so concurrent requests with the same token share
+// one database round trip.
+const pendingLookups = new Map<string, Promise<SessionRecord | null>>();
+
+// tokenExp is the token's exp claim (seconds), used to size the revoked entry.
+export async function findActiveSession(jti: string, tokenExp?: number) {
+  if (revokedCache.get(jti)) {
+    return null;
+  }
+  const cached = sessionCache.get(jti);
+  if (cached && cached.expiresAt.getTime() > now()) {
+    return cached;
+  }
+  let pending = pendingLookups.get(jti);
+  if (!pending) {
+    pending = lookupSession(jti, tokenExp).finally(() => pendingLookups.delete(jti));
+    pendingLookups.set(jti, pending);
+  }
+  return pending;
+}
+
+async function lookupSession(jti: string, tokenExp: number | undefined) {
+  const sessions = await getSessionsCollection();
+  const session = await sessions.findOne({ jti, expiresAt: { $gt: nowDate() } });
+  if (!session) {
+    if (tokenExp !== undefined) {
+      revokedCache.set(jti, true, tokenExp * 1000 - now());
+    }
+    return null;
+  }
+  if (TOKEN_CACHE_TTL_MS > 0) {
+    sessionCache.set(jti, session, Math.min(TOKEN_CACHE_TTL_MS, session.expiresAt.getTime() - now()));
+  }
+  if (SESSION_DEGRADATION_POLICY !== "strict") {
+    validatedSessions.set(jti, session, session.expiresAt.getTime() - now());
+  }
+  return session;
+}
+
+function forgetSession(jti: string) {
+  sessionCache.delete(jti);
+  validatedSessions.delete(jti);
+}
+
+// Fallback for a failed session lookup. Returns null when the token must be
+// refused, otherwise the last validated session (if this instance has one).
+export function degradedSessionLookup(jti: string): { session?: SessionRecord } | null {
+  if (SESSION_DEGRADATION_POLICY === "strict") {
+    return null;
+  }
+  const session = validatedSessions.get(jti);
+  if (SESSION_DEGRADATION_POLICY === "allow_cached" && !session) {
+    return null;
+  }
+  return { session };
+}
+
+// Last activity is only written when it is more than a minute stale so that
+// validation does not turn every request into a database write.
+export async function touchSession(session: SessionRecord) {
+  const now = nowDate();
+  if (!session._id || now.getTime() - session.lastActiveAt.getTime() < LAST_ACTIVE_RESOLUTION_MS) {
+    return;
+  }
+  // The record may be the cached one; updating it first keeps cache hits from
+  // repeating the write until the next minute.
+  session.lastActiveAt = now;
+  const sessions = await getSessionsCollection();
+  await sessions.updateOne({ _id: session._id }, { $set: { lastActiveAt: now } });
+}
+
+export async function listUserSessions(userId: string, tenantId: string) {
+  const sessions = await getSessionsCollection();
+  return sessions
+    .find({ userId, tenantId, expiresAt: { $gt: nowDate() } })
+    .sort({ lastActiveAt: -1 })
+    .toArray();
+}
+
+export async function revokeUserSession(userId: string, tenantId: string, sessionId: string) {
+  if (!ObjectId.isValid(sessionId)) {
+    return false;
+  }
+  const sessions = await getSessionsCollection();
+  const deleted = await sessions.findOneAndDelete({ _id: new ObjectId(sessionId), userId, tenantId });
+  if (!deleted) {
+    return false;
+  }
+  forgetSession(deleted.jti);
+  revokedCache.set(deleted.jti, true, deleted.expiresAt.getTime() - now());
+  return true;
+}
+
+// Works from the session records alone, so it also clears sessions left behind
+// by a user record that no longer exists.
+export async function revokeAllUserSessions(userId: string, tenantId: string) {
+  const sessions = await getSessionsCollection();
+  const active = await sessions.find({ userId, tenantId }).toArray();
+  if (active.length === 0) {
+    return 0;
+  }
+  const result = await sessions.deleteMany({ _id: { $in: active.map((session) => session._id as ObjectId) } });
+  for (const session of active) {
+    forgetSession(session.jti);
+    revokedCache.set(session.jti, true, session.expiresAt.getTime() - now());
+  }
+  return result.deletedCount;
+}
+
+export function toSessionView(session: SessionRecord, currentJti?: string) {
+  return {
+    id: session._id?.toHexString(),
+    current: session.jti === currentJti,
+    createdAt: session.createdAt,
+    expiresAt: session.expiresAt,
+    lastActiveAt: session.lastActiveAt,
+    ip: session.ip ?? null,
+    ipBound: session.ipBound ?? false,
+    device: session.device,
+    impersonatedBy: session.impersonatedBy ?? null,
+    rememberMe: session.rememberMe ?? false,
+  };
+}
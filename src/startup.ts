@@ -0,0 +1,102 @@
+import { probeMongo } from "./db";
+import { describeEffectiveConfig } from "./config";
+import { parseBooleanEnv, parseNumberEnv } from "./utils/env";
+import { IS_PRODUCTION, jwtSecretsProblem } from "./utils/jwt";
+
+export const PORT = Number(process.env.PORT) || 3000;
+// block: finish the dependency self-test before accepting traffic.
+// lazy: listen immediately but report not-ready until the self-test passes.
+export const STARTUP_MODE = process.env.STARTUP_MODE === "lazy" ? "lazy" : "block";
+export const STARTUP_EXIT_ON_FAILURE = parseBooleanEnv(process.env.STARTUP_EXIT_ON_FAILURE) ?? false;
+export const STARTUP_DEADLINE_MS = parseNumberEnv("STARTUP_DEADLINE_MS", 30_000);
+const RETRY_INITIAL_DELAY_MS = 250;
+const RETRY_MAX_DELAY_MS = 5_000;
+
+type CheckResult = {
+  name: string;
+  ok: boolean;
+  critical: boolean;
+  durationMs: number;
+  detail?: string;
+};
+
+async function runCheck(name: string, critical: boolean, check: () => Promise<void> | void): Promise<CheckResult> {
+  const startedAt = Date.now();
+  try {
+    await check();
+    return { name, ok: true, critical, durationMs: Date.now() - startedAt };
+  } catch (error) {
+    const detail = error instanceof Error ? error.message : String(error);
+    return { name, ok: false, critical, durationMs: Date.now() - startedAt, detail };
+  }
+}
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+// Retries the probe with exponential backoff until it succeeds or the startup
+// deadline passes, so a database that comes up shortly after the service does
+// not fail the boot.
+async function waitForMongo() {
+  const deadline = Date.now() + STARTUP_DEADLINE_MS;
+  let delay = RETRY_INITIAL_DELAY_MS;
+  for (let attempt = 1; ; attempt += 1) {
+    try {
+      await probeMongo();
+      return;
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      const remaining = deadline - Date.now();
+      if (remaining <= 0) {
+        throw new Error(`MongoDB probe failed after ${attempt} attempt(s): ${message}`);
+      }
+      console.log(`[startup] MongoDB probe attempt ${attempt} failed, retrying in ${Math.min(delay, remaining)}ms:`, message);
+      await sleep(Math.min(delay, remaining));
+      delay = Math.min(delay * 2, RETRY_MAX_DELAY_MS);
+    }
+  }
+}
+
+function requireStrongJwtSecret() {
+  const problem = jwtSecretsProblem();
+  if (problem) {
+    throw new Error(problem);
+  }
+}
+
+function requireEnv(name: string) {
+  if (!process.env[name]) {
+    throw new Error(`${name} environment variable is not set`);
+  }
+}
+
+// Logs the effective configuration and the result of each dependency check in
+// one block. Returns false when any critical check failed.
+export async function runStartupSelfCheck(): Promise<boolean> {
+  console.log("[startup] Effective configuration:", describeEffectiveConfig());
+
+  const results = [
+    // Only critical in production; elsewhere a random per-process secret is used.
+    await runCheck("JWT_SECRET strong", IS_PRODUCTION, () => requireStrongJwtSecret()),
+    await runCheck("MONGODB_URI configured", true, () => requireEnv("MONGODB_URI")),
+    await runCheck("MongoDB read/write probe", true, () => waitForMongo()),
+  ];
+
+  for (const result of results) {
+    if (result.ok) {
+      console.log(`[startup] check passed: ${result.name} (${result.durationMs}ms)`);
+    } else {
+      console.error(`[startup] check failed: ${result.name} (${result.durationMs}ms)`, {
+        critical: result.critical,
+        detail: result.detail,
+      });
+    }
+  }
+
+  const criticalFailures = results.filter((result) => !result.ok && result.critical);
+  console.log(
+    criticalFailures.length === 0
+      ? "[startup] Self-check complete: all critical checks passed"
+      : `[startup] Self-check complete: ${criticalFailures.length} critical check(s) failed`,
+  );
+  return criticalFailures.length === 0;
+}
@@ -0,0 +1,19 @@
+import os from "os";
+
+// scrypt hashing runs on libuv's threadpool, which it shares with fs and DNS
+// lookups and which defaults to 4 threads however many CPUs the host has.
+// libuv sizes the pool on first use, so entry points import this module
+// before anything else.
+const UV_THREADPOOL_SIZE_MAX = 1024;
+
+const configured = Number(process.env.UV_THREADPOOL_SIZE);
+const fromEnv = Number.isInteger(configured) && configured > 0;
+
+export const THREADPOOL_SIZE = fromEnv
+  ? Math.min(configured, UV_THREADPOOL_SIZE_MAX)
+  : Math.max(4, os.availableParallelism());
+
+process.env.UV_THREADPOOL_SIZE = String(THREADPOOL_SIZE);
+console.log(
+  `[threadpool] libuv threadpool size ${THREADPOOL_SIZE} (${fromEnv ? "UV_THREADPOOL_SIZE" : `default for ${os.availableParallelism()} CPUs`})`,
+);
@@ -0,0 +1,28 @@
+import { parseBooleanEnv } from "./utils/env";
+import { nowDate } from "./utils/clock";
+import type { UserRecord } from "./users";
+
+// Terms-of-service tracking is off unless a current version is configured.
+export const TOS_CURRENT_VERSION = process.env.TOS_CURRENT_VERSION?.trim() || null;
+export const TOS_URL = process.env.TOS_URL?.trim() || null;
+// Strict mode refuses to issue tokens until the current version is accepted.
+export const TOS_STRICT = parseBooleanEnv(process.env.TOS_STRICT) ?? false;
+
+export type TosAcceptance = {
+  version: string;
+  acceptedAt: Date;
+  ip?: string;
+};
+
+export function newTosAcceptance(version: string, ip: string | undefined): TosAcceptance {
+  return { version, acceptedAt: nowDate(), ip };
+}
+
+export function getAcceptedTosVersion(user: Pick<UserRecord, "tosAcceptances">) {
+  const history = user.tosAcceptances ?? [];
+  return history.length > 0 ? history[history.length - 1].version : null;
+}
+
+export function isTosAcceptanceRequired(user: Pick<UserRecord, "tosAcceptances">) {
+  return TOS_CURRENT_VERSION !== null && getAcceptedTosVersion(user) !== TOS_CURRENT_VERSION;
+}
@@ -0,0 +1,136 @@
+import { ObjectId, type Filter } from "mongodb";
+import { getDatabaseName, getMongoClient } from "./db";
+import { DEFAULT_TENANT_ID } from "./middleware/tenant";
+import { parseCountEnv, parseNumberEnv } from "./utils/env";
+import type { KnownDevice } from "./loginNotifications";
+import type { TosAcceptance } from "./tos";
+import type { PasswordHashEntry } from "./utils/password";
+
+// In ascending order of privilege.
+export const USER_ROLES = ["user", "admin"] as const;
+
+export type UserRole = (typeof USER_ROLES)[number];
+
+export const DEFAULT_USER_ROLES: UserRole[] = ["user"];
+
+// Days a deleted account stays recoverable before the maintenance task purges it.
+export const ACCOUNT_DELETION_GRACE_DAYS = parseNumberEnv("ACCOUNT_DELETION_GRACE_DAYS", 14);
+
+// Number of previous passwords (besides the current one) that cannot be reused;
+// 0 turns the reuse check off entirely.
+export const PASSWORD_HISTORY_SIZE = parseCountEnv("PASSWORD_HISTORY_SIZE", 5);
+
+export type UserPreferences = {
+  // Email on logins from a new device or country (default on).
+  notifyNewDevice?: boolean;
+};
+
+export type UserRecord = {
+  _id?: ObjectId;
+  // Users created before multi-tenancy had no tenantId; getUsersCollection
+  // backfills the default tenant, and tenantFilter still matches either.
+  tenantId?: string;
+  email: string;
+  passwordHash: string;
+  passwordSalt: string;
+  // Most recent previous passwords first, capped at PASSWORD_HISTORY_SIZE.
+  passwordHistory?: PasswordHashEntry[];
+  roles?: UserRole[];
+  // Extra JWT claims (e.g. tenant_id, org) issued for this user.
+  claims?: Record<string, unknown>;
+  // Terms-of-service acceptances, oldest first; the last entry is the accepted version.
+  tosAcceptances?: TosAcceptance[];
+  // Devices and countries seen at login, used to spot sign-ins from new places.
+  knownDevices?: KnownDevice[];
+  knownCountries?: string[];
+  preferences?: UserPreferences;
+  createdAt: Date;
+  // Set while the account is pending deletion; the record (which keeps the
+  // email taken) is purged once deletionScheduledAt has passed.
+  deletionRequestedAt?: Date;
+  deletionScheduledAt?: Date;
+  // Set by SCIM provisioning: the IdP's id for the user, and deactivation,
+  // which refuses logins. Provisioned users start without a password.
+  externalId?: string;
+  disabled?: boolean;
+  updatedAt?: Date;
+  // Consecutive wrong passwords and the resulting lock (see lockout.ts).
+  failedLoginCount?: number;
+  lockedUntil?: Date;
+};
+
+let indexesReady: Promise<unknown> | null = null;
+
+// Emails are unique per tenant, enforced by the index so concurrent inserts
+// for one email fail with a duplicate-key error instead of both landing.
+// Users from before multi-tenancy are first given the default tenant
+// explicitly; without a tenantId they would never collide with new
+// default-tenant users in the index.
+export async function getUsersCollection() {
+  const client = await getMongoClient();
+  const users = client.db(getDatabaseName()).collection<UserRecord>("users");
+  indexesReady ??= users
+    .updateMany({ tenantId: { $exists: false } }, { $set: { tenantId: DEFAULT_TENANT_ID } })
+    .then(() => users.createIndex({ tenantId: 1, email: 1 }, { unique: true }))
+    .catch((error) => {
+      indexesReady = null;
+      throw error;
+    });
+  await indexesReady;
+  return users;
+}
+
+export function normalizeEmail(email: string) {
+  return email.trim().toLowerCase();
+}
+
+export function isUserRole(value: unknown): value is UserRole {
+  return typeof value === "string" && (USER_ROLES as readonly string[]).includes(value);
+}
+
+export function getUserRoles(user: Pick<UserRecord, "roles">): UserRole[] {
+  return user.roles && user.roles.length > 0 ? user.roles : DEFAULT_USER_ROLES;
+}
+
+// JWT_EXPIRY_BY_ROLE, e.g. "admin=900,user=3600": token lifetime in seconds
+// per role, overriding JWT_EXPIRES_IN.
+function parseExpiryByRole(raw: string | undefined) {
+  const lifetimes = new Map<UserRole, number>();
+  for (const entry of (raw ?? "").split(",").map((value) => value.trim()).filter(Boolean)) {
+    const [role, seconds] = entry.split("=").map((value) => value.trim());
+    const lifetime = Number(seconds);
+    if (isUserRole(role) && Number.isInteger(lifetime) && lifetime > 0) {
+      lifetimes.set(role, lifetime);
+    } else {
+      console.error("[users] Ignoring invalid JWT_EXPIRY_BY_ROLE entry:", entry);
+    }
+  }
+  return lifetimes;
+}
+
+export const TOKEN_LIFETIME_BY_ROLE = parseExpiryByRole(process.env.JWT_EXPIRY_BY_ROLE);
+
+// Lifetime configured for the user's highest-privilege role, if any.
+export function tokenLifetimeForRoles(roles: readonly string[]): number | undefined {
+  const highest = [...USER_ROLES].reverse().find((role) => roles.includes(role));
+  return highest ? TOKEN_LIFETIME_BY_ROLE.get(highest) : undefined;
+}
+
+export function getPasswordHistory(user: Pick<UserRecord, "passwordHash" | "passwordSalt" | "passwordHistory">) {
+  return [{ hash: user.passwordHash, salt: user.passwordSalt }, ...(user.passwordHistory ?? [])];
+}
+
+export function isDuplicateKeyError(error: unknown) {
+  return typeof error === "object" && error !== null && (error as { code?: unknown }).code === 11000;
+}
+
+export function tenantFilter(tenantId: string): Filter<UserRecord> {
+  if (tenantId === DEFAULT_TENANT_ID) {
+    return { $or: [{ tenantId }, { tenantId: { $exists: false } }] };
+  }
+  return { tenantId };
+}
+
+export function getUserTenant(user: Pick<UserRecord, "tenantId">): string {
+  return user.tenantId ?? DEFAULT_TENANT_ID;
+}
@@ -0,0 +1,48 @@
+// Source of "now" for token expiry, session lifetimes, grace periods, and
+// caches. Everything time-dependent reads the clock through these helpers so a
+// ManualClock can be swapped in to step past an expiry without real sleeps.
+// Timings that measure elapsed wall time (startup checks, probes) and the
+// rate limiter's own timers are deliberately not routed through it.
+export interface Clock {
+  // Milliseconds since the Unix epoch.
+  now(): number;
+}
+
+export const systemClock: Clock = {
+  now: () => Date.now(),
+};
+
+let currentClock: Clock = systemClock;
+
+export function setClock(clock: Clock) {
+  currentClock = clock;
+}
+
+export function now() {
+  return currentClock.now();
+}
+
+export function nowDate() {
+  return new Date(currentClock.now());
+}
+
+// Seconds since the Unix epoch, the unit of JWT iat/exp claims.
+export function nowUnix() {
+  return Math.floor(currentClock.now() / 1000);
+}
+
+export class ManualClock implements Clock {
+  constructor(private current = Date.now()) {}
+
+  now() {
+    return this.current;
+  }
+
+  advance(ms: number) {
+    this.current += ms;
+  }
+
+  set(ms: number) {
+    this.current = ms;
+  }
+}
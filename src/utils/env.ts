@@ -2,3 +2,27 @@ export function parseNumberEnv(name: string, fallback: number) {
   const value = Number(process.env[name]);
   return Number.isFinite(value) && value > 0 ? value : fallback;
 }
+
+// Like parseNumberEnv, but 0 is a valid setting (typically "disabled").
+export function parseCountEnv(name: string, fallback: number) {
+  const raw = process.env[name]?.trim();
+  const value = Number(raw);
+  return raw && Number.isInteger(value) && value >= 0 ? value : fallback;
+}
+
+export function parseBooleanEnv(value: string | undefined): boolean | undefined {
+  if (value === undefined) {
+    return undefined;
+  }
+
+  const normalized = value.trim().toLowerCase();
+  if (["1", "true", "yes", "on"].includes(normalized)) {
+    return true;
+  }
+
+  if (["0", "false", "no", "off"].includes(normalized)) {
+    return false;
+  }
+
+  return undefined;
+}
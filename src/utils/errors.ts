@@ -0,0 +1,87 @@
+import type { Request, Response, NextFunction } from "express";
+import { isOperationTimeoutError } from "../db";
+import { localizeMessage, resolveLanguage } from "./i18n";
+
+export class ApiError extends Error {
+  constructor(
+    readonly status: number,
+    readonly code: string,
+    message: string,
+    readonly details?: unknown,
+  ) {
+    super(message);
+    this.name = "ApiError";
+  }
+}
+
+export function sendError(res: Response, status: number, code: string, message: string, details?: unknown) {
+  const language = resolveLanguage(res.req.get("accept-language"));
+  res.setHeader("Content-Language", language);
+  res.status(status).json({
+    ok: false,
+    error: {
+      code,
+      message: localizeMessage(code, language, message, details),
+      details: details ?? null,
+      request_id: res.locals.requestId ?? null,
+    },
+  });
+}
+
+export function sendApiError(res: Response, error: ApiError) {
+  sendError(res, error.status, error.code, error.message, error.details);
+}
+
+// Used by route catch blocks: known errors keep their status and code, anything
+// else is reported as a 500 with the handler's message.
+export function sendRouteError(res: Response, error: unknown, message: string) {
+  if (error instanceof ApiError) {
+    sendApiError(res, error);
+    return;
+  }
+  if (isOperationTimeoutError(error)) {
+    sendError(res, 503, "dependency_timeout", "Database operation timed out");
+    return;
+  }
+  sendError(res, 500, "internal_error", message);
+}
+
+type BodyParserError = Error & { type?: string; status?: number };
+
+export function notFoundHandler(req: Request, res: Response) {
+  sendError(res, 404, "not_found", `Route ${req.method} ${req.path} not found`);
+}
+
+// Express 5 routes both thrown errors and rejected handler promises here, so a
+// bug in a handler becomes a 500 envelope carrying the request id.
+export function errorHandler(error: unknown, req: Request, res: Response, next: NextFunction) {
+  if (res.headersSent) {
+    // Too late for an error body; Express's final handler closes the connection.
+    console.error(`[${req.method} ${req.path}] Error after the response started:`, {
+      message: error instanceof Error ? error.message : String(error),
+      requestId: res.locals.requestId,
+    });
+    next(error);
+    return;
+  }
+  if (error instanceof ApiError) {
+    sendApiError(res, error);
+    return;
+  }
+  const err = error as BodyParserError;
+  if (err?.type === "entity.parse.failed") {
+    sendError(res, 400, "invalid_json", "Request body is not valid JSON", { reason: err.message });
+    return;
+  }
+  if (err?.type === "entity.too.large") {
+    sendError(res, 413, "payload_too_large", "Request body is too large");
+    return;
+  }
+
+  console.error(`[${req.method} ${req.path}] Unhandled error:`, {
+    message: error instanceof Error ? error.message : String(error),
+    requestId: res.locals.requestId,
+    stack: error instanceof Error ? error.stack : undefined,
+  });
+  sendRouteError(res, error, "Internal server error");
+}
@@ -0,0 +1,99 @@
+const REDIRECT_POLICIES: readonly RequestRedirect[] = ["manual", "follow", "error"];
+
+// Outbound calls return a 3xx as-is unless OUTBOUND_HTTP_REDIRECT says
+// otherwise, so a redirecting provider shows up as a failed call instead of
+// being followed somewhere unexpected.
+export const OUTBOUND_HTTP_REDIRECT: RequestRedirect = (() => {
+  const raw = process.env.OUTBOUND_HTTP_REDIRECT?.trim().toLowerCase() || "manual";
+  if ((REDIRECT_POLICIES as readonly string[]).includes(raw)) {
+    return raw as RequestRedirect;
+  }
+  console.error("[http] Ignoring invalid OUTBOUND_HTTP_REDIRECT:", raw);
+  return "manual";
+})();
+
+export type OutboundCallOptions = {
+  // Upper bound for this call.
+  timeoutMs: number;
+  // Epoch ms by which the calling request must finish; the call only gets the
+  // time left until then, if that is less than timeoutMs.
+  deadline?: number;
+  // Forwarded as X-Request-Id so the call can be correlated with our logs.
+  requestId?: string;
+};
+
+export class DeadlineExceededError extends Error {
+  constructor(url: string) {
+    super(`No time left before the request deadline to call ${url}`);
+    this.name = "DeadlineExceededError";
+  }
+}
+
+type OutboundStats = {
+  count: number;
+  failures: number;
+  totalMs: number;
+  maxMs: number;
+};
+
+const outboundStats = new Map<string, OutboundStats>();
+
+function recordOutboundCall(host: string, durationMs: number, failed: boolean) {
+  const stats = outboundStats.get(host) ?? { count: 0, failures: 0, totalMs: 0, maxMs: 0 };
+  stats.count += 1;
+  stats.totalMs += durationMs;
+  stats.maxMs = Math.max(stats.maxMs, durationMs);
+  if (failed) {
+    stats.failures += 1;
+  }
+  outboundStats.set(host, stats);
+}
+
+// Per target host, in the same shape as the MongoDB command stats.
+export function getOutboundStats() {
+  return Object.fromEntries(
+    Array.from(outboundStats, ([host, stats]) => [
+      host,
+      {
+        count: stats.count,
+        failures: stats.failures,
+        avgMs: stats.count > 0 ? Math.round((stats.totalMs / stats.count) * 100) / 100 : 0,
+        maxMs: Math.round(stats.maxMs * 100) / 100,
+      },
+    ]),
+  );
+}
+
+export function remainingBudgetMs(timeoutMs: number, deadline: number | undefined, nowMs = Date.now()) {
+  return deadline === undefined ? timeoutMs : Math.min(timeoutMs, deadline - nowMs);
+}
+
+// Every outbound HTTP call goes through here: it gets the request id header,
+// a timeout that never outlives the caller's deadline, and latency stats per
+// host. Non-2xx responses are returned as-is and count as successful calls.
+export async function outboundFetch(url: string, init: RequestInit, options: OutboundCallOptions) {
+  const budgetMs = remainingBudgetMs(options.timeoutMs, options.deadline);
+  if (budgetMs <= 0) {
+    throw new DeadlineExceededError(url);
+  }
+  const headers = new Headers(init.headers);
+  if (options.requestId) {
+    headers.set("X-Request-Id", options.requestId);
+  }
+  const host = new URL(url).host;
+  const startedAt = performance.now();
+  let failed = false;
+  try {
+    return await fetch(url, {
+      redirect: OUTBOUND_HTTP_REDIRECT,
+      ...init,
+      headers,
+      signal: AbortSignal.timeout(budgetMs),
+    });
+  } catch (error) {
+    failed = true;
+    throw error;
+  } finally {
+    recordOutboundCall(host, performance.now() - startedAt, failed);
+  }
+}
@@ -0,0 +1,301 @@
+import fs from "fs";
+
+export const DEFAULT_LANGUAGE = "en";
+
+type MessageCatalog = Record<string, Record<string, string>>;
+
+// Error code -> language -> message template. Templates may reference fields of
+// the error details, e.g. "{minLength}". Codes whose message carries runtime
+// detail (internal errors, driver errors) are intentionally absent and are
+// always returned as produced by the handler.
+const DEFAULT_CATALOG: MessageCatalog = {
+  invalid_request: {
+    en: "Request body is invalid",
+    es: "El cuerpo de la solicitud no es válido",
+  },
+  missing_credentials: {
+    en: "Email and password are required",
+    es: "Se requieren el correo electrónico y la contraseña",
+  },
+  invalid_json: {
+    en: "Request body is not valid JSON",
+    es: "El cuerpo de la solicitud no es JSON válido",
+  },
+  unsupported_media_type: {
+    en: "Content-Type must be application/json",
+    es: "El Content-Type debe ser application/json",
+  },
+  json_too_complex: {
+    en: "Request body is nested too deeply",
+    es: "El cuerpo de la solicitud está anidado demasiado profundamente",
+  },
+  unknown_fields: {
+    en: "Request body contains unknown fields",
+    es: "El cuerpo de la solicitud contiene campos desconocidos",
+  },
+  uri_too_long: {
+    en: "Request URL is too long",
+    es: "La URL de la solicitud es demasiado larga",
+  },
+  field_too_long: {
+    en: "Field {field} is too long",
+    es: "El campo {field} es demasiado largo",
+  },
+  payload_too_large: {
+    en: "Request body is too large",
+    es: "El cuerpo de la solicitud es demasiado grande",
+  },
+  invalid_email: {
+    en: "Valid email is required",
+    es: "Se requiere un correo electrónico válido",
+  },
+  weak_password: {
+    en: "Password must be at least {minLength} chars, include upper/lower/number/symbol, and not contain the email address",
+    es: "La contraseña debe tener al menos {minLength} caracteres, incluir mayúsculas, minúsculas, números y símbolos, y no contener el correo electrónico",
+  },
+  password_reused: {
+    en: "Password was used recently, choose a different one",
+    es: "La contraseña se usó recientemente, elige una diferente",
+  },
+  registration_disabled: {
+    en: "Registration is disabled",
+    es: "El registro está deshabilitado",
+  },
+  email_check_disabled: {
+    en: "Email availability checks are disabled",
+    es: "La comprobación de disponibilidad de correos está deshabilitada",
+  },
+  invalid_invite: {
+    en: "Invite is invalid, expired, or already used",
+    es: "La invitación no es válida, expiró o ya se usó",
+  },
+  invalid_reset_token: {
+    en: "Reset token is invalid, expired, or already used",
+    es: "El token de restablecimiento no es válido, expiró o ya se usó",
+  },
+  tos_acceptance_required: {
+    en: "The current terms of service must be accepted",
+    es: "Se deben aceptar los términos de servicio vigentes",
+  },
+  tos_not_configured: {
+    en: "Terms of service are not configured",
+    es: "Los términos de servicio no están configurados",
+  },
+  tos_version_mismatch: {
+    en: "Only the current terms of service version can be accepted",
+    es: "Solo se puede aceptar la versión vigente de los términos de servicio",
+  },
+  captcha_required: {
+    en: "CAPTCHA verification is required",
+    es: "Se requiere la verificación CAPTCHA",
+  },
+  captcha_failed: {
+    en: "CAPTCHA verification failed",
+    es: "La verificación CAPTCHA falló",
+  },
+  captcha_unavailable: {
+    en: "CAPTCHA verification is temporarily unavailable",
+    es: "La verificación CAPTCHA no está disponible temporalmente",
+  },
+  email_taken: {
+    en: "Email is already registered",
+    es: "El correo electrónico ya está registrado",
+  },
+  invalid_credentials: {
+    en: "Invalid credentials",
+    es: "Credenciales inválidas",
+  },
+  account_disabled: {
+    en: "Account is disabled",
+    es: "La cuenta está deshabilitada",
+  },
+  too_many_concurrent_logins: {
+    en: "Another login for this account is in progress, try again shortly",
+    es: "Hay otro inicio de sesión en curso para esta cuenta, inténtalo de nuevo en breve",
+  },
+  account_locked: {
+    en: "Account is temporarily locked",
+    es: "La cuenta está bloqueada temporalmente",
+  },
+  missing_token: {
+    en: "Missing bearer token",
+    es: "Falta el token de acceso",
+  },
+  token_issue_failed: {
+    en: "Could not issue a token",
+    es: "No se pudo emitir un token",
+  },
+  internal_auth_required: {
+    en: "A valid internal service token is required",
+    es: "Se requiere un token de servicio interno válido",
+  },
+  malformed_authorization: {
+    en: "Authorization header must be \"Bearer <token>\"",
+    es: "La cabecera Authorization debe ser \"Bearer <token>\"",
+  },
+  unauthorized: {
+    en: "Unauthorized",
+    es: "No autorizado",
+  },
+  forbidden: {
+    en: "Forbidden",
+    es: "Acceso denegado",
+  },
+  invalid_tenant: {
+    en: "Tenant identifier is malformed",
+    es: "El identificador del inquilino no es válido",
+  },
+  unknown_tenant: {
+    en: "Unknown tenant",
+    es: "Inquilino desconocido",
+  },
+  tenant_mismatch: {
+    en: "Token does not belong to this tenant",
+    es: "El token no pertenece a este inquilino",
+  },
+  invalid_scope: {
+    en: "Requested scope exceeds the presented token",
+    es: "El alcance solicitado excede el del token presentado",
+  },
+  impersonation_read_only: {
+    en: "Impersonation tokens cannot modify data",
+    es: "Los tokens de suplantación no pueden modificar datos",
+  },
+  session_revoked: {
+    en: "Session has been revoked or has expired",
+    es: "La sesión fue revocada o ha expirado",
+  },
+  token_ip_mismatch: {
+    en: "Token cannot be used from this network",
+    es: "El token no se puede usar desde esta red",
+  },
+  session_extend_too_early: {
+    en: "Session can only be extended close to expiry",
+    es: "La sesión solo se puede extender cerca de su vencimiento",
+  },
+  session_lifetime_exceeded: {
+    en: "Session has reached its maximum lifetime, log in again",
+    es: "La sesión alcanzó su duración máxima, inicia sesión de nuevo",
+  },
+  session_not_found: {
+    en: "Session not found",
+    es: "Sesión no encontrada",
+  },
+  user_not_found: {
+    en: "User not found",
+    es: "Usuario no encontrado",
+  },
+  deletion_not_pending: {
+    en: "Account is not pending deletion",
+    es: "La cuenta no tiene una eliminación pendiente",
+  },
+  not_found: {
+    en: "Route not found",
+    es: "Ruta no encontrada",
+  },
+  rate_limited: {
+    en: "Too many requests, try again later",
+    es: "Demasiadas solicitudes, inténtalo más tarde",
+  },
+  not_ready: {
+    en: "Service is still starting up",
+    es: "El servicio todavía se está iniciando",
+  },
+  dependency_unavailable: {
+    en: "Session store is unavailable",
+    es: "El almacén de sesiones no está disponible",
+  },
+  dependency_timeout: {
+    en: "Database operation timed out",
+    es: "La operación de base de datos excedió el tiempo de espera",
+  },
+  batch_too_large: {
+    en: "At most {max} users can be created per request",
+    es: "Se pueden crear como máximo {max} usuarios por solicitud",
+  },
+  email_not_configured: {
+    en: "Password reset is unavailable: email delivery is not configured",
+    es: "El restablecimiento de contraseña no está disponible: el envío de correo no está configurado",
+  },
+  row_too_large: {
+    en: "An import row is too large",
+    es: "Una fila de la importación es demasiado grande",
+  },
+};
+
+export const ERROR_CATALOG_PATH = process.env.ERROR_CATALOG_PATH || null;
+
+function loadCatalog(): MessageCatalog {
+  if (!ERROR_CATALOG_PATH) {
+    return DEFAULT_CATALOG;
+  }
+  try {
+    const overrides = JSON.parse(fs.readFileSync(ERROR_CATALOG_PATH, "utf8")) as MessageCatalog;
+    const catalog: MessageCatalog = { ...DEFAULT_CATALOG };
+    for (const [code, templates] of Object.entries(overrides)) {
+      catalog[code] = { ...catalog[code], ...templates };
+    }
+    console.log("[i18n] Loaded error catalog from", ERROR_CATALOG_PATH);
+    return catalog;
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    console.error("[i18n] Failed to load error catalog, using defaults:", message);
+    return DEFAULT_CATALOG;
+  }
+}
+
+const catalog = loadCatalog();
+
+const supportedLanguages = new Set(
+  Object.values(catalog).flatMap((templates) => Object.keys(templates)),
+);
+
+export function parseAcceptLanguage(header: string | undefined): string[] {
+  if (!header) {
+    return [];
+  }
+  return header
+    .split(",")
+    .map((part, index) => {
+      const [tag, ...params] = part.trim().split(";");
+      const qParam = params.map((param) => param.trim()).find((param) => param.startsWith("q="));
+      const q = qParam ? Number(qParam.slice(2)) : 1;
+      return { tag: tag.trim().toLowerCase(), q: Number.isFinite(q) ? q : 0, index };
+    })
+    .filter(({ tag, q }) => tag && tag !== "*" && q > 0)
+    .sort((a, b) => b.q - a.q || a.index - b.index)
+    .map(({ tag }) => tag);
+}
+
+export function resolveLanguage(header: string | undefined): string {
+  for (const tag of parseAcceptLanguage(header)) {
+    if (supportedLanguages.has(tag)) {
+      return tag;
+    }
+    const primary = tag.split("-")[0];
+    if (supportedLanguages.has(primary)) {
+      return primary;
+    }
+  }
+  return DEFAULT_LANGUAGE;
+}
+
+function fillTemplate(template: string, params: unknown) {
+  if (typeof params !== "object" || params === null) {
+    return template;
+  }
+  const values = params as Record<string, unknown>;
+  return template.replace(/\{(\w+)\}/g, (match, name: string) =>
+    values[name] === undefined ? match : String(values[name]),
+  );
+}
+
+// Handlers produce the English message (which is also what gets logged), so the
+// catalog is only consulted for other languages.
+export function localizeMessage(code: string, language: string, message: string, params?: unknown) {
+  if (language === DEFAULT_LANGUAGE) {
+    return message;
+  }
+  const template = catalog[code]?.[language];
+  return template ? fillTemplate(template, params) : message;
+}
@@ -0,0 +1,140 @@
+import net from "net";
+
+function parseIpv4(address: string): number[] | null {
+  const parts = address.split(".");
+  if (parts.length !== 4 || !parts.every((part) => /^\d{1,3}$/.test(part) && Number(part) <= 255)) {
+    return null;
+  }
+  return parts.map(Number);
+}
+
+function parseIpv6(address: string): number[] | null {
+  // An embedded IPv4 tail (e.g. ::ffff:10.0.0.1) is rewritten as two hextets.
+  let normalized = address;
+  const ipv4Tail = normalized.match(/(\d+\.\d+\.\d+\.\d+)$/);
+  if (ipv4Tail) {
+    const octets = parseIpv4(ipv4Tail[1]);
+    if (!octets) {
+      return null;
+    }
+    const high = ((octets[0] << 8) | octets[1]).toString(16);
+    const low = ((octets[2] << 8) | octets[3]).toString(16);
+    normalized = `${normalized.slice(0, -ipv4Tail[1].length)}${high}:${low}`;
+  }
+
+  const halves = normalized.split("::");
+  if (halves.length > 2) {
+    return null;
+  }
+  const head = halves[0] ? halves[0].split(":") : [];
+  const tail = halves.length === 2 && halves[1] ? halves[1].split(":") : [];
+  const missing = 8 - head.length - tail.length;
+  if ((halves.length === 2 && missing < 1) || (halves.length === 1 && missing !== 0)) {
+    return null;
+  }
+  const hextets = [...head, ...Array<string>(halves.length === 2 ? missing : 0).fill("0"), ...tail];
+  if (!hextets.every((hextet) => /^[0-9a-f]{1,4}$/i.test(hextet))) {
+    return null;
+  }
+  return hextets.flatMap((hextet) => {
+    const value = parseInt(hextet, 16);
+    return [value >> 8, value & 0xff];
+  });
+}
+
+// Returns the address as bytes, unwrapping IPv4-mapped IPv6 addresses so that
+// "::ffff:10.0.0.1" and "10.0.0.1" compare equal.
+export function parseIp(address: string): number[] | null {
+  const trimmed = address.trim();
+  const family = net.isIP(trimmed);
+  if (family === 4) {
+    return parseIpv4(trimmed);
+  }
+  if (family !== 6) {
+    return null;
+  }
+  const bytes = parseIpv6(trimmed.split("%")[0]);
+  if (bytes && bytes.slice(0, 10).every((byte) => byte === 0) && bytes[10] === 0xff && bytes[11] === 0xff) {
+    return bytes.slice(12);
+  }
+  return bytes;
+}
+
+function prefixMatches(a: number[], b: number[], prefixLength: number) {
+  const fullBytes = Math.floor(prefixLength / 8);
+  for (let i = 0; i < fullBytes; i += 1) {
+    if (a[i] !== b[i]) {
+      return false;
+    }
+  }
+  const remainingBits = prefixLength % 8;
+  if (remainingBits === 0) {
+    return true;
+  }
+  const mask = (0xff << (8 - remainingBits)) & 0xff;
+  return (a[fullBytes] & mask) === (b[fullBytes] & mask);
+}
+
+export type Cidr = {
+  bytes: number[];
+  prefixLength: number;
+};
+
+export function parseCidr(value: string): Cidr | null {
+  const [address, prefix, ...rest] = value.trim().split("/");
+  const bytes = parseIp(address);
+  if (!bytes || rest.length > 0) {
+    return null;
+  }
+  const maxPrefix = bytes.length * 8;
+  if (prefix === undefined) {
+    return { bytes, prefixLength: maxPrefix };
+  }
+  if (!/^\d+$/.test(prefix) || Number(prefix) > maxPrefix) {
+    return null;
+  }
+  return { bytes, prefixLength: Number(prefix) };
+}
+
+// Inverse of parseCidr, for logs and reports; IPv6 is written without "::".
+export function formatCidr({ bytes, prefixLength }: Cidr) {
+  if (bytes.length === 4) {
+    return `${bytes.join(".")}/${prefixLength}`;
+  }
+  const hextets: string[] = [];
+  for (let i = 0; i < bytes.length; i += 2) {
+    hextets.push(((bytes[i] << 8) | bytes[i + 1]).toString(16));
+  }
+  return `${hextets.join(":")}/${prefixLength}`;
+}
+
+export function cidrContains(cidr: Cidr, address: string) {
+  const bytes = parseIp(address);
+  return bytes !== null && bytes.length === cidr.bytes.length && prefixMatches(cidr.bytes, bytes, cidr.prefixLength);
+}
+
+// Parses a comma-separated CIDR list; invalid entries are logged and skipped.
+export function parseCidrList(raw: string, label: string): Cidr[] {
+  const cidrs: Cidr[] = [];
+  for (const entry of raw.split(",").map((value) => value.trim()).filter(Boolean)) {
+    const cidr = parseCidr(entry);
+    if (cidr) {
+      cidrs.push(cidr);
+    } else {
+      console.error(`[${label}] Ignoring invalid CIDR:`, entry);
+    }
+  }
+  return cidrs;
+}
+
+// True when both addresses are the same family and share the given prefix
+// (IPv4 and IPv6 tolerances are configured separately).
+export function isSameNetwork(a: string, b: string, prefixV4: number, prefixV6: number) {
+  const first = parseIp(a);
+  const second = parseIp(b);
+  if (!first || !second || first.length !== second.length) {
+    return false;
+  }
+  const prefixLength = first.length === 4 ? Math.min(prefixV4, 32) : Math.min(prefixV6, 128);
+  return prefixMatches(first, second, prefixLength);
+}
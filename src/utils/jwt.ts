@@ -1,31 +1,214 @@
-import jwt, { type JwtPayload, type SignOptions } from "jsonwebtoken";
+import crypto from "crypto";
+import jwt, { type JwtPayload, type SignOptions, type VerifyOptions } from "jsonwebtoken";
+import { nowUnix } from "./clock";
+import { ApiError } from "./errors";
+import { readSecretList } from "./secrets";
 
 export type AuthPayload = {
   sub: string;
   email: string;
+  roles: string[];
+  tenantId: string;
+  // Identifies the server-side session backing the token.
+  jti?: string;
+  // Expiry (seconds since epoch) of a verified token; ignored when signing.
+  exp?: number;
+  // Set on tokens minted by token exchange for a downstream audience.
+  scope?: string[];
+  audience?: string;
+  // Admin acting as this user; carried as the act and impersonation claims.
+  actor?: string;
+  // Login IP of an IP-bound session, carried as the bip claim so the binding
+  // holds even when the session record cannot be read.
+  boundIp?: string;
+  // Login time (seconds since epoch) as the auth_time claim; renewed tokens
+  // keep it so the session's total lifetime can be capped. Defaults to iat.
+  authTime?: number;
+  extra?: Record<string, unknown>;
 };
 
-export function getJwtSecret(): string {
-  const secret = process.env.JWT_SECRET;
+export type TokenOptions = {
+  expiresIn?: SignOptions["expiresIn"];
+};
+
+export type ExtraClaims = Record<string, unknown>;
+
+// Claims owned by the token format itself; extra claims can never override them.
+const RESERVED_CLAIMS = new Set([
+  "sub",
+  "email",
+  "roles",
+  "tenant_id",
+  "scope",
+  "act",
+  "impersonation",
+  "iat",
+  "exp",
+  "nbf",
+  "iss",
+  "aud",
+  "jti",
+  "bip",
+  "auth_time",
+]);
+
+function pickExtraClaims(claims: Record<string, unknown>): ExtraClaims {
+  const extra: ExtraClaims = {};
+  for (const [name, value] of Object.entries(claims)) {
+    if (!RESERVED_CLAIMS.has(name) && value !== undefined) {
+      extra[name] = value;
+    }
+  }
+  return extra;
+}
+
+// APP_ENV=production (or NODE_ENV=production when APP_ENV is unset) makes a
+// missing or weak JWT_SECRET fatal instead of a warning.
+export const APP_ENV = process.env.APP_ENV ?? process.env.NODE_ENV;
+export const IS_PRODUCTION = APP_ENV === "production";
+const MIN_JWT_SECRET_BYTES = 32;
+// Placeholders from docs and examples that must never sign real tokens.
+const KNOWN_DEFAULT_SECRETS = new Set(["dev-secret", "secret", "your-secret", "changeme", "change-me", "jwt-secret"]);
+
+// JWT_SECRET (or JWT_SECRET_FILE) may list several keys: tokens are signed
+// with the first and verified against all of them.
+export const JWT_SECRETS = readSecretList("JWT_SECRET");
+
+let ephemeralSecret: string | null = null;
+
+export function isJwtSecret(value: string) {
+  return JWT_SECRETS.includes(value);
+}
+
+export function jwtSecretProblem(secret: string | undefined): string | null {
   if (!secret) {
+    return "JWT_SECRET environment variable is not set";
+  }
+  if (KNOWN_DEFAULT_SECRETS.has(secret.trim().toLowerCase())) {
+    return "JWT_SECRET is a well-known placeholder value";
+  }
+  if (Buffer.byteLength(secret) < MIN_JWT_SECRET_BYTES) {
+    return `JWT_SECRET is shorter than ${MIN_JWT_SECRET_BYTES} bytes`;
+  }
+  return null;
+}
+
+// Checks every configured key, since any of them can verify a token.
+export function jwtSecretsProblem(): string | null {
+  if (JWT_SECRETS.length === 0) {
+    return jwtSecretProblem(undefined);
+  }
+  for (const [index, secret] of JWT_SECRETS.entries()) {
+    const problem = jwtSecretProblem(secret);
+    if (problem) {
+      return JWT_SECRETS.length > 1 ? `${problem} (key ${index + 1})` : problem;
+    }
+  }
+  return null;
+}
+
+// Called when the app module loads, so a production deployment with a weak
+// secret fails to start rather than accepting forgeable tokens.
+export function enforceJwtSecretPolicy() {
+  const problem = jwtSecretsProblem();
+  if (!problem) {
+    return;
+  }
+  if (IS_PRODUCTION) {
+    throw new Error(`${problem} (refused because APP_ENV=production)`);
+  }
+  console.warn(`[jwt] WARNING: ${problem}. This is refused in production.`);
+}
+
+// The signing key. Outside production a missing secret is replaced by a
+// random one that lives as long as the process, so tokens never verify across
+// restarts or against a secret anyone else knows.
+export function getJwtSecret(): string {
+  if (JWT_SECRETS.length > 0) {
+    return JWT_SECRETS[0];
+  }
+  if (IS_PRODUCTION) {
     throw new Error("JWT_SECRET environment variable is not set");
   }
-  return secret;
+  if (!ephemeralSecret) {
+    ephemeralSecret = crypto.randomBytes(32).toString("hex");
+    console.warn("[jwt] WARNING: JWT_SECRET is not set, signing with a random per-process secret");
+  }
+  return ephemeralSecret;
+}
+
+function isBadSignature(error: unknown) {
+  return error instanceof jwt.JsonWebTokenError && error.message === "invalid signature";
+}
+
+// jwt.verify against each key in turn. Only a signature mismatch moves on to
+// the next key; expiry and format errors mean the right key was found or that
+// no key will do, and are thrown as is.
+export function verifyJwt(token: string, options: VerifyOptions = {}): string | JwtPayload {
+  const secrets = JWT_SECRETS.length > 0 ? JWT_SECRETS : [getJwtSecret()];
+  let lastError: unknown;
+  for (const secret of secrets) {
+    try {
+      return jwt.verify(token, secret, options);
+    } catch (error) {
+      if (!isBadSignature(error)) {
+        throw error;
+      }
+      lastError = error;
+    }
+  }
+  throw lastError;
 }
 
-function resolveJwtExpiresIn(): SignOptions["expiresIn"] {
-  const raw = process.env.JWT_EXPIRES_IN;
+export function parseExpiresIn(raw: string | undefined, fallback: string): SignOptions["expiresIn"] {
   if (!raw) {
-    return "1h";
+    return fallback as SignOptions["expiresIn"];
   }
   const normalized = raw.toLowerCase();
   const isValid = /^\d+$/.test(normalized) || /^\d+(ms|s|m|h|d|w|y)$/.test(normalized);
-  return (isValid ? raw : "1h") as SignOptions["expiresIn"];
+  return (isValid ? raw : fallback) as SignOptions["expiresIn"];
+}
+
+export const JWT_EXPIRES_IN = parseExpiresIn(process.env.JWT_EXPIRES_IN, "1h");
+export const JWT_HEADER_TYP = process.env.JWT_HEADER_TYP?.trim() || "JWT";
+
+// The one place AuthPayload is turned into claims; parseAuthPayload is its
+// inverse. exp and aud are added by jwt.sign from the token options.
+export function buildClaims(payload: AuthPayload, extraClaims: ExtraClaims = {}) {
+  const { sub, email, roles, tenantId, jti, scope, actor, boundIp, authTime } = payload;
+  const iat = nowUnix();
+  return {
+    ...pickExtraClaims({ ...payload.extra, ...extraClaims }),
+    sub,
+    email,
+    roles,
+    tenant_id: tenantId,
+    // Explicit iat so exp (iat + expiresIn) follows the injectable clock.
+    iat,
+    auth_time: authTime ?? iat,
+    ...(jti ? { jti } : {}),
+    ...(scope ? { scope: scope.join(" ") } : {}),
+    ...(actor ? { act: { sub: actor }, impersonation: true } : {}),
+    ...(boundIp ? { bip: boundIp } : {}),
+  };
 }
 
-export function createToken(payload: AuthPayload): string {
-  const expiresIn = resolveJwtExpiresIn();
-  return jwt.sign(payload, getJwtSecret(), { expiresIn });
+export function createToken(payload: AuthPayload, extraClaims: ExtraClaims = {}, options: TokenOptions = {}): string {
+  const expiresIn = options.expiresIn ?? JWT_EXPIRES_IN;
+  const { audience } = payload;
+  const claims = buildClaims(payload, extraClaims);
+  // Signing only fails on bad input (e.g. an unusable expiresIn or an
+  // unserializable extra claim); surface it as a 500 with its own code.
+  try {
+    return jwt.sign(claims, getJwtSecret(), {
+      expiresIn,
+      ...(audience ? { audience } : {}),
+      header: { alg: "HS256", typ: JWT_HEADER_TYP },
+    });
+  } catch (error) {
+    console.error("[jwt] Token signing failed:", error instanceof Error ? error.message : error);
+    throw new ApiError(500, "token_issue_failed", "Could not issue a token");
+  }
 }
 
 export function parseAuthPayload(decoded: string | JwtPayload): AuthPayload {
@@ -34,8 +217,45 @@ export function parseAuthPayload(decoded: string | JwtPayload): AuthPayload {
   }
   const subject = decoded.sub;
   const email = decoded.email;
-  if (typeof subject !== "string" || typeof email !== "string") {
+  const tenantId = decoded.tenant_id;
+  if (typeof subject !== "string" || typeof email !== "string" || typeof tenantId !== "string") {
     throw new Error("Invalid token payload");
   }
-  return { sub: subject, email };
+  const roles = Array.isArray(decoded.roles)
+    ? decoded.roles.filter((role): role is string => typeof role === "string")
+    : [];
+  const scope = typeof decoded.scope === "string" ? decoded.scope.split(" ").filter(Boolean) : undefined;
+  const audience = typeof decoded.aud === "string" ? decoded.aud : undefined;
+  const jti = typeof decoded.jti === "string" ? decoded.jti : undefined;
+  const exp = typeof decoded.exp === "number" ? decoded.exp : undefined;
+  const act: unknown = decoded.act;
+  const actorSub = typeof act === "object" && act !== null ? (act as { sub?: unknown }).sub : undefined;
+  const actor = typeof actorSub === "string" ? actorSub : undefined;
+  const boundIp = typeof decoded.bip === "string" ? decoded.bip : undefined;
+  const authTime = typeof decoded.auth_time === "number" ? decoded.auth_time : undefined;
+  return {
+    sub: subject,
+    email,
+    roles,
+    tenantId,
+    jti,
+    exp,
+    scope,
+    audience,
+    actor,
+    boundIp,
+    authTime,
+    extra: pickExtraClaims(decoded),
+  };
+}
+
+// Checks the signature (and expiry unless ignoreExpiration) against the
+// injectable clock and parses the claims. Throws jsonwebtoken's errors for
+// bad tokens and a plain Error for a payload missing required claims.
+export function verifyToken(token: string, options: { ignoreExpiration?: boolean } = {}): AuthPayload {
+  const decoded = verifyJwt(token, {
+    clockTimestamp: nowUnix(),
+    ...(options.ignoreExpiration ? { ignoreExpiration: true } : {}),
+  });
+  return parseAuthPayload(decoded);
 }
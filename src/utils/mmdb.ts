@@ -0,0 +1,198 @@
+import { parseIp } from "./ip";
+
+// Minimal reader for MaxMind DB files (the .mmdb format of GeoLite2/GeoIP2):
+// the binary search tree maps address bits to records in the data section.
+// See https://maxmind.github.io/MaxMind-DB/ for the format.
+
+const METADATA_MARKER = Buffer.from("\xAB\xCD\xEFMaxMind.com", "latin1");
+// The metadata sits within the last 128 KiB of the file.
+const METADATA_MAX_SIZE = 128 * 1024;
+// Between the search tree and the data section.
+const DATA_SECTION_SEPARATOR_SIZE = 16;
+
+const enum DataType {
+  Extended = 0,
+  Pointer = 1,
+  Utf8String = 2,
+  Double = 3,
+  Bytes = 4,
+  Uint16 = 5,
+  Uint32 = 6,
+  Map = 7,
+  Int32 = 8,
+  Uint64 = 9,
+  Uint128 = 10,
+  Array = 11,
+  Boolean = 14,
+  Float = 15,
+}
+
+type Decoded = [value: unknown, next: number];
+
+class DataDecoder {
+  // base: where pointers are resolved from (the data section start).
+  constructor(
+    private readonly buffer: Buffer,
+    private readonly base: number,
+  ) {}
+
+  decode(offset: number): Decoded {
+    const control = this.buffer[offset++];
+    let type = control >> 5;
+    if (type === DataType.Pointer) {
+      return this.decodePointer(control, offset);
+    }
+    if (type === DataType.Extended) {
+      type = 7 + this.buffer[offset++];
+    }
+    let size = control & 0x1f;
+    if (size === 29) {
+      size = 29 + this.buffer[offset];
+      offset += 1;
+    } else if (size === 30) {
+      size = 285 + this.buffer.readUInt16BE(offset);
+      offset += 2;
+    } else if (size === 31) {
+      size = 65_821 + this.buffer.readUIntBE(offset, 3);
+      offset += 3;
+    }
+    return this.decodeValue(type, size, offset);
+  }
+
+  private decodePointer(control: number, offset: number): Decoded {
+    const pointerSize = (control >> 3) & 0x3;
+    const high = control & 0x7;
+    let pointer: number;
+    if (pointerSize === 0) {
+      pointer = (high << 8) | this.buffer[offset];
+    } else if (pointerSize === 1) {
+      pointer = ((high << 16) | this.buffer.readUInt16BE(offset)) + 2048;
+    } else if (pointerSize === 2) {
+      pointer = high * 2 ** 24 + this.buffer.readUIntBE(offset, 3) + 526_336;
+    } else {
+      pointer = this.buffer.readUInt32BE(offset);
+    }
+    const [value] = this.decode(this.base + pointer);
+    return [value, offset + pointerSize + 1];
+  }
+
+  private decodeValue(type: number, size: number, offset: number): Decoded {
+    switch (type) {
+      case DataType.Utf8String:
+        return [this.buffer.toString("utf8", offset, offset + size), offset + size];
+      case DataType.Double:
+        return [this.buffer.readDoubleBE(offset), offset + 8];
+      case DataType.Float:
+        return [this.buffer.readFloatBE(offset), offset + 4];
+      case DataType.Bytes:
+        return [this.buffer.subarray(offset, offset + size), offset + size];
+      case DataType.Uint16:
+      case DataType.Uint32:
+        return [size === 0 ? 0 : this.buffer.readUIntBE(offset, size), offset + size];
+      case DataType.Int32:
+        if (size === 4) {
+          return [this.buffer.readInt32BE(offset), offset + 4];
+        }
+        // Shorter encodings are always non-negative.
+        return [size === 0 ? 0 : this.buffer.readUIntBE(offset, size), offset + size];
+      case DataType.Uint64:
+      case DataType.Uint128: {
+        let value = 0n;
+        for (let i = 0; i < size; i += 1) {
+          value = (value << 8n) | BigInt(this.buffer[offset + i]);
+        }
+        return [value, offset + size];
+      }
+      case DataType.Boolean:
+        return [size !== 0, offset];
+      case DataType.Map: {
+        const map: Record<string, unknown> = {};
+        for (let i = 0; i < size; i += 1) {
+          const [key, afterKey] = this.decode(offset);
+          const [value, afterValue] = this.decode(afterKey);
+          map[String(key)] = value;
+          offset = afterValue;
+        }
+        return [map, offset];
+      }
+      case DataType.Array: {
+        const array: unknown[] = [];
+        for (let i = 0; i < size; i += 1) {
+          const [value, next] = this.decode(offset);
+          array.push(value);
+          offset = next;
+        }
+        return [array, offset];
+      }
+      default:
+        throw new Error(`Unsupported MaxMind DB data type ${type}`);
+    }
+  }
+}
+
+export class MmdbReader {
+  private readonly nodeCount: number;
+  private readonly recordSize: number;
+  private readonly nodeByteSize: number;
+  private readonly ipVersion: number;
+  private readonly data: DataDecoder;
+  // Node reached after the 96 zero bits that prefix IPv4 addresses in an IPv6 tree.
+  private readonly ipv4Start: number;
+
+  constructor(private readonly buffer: Buffer) {
+    const markerAt = buffer.lastIndexOf(METADATA_MARKER);
+    if (markerAt === -1 || markerAt < buffer.length - METADATA_MAX_SIZE) {
+      throw new Error("Not a MaxMind DB file: metadata not found");
+    }
+    const metadataStart = markerAt + METADATA_MARKER.length;
+    const [decoded] = new DataDecoder(buffer, metadataStart).decode(metadataStart);
+    const metadata = decoded as Record<string, unknown>;
+    const { node_count: nodeCount, record_size: recordSize, ip_version: ipVersion } = metadata;
+    if (typeof nodeCount !== "number" || ![24, 28, 32].includes(recordSize as number)) {
+      throw new Error("Unsupported MaxMind DB metadata");
+    }
+    this.nodeCount = nodeCount;
+    this.recordSize = recordSize as number;
+    this.nodeByteSize = (this.recordSize * 2) / 8;
+    this.ipVersion = ipVersion === 6 ? 6 : 4;
+    this.data = new DataDecoder(buffer, this.nodeCount * this.nodeByteSize + DATA_SECTION_SEPARATOR_SIZE);
+
+    let node = 0;
+    for (let i = 0; i < 96 && this.ipVersion === 6 && node < this.nodeCount; i += 1) {
+      node = this.readRecord(node, 0);
+    }
+    this.ipv4Start = node;
+  }
+
+  private readRecord(node: number, bit: number) {
+    const offset = node * this.nodeByteSize;
+    if (this.recordSize === 24) {
+      return this.buffer.readUIntBE(offset + bit * 3, 3);
+    }
+    if (this.recordSize === 28) {
+      const middle = this.buffer[offset + 3];
+      return bit === 0
+        ? ((middle & 0xf0) << 20) | this.buffer.readUIntBE(offset, 3)
+        : ((middle & 0x0f) << 24) | this.buffer.readUIntBE(offset + 4, 3);
+    }
+    return this.buffer.readUInt32BE(offset + bit * 4);
+  }
+
+  // The record for the address, or null when the database has none.
+  get(address: string): unknown {
+    const bytes = parseIp(address);
+    if (!bytes || (bytes.length === 16 && this.ipVersion === 4)) {
+      return null;
+    }
+    let node = bytes.length === 4 ? this.ipv4Start : 0;
+    for (let i = 0; i < bytes.length * 8 && node < this.nodeCount; i += 1) {
+      node = this.readRecord(node, (bytes[i >> 3] >> (7 - (i & 7))) & 1);
+    }
+    if (node <= this.nodeCount) {
+      // Equal to node_count means "no data"; below it, the address ran out first.
+      return null;
+    }
+    const [record] = this.data.decode(this.nodeCount * this.nodeByteSize + (node - this.nodeCount));
+    return record;
+  }
+}
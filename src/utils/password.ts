@@ -28,3 +28,17 @@ export async function verifyPassword(password: string, salt: string, expectedHas
   }
   return crypto.timingSafeEqual(expectedBuffer, hash);
 }
+
+export type PasswordHashEntry = {
+  hash: string;
+  salt: string;
+};
+
+export async function matchesAnyPassword(password: string, entries: readonly PasswordHashEntry[]) {
+  for (const entry of entries) {
+    if (await verifyPassword(password, entry.salt, entry.hash)) {
+      return true;
+    }
+  }
+  return false;
+}
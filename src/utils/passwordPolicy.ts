@@ -0,0 +1,62 @@
+import { parseNumberEnv } from "./env";
+
+export const PASSWORD_MIN_LENGTH = parseNumberEnv("PASSWORD_MIN_LENGTH", 8);
+// Shorter email names ("al@...") are too common in passwords to reject.
+const EMAIL_NAME_MIN_LENGTH = 3;
+
+export const WEAK_PASSWORD_MESSAGE = `Password must be at least ${PASSWORD_MIN_LENGTH} chars, include upper/lower/number/symbol, and not contain the email address`;
+
+export type PasswordRuleFailure = {
+  code: string;
+  message: string;
+};
+
+export type PasswordPolicyResult = {
+  accepted: boolean;
+  // 0-2 for rejected passwords (fewer failed rules score higher), 3 for
+  // accepted ones, 4 for accepted ones at least twice the minimum length.
+  score: number;
+  failures: PasswordRuleFailure[];
+};
+
+const CHARACTER_RULES = [
+  { code: "missing_lowercase", pattern: /[a-z]/, message: "Password must include a lowercase letter" },
+  { code: "missing_uppercase", pattern: /[A-Z]/, message: "Password must include an uppercase letter" },
+  { code: "missing_digit", pattern: /\d/, message: "Password must include a number" },
+  { code: "missing_symbol", pattern: /[^A-Za-z0-9]/, message: "Password must include a symbol" },
+];
+
+function containsEmail(password: string, email: string) {
+  const lowered = password.toLowerCase();
+  const address = email.trim().toLowerCase();
+  const name = address.split("@")[0];
+  return lowered.includes(address) || (name.length >= EMAIL_NAME_MIN_LENGTH && lowered.includes(name));
+}
+
+// The password policy. Registration, admin-created users, password changes
+// and resets, and POST /auth/password/strength all call this, so the strength
+// endpoint never disagrees with what the others accept.
+export function checkPasswordPolicy(password: string, email?: string): PasswordPolicyResult {
+  const failures: PasswordRuleFailure[] = [];
+  if (password.length < PASSWORD_MIN_LENGTH) {
+    failures.push({ code: "too_short", message: `Password must be at least ${PASSWORD_MIN_LENGTH} characters` });
+  }
+  for (const rule of CHARACTER_RULES) {
+    if (!rule.pattern.test(password)) {
+      failures.push({ code: rule.code, message: rule.message });
+    }
+  }
+  if (email && containsEmail(password, email)) {
+    failures.push({ code: "similar_to_email", message: "Password must not contain the email address" });
+  }
+
+  if (failures.length > 0) {
+    return { accepted: false, score: Math.max(0, 3 - failures.length), failures };
+  }
+  return { accepted: true, score: password.length >= PASSWORD_MIN_LENGTH * 2 ? 4 : 3, failures };
+}
+
+// Details sent with 400 weak_password, listing the failed rules' codes.
+export function weakPasswordDetails(result: PasswordPolicyResult) {
+  return { minLength: PASSWORD_MIN_LENGTH, failures: result.failures.map((failure) => failure.code) };
+}
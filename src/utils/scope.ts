@@ -0,0 +1,25 @@
+// Scopes granted by a token: its explicit scope claim when it has one (already
+// narrowed by a previous exchange), otherwise the user's roles.
+export function grantedScopes(token: { scope?: string[]; roles: string[] }): string[] {
+  return token.scope ?? token.roles;
+}
+
+export function parseScope(value: unknown): string[] | undefined {
+  if (typeof value === "string") {
+    return Array.from(new Set(value.split(" ").filter(Boolean)));
+  }
+  if (Array.isArray(value) && value.every((item) => typeof item === "string")) {
+    return Array.from(new Set(value.filter(Boolean)));
+  }
+  return undefined;
+}
+
+// Requested scopes can only narrow what was granted; anything outside the
+// granted set is reported back as denied rather than silently dropped.
+export function intersectScopes(granted: readonly string[], requested: readonly string[]) {
+  const grantedSet = new Set(granted);
+  return {
+    allowed: requested.filter((scope) => grantedSet.has(scope)),
+    denied: requested.filter((scope) => !grantedSet.has(scope)),
+  };
+}
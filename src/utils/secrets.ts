@@ -0,0 +1,50 @@
+import crypto from "crypto";
+import fs from "fs";
+
+// Secrets that must rotate without downtime are lists: <NAME> holds them
+// comma-separated, or <NAME>_FILE names a file with one per line (and wins
+// when both are set). The first entry signs; every entry is accepted, so a new
+// key goes first while the old one stays listed until clients have moved on.
+// Only the number of keys is ever logged.
+export function readSecretList(name: string): string[] {
+  const file = process.env[`${name}_FILE`]?.trim();
+  const raw = file ? fs.readFileSync(file, "utf8") : (process.env[name] ?? "");
+  const secrets = raw
+    .split(file ? /\r?\n/ : ",")
+    .map((secret) => secret.trim())
+    .filter(Boolean);
+  if (secrets.length > 0) {
+    console.log(`[secrets] ${name}: ${secrets.length} key(s) loaded${file ? " from file" : ""}`);
+  }
+  return secrets;
+}
+
+// Wraps a secret setting's value (a single key or a key list) so reports such
+// as describeSettings only show fingerprints; serialized by accident it shows
+// a placeholder instead of the value.
+export class Secret {
+  readonly keys: readonly string[];
+
+  constructor(value: string | readonly string[] | undefined) {
+    this.keys = typeof value === "string" ? [value] : (value ?? []);
+  }
+
+  toJSON() {
+    return "[secret]";
+  }
+}
+
+function digest(value: string) {
+  return crypto.createHash("sha256").update(value).digest();
+}
+
+// Compares digests so neither the length nor the position of a match leaks;
+// every entry is checked even after a match.
+export function matchesAnySecret(presented: string, secrets: readonly string[]) {
+  const presentedDigest = digest(presented);
+  let matched = false;
+  for (const secret of secrets) {
+    matched = crypto.timingSafeEqual(presentedDigest, digest(secret)) || matched;
+  }
+  return matched;
+}
@@ -0,0 +1,39 @@
+export type DeviceDescriptor = {
+  browser: string;
+  os: string;
+  description: string;
+};
+
+// Ordered so that more specific tokens win (Edge and Opera also claim Chrome,
+// Chrome also claims Safari).
+const BROWSERS: [RegExp, string][] = [
+  [/Edg(e|A|iOS)?\//, "Edge"],
+  [/OPR\/|Opera/, "Opera"],
+  [/Firefox\/|FxiOS\//, "Firefox"],
+  [/Chrome\/|CriOS\//, "Chrome"],
+  [/Safari\//, "Safari"],
+  [/curl\//, "curl"],
+  [/PostmanRuntime\//, "Postman"],
+];
+
+const OPERATING_SYSTEMS: [RegExp, string][] = [
+  [/Windows NT/, "Windows"],
+  [/iPhone|iPad|iPod/, "iOS"],
+  [/Android/, "Android"],
+  [/Mac OS X|Macintosh/, "macOS"],
+  [/CrOS/, "ChromeOS"],
+  [/Linux/, "Linux"],
+];
+
+function match(userAgent: string, table: [RegExp, string][]) {
+  return table.find(([pattern]) => pattern.test(userAgent))?.[1] ?? "Unknown";
+}
+
+export function parseUserAgent(userAgent: string | undefined): DeviceDescriptor {
+  if (!userAgent) {
+    return { browser: "Unknown", os: "Unknown", description: "Unknown device" };
+  }
+  const browser = match(userAgent, BROWSERS);
+  const os = match(userAgent, OPERATING_SYSTEMS);
+  return { browser, os, description: `${browser} on ${os}` };
+}
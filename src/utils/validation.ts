@@ -8,17 +8,3 @@ export function isValidEmail(email: string) {
   }
   return !local.includes("..") && !domain.includes("..");
 }
-
-import { parseNumberEnv } from "./env";
-
-export const PASSWORD_MIN_LENGTH = parseNumberEnv("PASSWORD_MIN_LENGTH", 8);
-
-export function isStrongPassword(password: string) {
-  return (
-    password.length >= PASSWORD_MIN_LENGTH &&
-    /[a-z]/.test(password) &&
-    /[A-Z]/.test(password) &&
-    /\d/.test(password) &&
-    /[^A-Za-z0-9]/.test(password)
-  );
-}
@@ -0,0 +1,72 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { login, needsMongo, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+const DAY_MS = 24 * 60 * 60 * 1000;
+
+describe("account deletion grace period", { skip: needsMongo }, () => {
+  let server: TestServer;
+  let purgeDeletedAccounts: typeof import("../src/maintenance").purgeDeletedAccounts;
+  const clock = new ManualClock();
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({ ACCOUNT_DELETION_GRACE_DAYS: "14" });
+    ({ purgeDeletedAccounts } = await import("../src/maintenance"));
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  it("keeps the account usable and its email taken until the purge", async () => {
+    const { token } = await registerUser(server, "leaving@example.com");
+    const deletion = await server.request("DELETE", "/auth/me", { token });
+    assert.equal(deletion.status, 200);
+    assert.equal(new Date(deletion.body.deletionScheduledAt).getTime(), clock.now() + 14 * DAY_MS);
+
+    const pending = await login(server, "leaving@example.com");
+    assert.equal(pending.status, 200);
+    assert.equal(pending.body.warning, "pending_deletion");
+
+    const reuse = await server.request("POST", "/auth/register", {
+      body: { email: "leaving@example.com", password: TEST_PASSWORD },
+    });
+    assert.equal(reuse.status, 409);
+
+    clock.advance(13 * DAY_MS);
+    assert.equal(await purgeDeletedAccounts(), 0);
+  });
+
+  it("can be cancelled during the grace period", async () => {
+    const { token } = (await login(server, "leaving@example.com")).body;
+    assert.equal((await server.request("POST", "/auth/me/cancel-deletion", { token })).status, 200);
+    assert.equal((await server.request("POST", "/auth/me/cancel-deletion", { token })).status, 409);
+
+    clock.advance(30 * DAY_MS);
+    assert.equal(await purgeDeletedAccounts(), 0);
+    assert.equal((await login(server, "leaving@example.com")).status, 200);
+  });
+
+  it("purges the user, sessions, and audit trail once, after the grace period", async () => {
+    const { token, id } = await registerUser(server, "purged@example.com");
+    assert.equal((await server.request("DELETE", "/auth/me", { token })).status, 200);
+
+    clock.advance(14 * DAY_MS + 1000);
+    assert.equal(await purgeDeletedAccounts(), 1);
+    assert.equal(await purgeDeletedAccounts(), 0);
+
+    assert.equal((await login(server, "purged@example.com")).status, 401);
+    const { getAuditCollection } = await import("../src/audit");
+    assert.equal(await (await getAuditCollection()).countDocuments({ userId: id }), 0);
+    const { getSessionsCollection } = await import("../src/sessions");
+    assert.equal(await (await getSessionsCollection()).countDocuments({ userId: id }), 0);
+
+    const again = await server.request("POST", "/auth/register", {
+      body: { email: "purged@example.com", password: TEST_PASSWORD },
+    });
+    assert.equal(again.status, 201);
+  });
+});
@@ -0,0 +1,55 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerAdmin, startApp, TestServer } from "./helpers";
+
+const ADMIN_TOKEN = "admin-listener-token";
+
+// The admin listener is started here rather than on ADMIN_PORT itself; the
+// setting only has to be present for the public app to hide the routes.
+describe("ADMIN_PORT", () => {
+  let server: TestServer;
+  let adminServer: TestServer;
+
+  before(async () => {
+    server = await startApp({ ADMIN_PORT: "9091", ADMIN_TOKEN });
+    const { adminApp } = await import("../src/adminApp");
+    adminServer = await TestServer.start(adminApp);
+  });
+
+  after(async () => {
+    await adminServer.close();
+    await server.close();
+  });
+
+  const withAdminToken = (token?: string) => ({ token, headers: { "X-Admin-Token": ADMIN_TOKEN } });
+
+  it("hides operational routes on the main port", async () => {
+    for (const path of ["/healthz", "/db/healthz", "/admin/stats", "/admin/config", "/debug/token"]) {
+      const response = await server.request("GET", path, withAdminToken());
+      assert.equal(response.status, 404, path);
+      assert.equal(response.body.error.code, "not_found");
+    }
+    assert.equal((await server.request("GET", "/auth/status")).status, 200);
+  });
+
+  it("serves them on the admin port with the admin token", async () => {
+    assert.equal((await adminServer.request("GET", "/healthz")).status, 401);
+    const wrong = await adminServer.request("GET", "/healthz", { headers: { "X-Admin-Token": "wrong" } });
+    assert.equal(wrong.status, 401);
+    assert.equal((await adminServer.request("GET", "/healthz", withAdminToken())).status, 200);
+  });
+
+  it("does not serve the public API on the admin port", async () => {
+    const response = await adminServer.request("GET", "/auth/status", withAdminToken());
+    assert.equal(response.status, 404);
+  });
+
+  it("still requires an admin JWT behind the admin token", { skip: needsMongo }, async () => {
+    const { token } = await registerAdmin(server, "port-admin@example.com");
+    assert.equal((await adminServer.request("GET", "/admin/stats", withAdminToken())).status, 401);
+    assert.equal((await adminServer.request("GET", "/admin/stats", { token })).status, 401);
+    const stats = await adminServer.request("GET", "/admin/stats", withAdminToken(token));
+    assert.equal(stats.status, 200);
+    assert.ok(stats.body.stats);
+  });
+});
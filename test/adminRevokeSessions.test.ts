@@ -0,0 +1,94 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { login, needsMongo, registerAdmin, registerUser, startApp, type TestServer } from "./helpers";
+
+describe("POST /admin/users/:id/revoke-sessions", { skip: needsMongo }, () => {
+  let server: TestServer;
+  let adminToken: string;
+
+  const revoke = (userId: string, query = "") =>
+    server.request("POST", `/admin/users/${userId}/revoke-sessions${query}`, { token: adminToken });
+
+  // Registration opens one session; each extra login opens another.
+  const userWithSessions = async (email: string, sessions: number) => {
+    const { id, token } = await registerUser(server, email);
+    const tokens = [token];
+    for (let i = 1; i < sessions; i += 1) {
+      tokens.push((await login(server, email)).body.token);
+    }
+    return { id, tokens };
+  };
+
+  before(async () => {
+    server = await startApp();
+    ({ token: adminToken } = await registerAdmin(server, "support@example.com"));
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("reports zero for a user without sessions", async () => {
+    const response = await revoke("000000000000000000000000");
+    assert.equal(response.status, 200);
+    assert.equal(response.body.revoked, 0);
+  });
+
+  it("revokes a single session", async () => {
+    const { id, tokens } = await userWithSessions("one@example.com", 1);
+    assert.equal((await revoke(id)).body.revoked, 1);
+    assert.equal((await server.request("GET", "/auth/me", { token: tokens[0] })).status, 401);
+  });
+
+  it("revokes many sessions and audits the acting admin", async () => {
+    const { id, tokens } = await userWithSessions("many@example.com", 4);
+    assert.equal((await revoke(id)).body.revoked, 4);
+    for (const token of tokens) {
+      assert.equal((await server.request("GET", "/auth/me", { token })).status, 401);
+    }
+
+    const { getAuditCollection } = await import("../src/audit");
+    const audit = await getAuditCollection();
+    const event = await audit.findOne({ type: "admin.sessions_revoked", userId: id });
+    assert.ok(event);
+    assert.ok(event.actorId);
+    assert.deepEqual(event.details, { revoked: 4 });
+  });
+
+  it("revokes and audits the same way through DELETE /admin/users/:id/sessions", async () => {
+    const { id, tokens } = await userWithSessions("delete@example.com", 2);
+    const response = await server.request("DELETE", `/admin/users/${id}/sessions`, { token: adminToken });
+    assert.equal(response.status, 200);
+    assert.equal(response.body.revoked, 2);
+    assert.equal((await server.request("GET", "/auth/me", { token: tokens[1] })).status, 401);
+
+    const { getAuditCollection } = await import("../src/audit");
+    const event = await (await getAuditCollection()).findOne({ type: "admin.sessions_revoked", userId: id });
+    assert.deepEqual(event?.details, { revoked: 2 });
+  });
+
+  it("only reports sessions on a dry run", async () => {
+    const { id, tokens } = await userWithSessions("dry-run@example.com", 2);
+    const response = await revoke(id, "?dry_run=true");
+    assert.equal(response.body.dryRun, true);
+    assert.equal(response.body.revoked, 2);
+    assert.equal(response.body.sessions.length, 2);
+    assert.equal((await server.request("GET", "/auth/me", { token: tokens[0] })).status, 200);
+  });
+
+  it("revokes sessions of a user whose record is gone", async () => {
+    const { id, tokens } = await userWithSessions("orphan@example.com", 2);
+    const { getUsersCollection } = await import("../src/users");
+    await (await getUsersCollection()).deleteOne({ email: "orphan@example.com" });
+
+    assert.equal((await revoke(id)).body.revoked, 2);
+    assert.equal((await server.request("GET", "/auth/validate", { token: tokens[1] })).status, 401);
+  });
+
+  it("is forbidden to non-admins", async () => {
+    const { id, tokens } = await userWithSessions("not-admin@example.com", 1);
+    const response = await server.request("POST", `/admin/users/${id}/revoke-sessions`, { token: tokens[0] });
+    assert.equal(response.status, 403);
+    assert.equal(response.body.error.code, "forbidden");
+  });
+});
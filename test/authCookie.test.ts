@@ -0,0 +1,47 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+const COOKIE_NAME = "auth_token";
+
+describe("AUTH_COOKIE_ONLY", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ AUTH_COOKIE_ONLY: "true" });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  const withCookie = (value: string) => ({ headers: { Cookie: `${COOKIE_NAME}=${value}` } });
+
+  it("reads the token from the cookie", async () => {
+    const response = await server.request("GET", "/auth/me", withCookie("not-a-jwt"));
+    assert.equal(response.status, 401);
+    assert.equal(response.body.error.code, "invalid_token");
+  });
+
+  it("sets the token as a cookie on login and accepts it", { skip: needsMongo }, async () => {
+    const credentials = { email: "cookie@example.com", password: TEST_PASSWORD };
+    assert.equal((await server.request("POST", "/auth/register", { body: credentials })).status, 201);
+
+    const login = await server.request("POST", "/auth/login", { body: credentials });
+    assert.equal(login.status, 200);
+    assert.equal(login.body.token, undefined);
+    assert.equal(login.body.access_token, undefined);
+    assert.ok(!JSON.stringify(login.body).includes("eyJ"));
+
+    const [cookie] = login.headers.getSetCookie();
+    assert.match(cookie, new RegExp(`^${COOKIE_NAME}=[^;]+`));
+    assert.match(cookie, /; HttpOnly/i);
+    assert.match(cookie, /; SameSite=Strict/i);
+    const token = cookie.slice(COOKIE_NAME.length + 1, cookie.indexOf(";"));
+
+    const me = await server.request("GET", "/auth/me", withCookie(token));
+    assert.equal(me.status, 200);
+    assert.equal(me.body.user.email, "cookie@example.com");
+    assert.equal((await server.request("GET", "/auth/me")).status, 401);
+  });
+});
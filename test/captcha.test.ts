@@ -0,0 +1,57 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { CaptchaProvider, startApp, type TestServer } from "./helpers";
+
+const CAPTCHA_SECRET = "captcha-secret";
+
+// Registration checks the CAPTCHA before anything else, so a request without
+// credentials that gets as far as missing_credentials has passed it.
+describe("CAPTCHA verification, failing closed", () => {
+  const provider = new CaptchaProvider();
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({
+      CAPTCHA_PROVIDER: "hcaptcha",
+      CAPTCHA_VERIFY_URL: await provider.listen(),
+      CAPTCHA_SECRET,
+      CAPTCHA_TIMEOUT_MS: "200",
+      CAPTCHA_FAIL_OPEN: "false",
+    });
+  });
+
+  after(async () => {
+    await server.close();
+    await provider.close();
+  });
+
+  const register = (captchaToken?: string) => server.request("POST", "/auth/register", { body: { captchaToken } });
+
+  it("requires a token", async () => {
+    const response = await register();
+    assert.equal(response.status, 403);
+    assert.equal(response.body.error.code, "captcha_required");
+  });
+
+  it("lets a verified request through", async () => {
+    const response = await register("pass-1");
+    assert.equal(response.status, 400);
+    assert.equal(response.body.error.code, "missing_credentials");
+    const form = provider.requests.at(-1)!;
+    assert.equal(form.get("secret"), CAPTCHA_SECRET);
+    assert.equal(form.get("response"), "pass-1");
+    assert.ok(form.has("remoteip"));
+  });
+
+  it("rejects a token the provider refuses", async () => {
+    const response = await register("fail-1");
+    assert.equal(response.status, 403);
+    assert.equal(response.body.error.code, "captcha_failed");
+  });
+
+  it("rejects requests while the provider times out", async () => {
+    const response = await register("slow-1");
+    assert.equal(response.status, 503);
+    assert.equal(response.body.error.code, "captcha_unavailable");
+  });
+});
@@ -0,0 +1,45 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { CaptchaProvider, startApp, type TestServer } from "./helpers";
+
+// Same checks as captcha.test.ts with CAPTCHA_FAIL_OPEN, which only changes
+// what happens while the provider is unreachable.
+describe("CAPTCHA verification, failing open", () => {
+  const provider = new CaptchaProvider();
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({
+      CAPTCHA_PROVIDER: "hcaptcha",
+      CAPTCHA_VERIFY_URL: await provider.listen(),
+      CAPTCHA_SECRET: "captcha-secret",
+      CAPTCHA_TIMEOUT_MS: "200",
+      CAPTCHA_FAIL_OPEN: "true",
+    });
+  });
+
+  after(async () => {
+    await server.close();
+    await provider.close();
+  });
+
+  const register = (captchaToken: string) => server.request("POST", "/auth/register", { body: { captchaToken } });
+
+  it("lets a verified request through", async () => {
+    const response = await register("pass-1");
+    assert.equal(response.status, 400);
+    assert.equal(response.body.error.code, "missing_credentials");
+  });
+
+  it("still rejects a token the provider refuses", async () => {
+    const response = await register("fail-1");
+    assert.equal(response.status, 403);
+    assert.equal(response.body.error.code, "captcha_failed");
+  });
+
+  it("lets requests through while the provider times out", async () => {
+    const response = await register("slow-1");
+    assert.equal(response.status, 400);
+    assert.equal(response.body.error.code, "missing_credentials");
+  });
+});
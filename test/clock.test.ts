@@ -0,0 +1,42 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+describe("token expiry with a manual clock", () => {
+  let server: TestServer;
+  const clock = new ManualClock();
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({ JWT_EXPIRES_IN: "1h" });
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  it("rejects a token once the clock passes its expiry", { skip: needsMongo }, async () => {
+    const { token } = await registerUser(server, "expiry@example.com");
+    assert.equal((await server.request("GET", "/auth/me", { token })).status, 200);
+
+    clock.advance(59 * 60 * 1000);
+    assert.equal((await server.request("GET", "/auth/me", { token })).status, 200);
+
+    clock.advance(2 * 60 * 1000);
+    const expired = await server.request("GET", "/auth/me", { token });
+    assert.equal(expired.status, 401);
+    assert.equal(expired.body.error.code, "invalid_token");
+  });
+
+  it("refuses to verify an expired token without a database", async () => {
+    const { createToken, verifyToken } = await import("../src/utils/jwt");
+    const token = createToken({ sub: "user-1", email: "a@example.com", roles: ["user"], tenantId: "default" });
+    assert.equal(verifyToken(token).sub, "user-1");
+
+    clock.advance(60 * 60 * 1000 + 1000);
+    assert.throws(() => verifyToken(token), { name: "TokenExpiredError" });
+    assert.equal(verifyToken(token, { ignoreExpiration: true }).sub, "user-1");
+  });
+});
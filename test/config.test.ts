@@ -0,0 +1,51 @@
+import assert from "node:assert/strict";
+import crypto from "crypto";
+import { before, describe, it } from "node:test";
+import { useTestEnv } from "./helpers";
+
+const JWT_KEYS = [crypto.randomBytes(32).toString("hex"), crypto.randomBytes(32).toString("hex")];
+
+describe("effective configuration", () => {
+  let settings: Record<string, { value: string | null; source: string; from?: string }>;
+
+  before(async () => {
+    useTestEnv({
+      JWT_SECRET: JWT_KEYS.join(","),
+      SESSION_DEGRADATION_POLICY: "sometimes",
+      PASSWORD_MIN_LENGTH: "-3",
+      TOKEN_CACHE_TTL_MS: "",
+      TENANTS: "acme, Globex",
+    });
+    const { describeSettings } = await import("../src/config");
+    settings = describeSettings();
+  });
+
+  it("reports the value the service parsed, not the raw variable", () => {
+    assert.deepEqual(settings.SESSION_DEGRADATION_POLICY, {
+      value: "strict",
+      source: "env",
+      from: "SESSION_DEGRADATION_POLICY",
+    });
+    assert.equal(settings.PASSWORD_MIN_LENGTH.value, "8");
+    assert.equal(settings.TENANTS.value, "acme,globex");
+  });
+
+  it("marks defaults and unset settings", () => {
+    assert.deepEqual(settings.TOKEN_CACHE_TTL_MS, { value: "0", source: "default" });
+    assert.deepEqual(settings.SCIM_TOKEN, { value: null, source: "unset" });
+  });
+
+  it("shows secrets only as one fingerprint per key", () => {
+    const { value, source } = settings.JWT_SECRET;
+    assert.equal(source, "env");
+    assert.match(value ?? "", /^sha256:[0-9a-f]{4},sha256:[0-9a-f]{4}$/);
+    for (const key of JWT_KEYS) {
+      assert.ok(!JSON.stringify(settings).includes(key));
+    }
+  });
+
+  it("serializes a Secret as a placeholder", async () => {
+    const { Secret } = await import("../src/utils/secrets");
+    assert.equal(JSON.stringify({ key: new Secret("hunter2") }), '{"key":"[secret]"}');
+  });
+});
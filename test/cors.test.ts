@@ -0,0 +1,61 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { startApp, type TestServer } from "./helpers";
+
+const APP_ORIGIN = "https://app.example.com";
+
+describe("CORS", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({
+      CORS_ALLOWED_ORIGINS: APP_ORIGIN,
+      CORS_ROUTE_ORIGINS: "/admin=;/api=*",
+    });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  const get = (path: string, origin: string) => server.request("GET", path, { headers: { Origin: origin } });
+
+  it("allows credentialed requests from a listed origin", async () => {
+    const response = await get("/", APP_ORIGIN);
+    assert.equal(response.headers.get("access-control-allow-origin"), APP_ORIGIN);
+    assert.equal(response.headers.get("access-control-allow-credentials"), "true");
+    assert.match(response.headers.get("vary") ?? "", /Origin/);
+    assert.match(response.headers.get("access-control-expose-headers") ?? "", /X-Refreshed-Token/);
+  });
+
+  it("sends no CORS headers to other origins", async () => {
+    const response = await get("/", "https://evil.example.com");
+    assert.equal(response.headers.get("access-control-allow-origin"), null);
+    assert.equal(response.headers.get("access-control-allow-credentials"), null);
+  });
+
+  it("answers preflights with the credentials flag for a listed origin", async () => {
+    const response = await server.request("OPTIONS", "/auth/login", {
+      headers: {
+        Origin: APP_ORIGIN,
+        "Access-Control-Request-Method": "POST",
+        "Access-Control-Request-Headers": "content-type, authorization",
+      },
+    });
+    assert.equal(response.status, 204);
+    assert.equal(response.headers.get("access-control-allow-origin"), APP_ORIGIN);
+    assert.equal(response.headers.get("access-control-allow-credentials"), "true");
+    assert.equal(response.headers.get("access-control-allow-headers"), "content-type, authorization");
+  });
+
+  it("never allows credentials with a wildcard", async () => {
+    const response = await get("/api/users/1", "https://anyone.example.com");
+    assert.equal(response.headers.get("access-control-allow-origin"), "*");
+    assert.equal(response.headers.get("access-control-allow-credentials"), null);
+  });
+
+  it("refuses cross-origin access under a prefix with an empty list", async () => {
+    const response = await get("/admin/users", APP_ORIGIN);
+    assert.equal(response.headers.get("access-control-allow-origin"), null);
+  });
+});
@@ -0,0 +1,50 @@
+import assert from "node:assert/strict";
+import { setTimeout as delay } from "timers/promises";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+describe("debug capture", { skip: needsMongo }, () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ DEBUG_CAPTURE: "true", DEBUG_CAPTURE_PATH_PREFIX: "/auth/login" });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("redacts credentials in a captured login", async () => {
+    const { listDebugCaptures } = await import("../src/debugCapture");
+    await registerUser(server, "captured@example.com");
+    const response = await server.request("POST", "/auth/login", {
+      body: { email: "captured@example.com", password: TEST_PASSWORD },
+      headers: {
+        Authorization: "Bearer stale-token",
+        "X-Internal-Authorization": "Bearer internal-token",
+        "X-Api-Key": "api-key",
+      },
+    });
+    assert.equal(response.status, 200);
+
+    // Captures are written after the response is sent.
+    let captures = await listDebugCaptures("default", 10);
+    for (let attempt = 0; captures.length === 0 && attempt < 50; attempt++) {
+      await delay(20);
+      captures = await listDebugCaptures("default", 10);
+    }
+    assert.equal(captures.length, 1);
+    const [capture] = captures;
+    assert.equal(capture.path, "/auth/login");
+    assert.deepEqual(capture.request.body, { email: "captured@example.com", password: "[REDACTED]" });
+    for (const name of ["authorization", "x-internal-authorization", "x-api-key"]) {
+      assert.match(capture.request.headers[name], /^sha256:[0-9a-f]{12}$/);
+    }
+    assert.equal(capture.request.headers["content-type"], "application/json");
+
+    const body = capture.response.body as Record<string, unknown>;
+    assert.equal(body.token, "[REDACTED]");
+    assert.deepEqual(body.user, response.body.user);
+    assert.ok(!JSON.stringify(capture).includes(response.body.token));
+  });
+});
@@ -0,0 +1,79 @@
+import assert from "node:assert/strict";
+import jwt from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+describe("POST /debug/token", () => {
+  let server: TestServer;
+  let createToken: typeof import("../src/utils/jwt").createToken;
+  const clock = new ManualClock();
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({ DEBUG_ENDPOINTS: "true" });
+    ({ createToken } = await import("../src/utils/jwt"));
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  const payload = { sub: "user-1", email: "debug@example.com", roles: ["user"], tenantId: "default" };
+
+  it("reports a valid token with its header and claims", async () => {
+    const response = await server.request("POST", "/debug/token", { body: { token: createToken(payload) } });
+    assert.equal(response.status, 200);
+    assert.equal(response.body.header.alg, "HS256");
+    assert.equal(response.body.claims.sub, "user-1");
+    assert.deepEqual(response.body.validation, { valid: true });
+    // Without a jti there is no session to look up.
+    assert.equal(response.body.session, "unknown");
+  });
+
+  it("reports whether the token's session is active or revoked", { skip: needsMongo }, async () => {
+    const { token } = await registerUser(server, "debug-session@example.com");
+    const inspect = async () => (await server.request("POST", "/debug/token", { body: { token } })).body.session;
+    assert.equal(await inspect(), "active");
+
+    const current = await server.request("GET", "/auth/sessions/current", { token });
+    const revoked = await server.request("DELETE", `/auth/sessions/${current.body.session.id}`, { token });
+    assert.equal(revoked.status, 200);
+    assert.equal(await inspect(), "revoked");
+  });
+
+  it("reports a token that cannot be decoded as malformed", async () => {
+    const response = await server.request("POST", "/debug/token", { body: { token: "not-a-jwt" } });
+    assert.equal(response.status, 200);
+    assert.equal(response.body.header, null);
+    assert.equal(response.body.claims, null);
+    assert.equal(response.body.validation.reason, "malformed");
+  });
+
+  it("still decodes a token signed with another key", async () => {
+    const token = jwt.sign({ ...payload, tenant_id: "default" }, "some-other-secret-that-is-long-enough");
+    const response = await server.request("POST", "/debug/token", { body: { token } });
+    assert.equal(response.body.claims.sub, "user-1");
+    assert.equal(response.body.validation.reason, "bad_signature");
+  });
+
+  it("reports missing claims", async () => {
+    const token = jwt.sign({ sub: "user-1" }, process.env.JWT_SECRET as string);
+    const response = await server.request("POST", "/debug/token", { body: { token } });
+    assert.equal(response.body.validation.reason, "missing_claim");
+  });
+
+  it("reports expiry with the expiry time", async () => {
+    const token = createToken(payload, {}, { expiresIn: 60 });
+    clock.advance(2 * 60 * 1000);
+    const response = await server.request("POST", "/debug/token", { body: { token } });
+    assert.equal(response.body.validation.reason, "expired");
+    assert.ok(response.body.validation.expiredAt);
+  });
+
+  it("requires a token", async () => {
+    const response = await server.request("POST", "/debug/token", { body: {} });
+    assert.equal(response.status, 400);
+  });
+});
@@ -0,0 +1,21 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { startApp, type TestServer } from "./helpers";
+
+describe("debug routes without DEBUG_ENDPOINTS", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ DEBUG_ENDPOINTS: "false" });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("does not register POST /debug/token at all", async () => {
+    const response = await server.request("POST", "/debug/token", { body: { token: "not-a-jwt" } });
+    assert.equal(response.status, 404);
+    assert.equal(response.body.error.code, "not_found");
+  });
+});
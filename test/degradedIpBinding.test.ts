@@ -0,0 +1,55 @@
+import assert from "node:assert/strict";
+import crypto from "crypto";
+import { after, before, describe, it } from "node:test";
+import { startApp, type TestServer } from "./helpers";
+
+const BOUND_IP = "203.0.113.9";
+
+describe("IP binding while the session store is down", { timeout: 120_000 }, () => {
+  let server: TestServer;
+  let createToken: typeof import("../src/utils/jwt").createToken;
+
+  before(async () => {
+    server = await startApp({
+      // Nothing listens here, so every session lookup fails.
+      MONGODB_URI: "mongodb://127.0.0.1:1",
+      MONGODB_OP_TIMEOUT_MS: "500",
+      SESSION_DEGRADATION_POLICY: "allow_with_warning",
+    });
+    ({ createToken } = await import("../src/utils/jwt"));
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  const userToken = (boundIp?: string) =>
+    createToken({
+      sub: "user-1",
+      email: "user@example.com",
+      roles: ["user"],
+      tenantId: "default",
+      jti: crypto.randomUUID(),
+      ...(boundIp ? { boundIp } : {}),
+    });
+
+  // Token validation needs nothing from the database beyond the session check.
+  const validate = (token: string, ip: string) =>
+    server.request("GET", "/auth/validate", { token, headers: { "X-Forwarded-For": ip } });
+
+  it("accepts a bound token from its own network", async () => {
+    const response = await validate(userToken(BOUND_IP), BOUND_IP);
+    assert.equal(response.status, 200);
+    assert.equal(response.headers.get("x-degraded-auth"), "true");
+  });
+
+  it("refuses a bound token from another network", async () => {
+    const response = await validate(userToken(BOUND_IP), "198.51.100.1");
+    assert.equal(response.status, 401);
+    assert.equal(response.body.error.code, "token_ip_mismatch");
+  });
+
+  it("leaves unbound tokens usable from anywhere", async () => {
+    assert.equal((await validate(userToken(), "198.51.100.1")).status, 200);
+  });
+});
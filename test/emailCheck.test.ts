@@ -0,0 +1,58 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const CHECKS_PER_IP = 3;
+
+describe("email availability check", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ EMAIL_CHECK_RATE_LIMIT_MAX: String(CHECKS_PER_IP) });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  // Each test uses its own client address so it gets a fresh budget.
+  function check(email: string, ip: string) {
+    return server.request("GET", `/auth/register/check-email?email=${encodeURIComponent(email)}`, {
+      headers: { "X-Forwarded-For": ip },
+    });
+  }
+
+  it("reports taken and available emails, normalized like registration", { skip: needsMongo }, async () => {
+    await registerUser(server, "taken@example.com");
+
+    const taken = await check("  Taken@Example.COM ", "198.51.100.1");
+    assert.equal(taken.status, 200);
+    assert.equal(taken.body.available, false);
+    assert.equal(taken.headers.get("cache-control"), "no-store");
+
+    const available = await check("free@example.com", "198.51.100.1");
+    assert.equal(available.status, 200);
+    assert.equal(available.body.available, true);
+  });
+
+  it("rejects a missing or malformed email", async () => {
+    assert.equal((await check("not-an-email", "198.51.100.2")).body.error.code, "invalid_email");
+    const missing = await server.request("GET", "/auth/register/check-email", {
+      headers: { "X-Forwarded-For": "198.51.100.2" },
+    });
+    assert.equal(missing.status, 400);
+  });
+
+  it("limits checks per client IP", async () => {
+    const statuses = [];
+    for (let i = 0; i <= CHECKS_PER_IP; i += 1) {
+      statuses.push((await check("invalid", "192.0.2.10")).status);
+    }
+    assert.deepEqual(statuses, [...Array(CHECKS_PER_IP).fill(400), 429]);
+
+    const limited = await check("invalid", "192.0.2.10");
+    assert.equal(limited.body.error.code, "rate_limited");
+    assert.ok(Number(limited.headers.get("retry-after")) > 0);
+    assert.equal((await check("invalid", "192.0.2.11")).status, 400);
+  });
+});
@@ -0,0 +1,56 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { TestServer, useTestEnv } from "./helpers";
+
+const SECRET_DETAIL = "connection string mongodb://user:hunter2@db";
+
+describe("errors thrown by route handlers", () => {
+  let server: TestServer;
+  let connections = 0;
+
+  before(async () => {
+    useTestEnv();
+    const { default: express } = await import("express");
+    const { requestId } = await import("../src/middleware/requestId");
+    const { errorHandler } = await import("../src/utils/errors");
+    const app = express()
+      .use(requestId)
+      .get("/throws", () => {
+        throw new Error(SECRET_DETAIL);
+      })
+      .get("/rejects", async () => {
+        throw new Error(SECRET_DETAIL);
+      })
+      .get("/ok", (_req, res) => {
+        res.json({ ok: true });
+      })
+      .use(errorHandler);
+    server = await TestServer.start({
+      listen(port: number) {
+        return app.listen(port).on("connection", () => (connections += 1));
+      },
+    });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  for (const path of ["/throws", "/rejects"]) {
+    it(`answers ${path} with a clean 500 and keeps the connection`, async () => {
+      const opened = connections;
+      const response = await server.request("GET", path, { headers: { "X-Request-Id": "crash-1" } });
+      assert.equal(response.status, 500);
+      assert.deepEqual(response.body, {
+        ok: false,
+        error: { code: "internal_error", message: "Internal server error", details: null, request_id: "crash-1" },
+      });
+      assert.ok(!JSON.stringify(response.body).includes("hunter2"));
+      assert.notEqual(response.headers.get("connection"), "close");
+
+      assert.equal((await server.request("GET", "/ok")).status, 200);
+      // Both requests went over one keep-alive connection.
+      assert.ok(connections - opened <= 1);
+    });
+  }
+});
@@ -0,0 +1,141 @@
+import assert from "node:assert/strict";
+import fs from "fs";
+import os from "os";
+import path from "path";
+import { describe, it } from "node:test";
+import { describeLocation, MaxMindGeoIpLookup } from "../src/geoip";
+import { parseIp } from "../src/utils/ip";
+import { MmdbReader } from "../src/utils/mmdb";
+
+// Builds small MaxMind DB files in the documented format, so the reader is
+// checked against the spec rather than against a vendored database.
+
+type Value = string | number | { pointer: number } | { [key: string]: Value };
+
+function encode(value: Value): number[] {
+  if (typeof value === "string") {
+    const bytes = [...Buffer.from(value)];
+    return [0x40 | bytes.length, ...bytes];
+  }
+  if (typeof value === "number") {
+    // uint32
+    return [0xc0 | 4, (value >>> 24) & 0xff, (value >>> 16) & 0xff, (value >>> 8) & 0xff, value & 0xff];
+  }
+  if (typeof value.pointer === "number") {
+    // Smallest pointer form: 11 bits, relative to the data section.
+    return [0x20 | (value.pointer >> 8), value.pointer & 0xff];
+  }
+  const entries = Object.entries(value as Record<string, Value>);
+  return [0xe0 | entries.length, ...entries.flatMap(([key, item]) => [...encode(key), ...encode(item)])];
+}
+
+type Network = { cidr: string; record: Value };
+type Child = number | { data: number } | null;
+
+function buildDatabase(recordSize: 24 | 28 | 32, ipVersion: 4 | 6, networks: Network[], shared: Value[] = []) {
+  const data: number[] = shared.flatMap(encode);
+  const nodes: [Child, Child][] = [[null, null]];
+  for (const { cidr, record } of networks) {
+    const [address, prefix] = cidr.split("/");
+    let bytes = parseIp(address)!;
+    let prefixLength = Number(prefix);
+    if (ipVersion === 6 && bytes.length === 4) {
+      bytes = [...Array(12).fill(0), ...bytes];
+      prefixLength += 96;
+    }
+    let node = 0;
+    for (let i = 0; i < prefixLength - 1; i += 1) {
+      const bit = (bytes[i >> 3] >> (7 - (i & 7))) & 1;
+      let next = nodes[node][bit];
+      if (typeof next !== "number") {
+        next = nodes.push([null, null]) - 1;
+        nodes[node][bit] = next;
+      }
+      node = next;
+    }
+    const last = prefixLength - 1;
+    nodes[node][(bytes[last >> 3] >> (7 - (last & 7))) & 1] = { data: data.length };
+    data.push(...encode(record));
+  }
+
+  const nodeCount = nodes.length;
+  const recordValue = (child: Child) =>
+    child === null ? nodeCount : typeof child === "number" ? child : nodeCount + 16 + child.data;
+  const tree = Buffer.alloc((nodeCount * recordSize * 2) / 8);
+  nodes.forEach(([left, right], index) => {
+    const offset = (index * recordSize * 2) / 8;
+    const [l, r] = [recordValue(left), recordValue(right)];
+    if (recordSize === 24) {
+      tree.writeUIntBE(l, offset, 3);
+      tree.writeUIntBE(r, offset + 3, 3);
+    } else if (recordSize === 28) {
+      tree.writeUIntBE(l & 0xffffff, offset, 3);
+      tree[offset + 3] = (((l >>> 24) & 0x0f) << 4) | ((r >>> 24) & 0x0f);
+      tree.writeUIntBE(r & 0xffffff, offset + 4, 3);
+    } else {
+      tree.writeUInt32BE(l, offset);
+      tree.writeUInt32BE(r, offset + 4);
+    }
+  });
+
+  const metadata = encode({ node_count: nodeCount, record_size: recordSize, ip_version: ipVersion });
+  return Buffer.concat([
+    tree,
+    Buffer.alloc(16),
+    Buffer.from(data),
+    Buffer.from("\xAB\xCD\xEFMaxMind.com", "latin1"),
+    Buffer.from(metadata),
+  ]);
+}
+
+const BERLIN = { country: { iso_code: "DE" }, city: { names: { en: "Berlin" } } };
+const NETWORKS: Network[] = [
+  { cidr: "1.2.3.0/24", record: BERLIN },
+  // Points back at the shared "FR" string at the start of the data section.
+  { cidr: "5.6.0.0/16", record: { country: { iso_code: { pointer: 0 } } } },
+];
+const IPV6_NETWORK: Network = { cidr: "2001:db8::/32", record: { country: { iso_code: "US" } } };
+
+describe("MaxMind DB reader", () => {
+  for (const recordSize of [24, 28, 32] as const) {
+    it(`reads IPv4 records from a ${recordSize}-bit IPv6 tree`, () => {
+      const reader = new MmdbReader(buildDatabase(recordSize, 6, [...NETWORKS, IPV6_NETWORK], ["FR"]));
+      assert.deepEqual(reader.get("1.2.3.4"), BERLIN);
+      assert.deepEqual(reader.get("::ffff:1.2.3.250"), BERLIN);
+      assert.deepEqual(reader.get("5.6.200.1"), { country: { iso_code: "FR" } });
+      assert.deepEqual(reader.get("2001:db8::1"), { country: { iso_code: "US" } });
+      assert.equal(reader.get("1.2.4.1"), null);
+      assert.equal(reader.get("2001:db9::1"), null);
+      assert.equal(reader.get("not-an-ip"), null);
+    });
+  }
+
+  it("reads an IPv4-only database and answers null for IPv6", () => {
+    const reader = new MmdbReader(buildDatabase(24, 4, NETWORKS, ["FR"]));
+    assert.deepEqual(reader.get("1.2.3.4"), BERLIN);
+    assert.equal(reader.get("2001:db8::1"), null);
+  });
+
+  it("refuses a file without MaxMind metadata", () => {
+    assert.throws(() => new MmdbReader(Buffer.from("not a database")), /metadata not found/);
+  });
+});
+
+describe("MaxMind GeoIP lookup", () => {
+  it("maps records to a country and English city name", async () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "geoip-"));
+    const file = path.join(dir, "GeoLite2-City.mmdb");
+    try {
+      fs.writeFileSync(file, buildDatabase(28, 6, NETWORKS, ["FR"]));
+      const geoIp = new MaxMindGeoIpLookup(file);
+      const berlin = await geoIp.lookup("1.2.3.4");
+      assert.deepEqual(berlin, { country: "DE", city: "Berlin" });
+      assert.equal(describeLocation(berlin), "Berlin, DE");
+      assert.deepEqual(await geoIp.lookup("5.6.7.8"), { country: "FR" });
+      assert.equal(await geoIp.lookup("9.9.9.9"), null);
+      assert.equal(describeLocation(null), "Unknown location");
+    } finally {
+      fs.rmSync(dir, { recursive: true, force: true });
+    }
+  });
+});
@@ -0,0 +1,196 @@
+import crypto from "crypto";
+import { once } from "events";
+import http, { type Server } from "http";
+import type { CommandStartedEvent } from "mongodb";
+import type { AddressInfo } from "net";
+
+export const TEST_MONGODB_URI = process.env.TEST_MONGODB_URI;
+
+// Pass as the skip option of tests that need a database.
+export const needsMongo = TEST_MONGODB_URI ? false : "TEST_MONGODB_URI is not set";
+
+export const TEST_PASSWORD = "StrongPass123!";
+
+// Settings are read when modules load, so this must run before the app (or
+// any module under test) is imported. Rate limits are lifted unless a test
+// overrides them.
+export function useTestEnv(overrides: Record<string, string> = {}) {
+  Object.assign(process.env, {
+    JWT_SECRET: crypto.randomBytes(32).toString("hex"),
+    MONGODB_URI: TEST_MONGODB_URI ?? "mongodb://127.0.0.1:1",
+    MONGODB_DB: `adventure_test_${crypto.randomBytes(6).toString("hex")}`,
+    MONGODB_TLS: "false",
+    AUTH_RATE_LIMIT_MAX: "10000",
+    LOGIN_RATE_LIMIT_MAX: "10000",
+    PASSWORD_RESET_RATE_LIMIT_MAX: "10000",
+    EMAIL_CHECK_RATE_LIMIT_MAX: "10000",
+    NEW_DEVICE_NOTIFICATIONS: "false",
+    ...overrides,
+  });
+}
+
+export type TestResponse = {
+  status: number;
+  headers: Headers;
+  body: any;
+};
+
+export type RequestOptions = {
+  body?: unknown;
+  // Sent as-is, e.g. NDJSON; otherwise body is sent as JSON.
+  rawBody?: string;
+  token?: string;
+  headers?: Record<string, string>;
+};
+
+export class TestServer {
+  private constructor(
+    private readonly server: Server,
+    readonly url: string,
+  ) {}
+
+  static async start(app: { listen(port: number): Server }) {
+    const server = app.listen(0);
+    await once(server, "listening");
+    const { port } = server.address() as AddressInfo;
+    return new TestServer(server, `http://127.0.0.1:${port}`);
+  }
+
+  async request(method: string, path: string, options: RequestOptions = {}): Promise<TestResponse> {
+    const headers: Record<string, string> = { ...options.headers };
+    if (options.body !== undefined) {
+      headers["Content-Type"] ??= "application/json";
+    }
+    if (options.token) {
+      headers.Authorization = `Bearer ${options.token}`;
+    }
+    const response = await fetch(`${this.url}${path}`, {
+      method,
+      headers,
+      body: options.rawBody ?? (options.body === undefined ? undefined : JSON.stringify(options.body)),
+    });
+    const text = await response.text();
+    let body: unknown = text;
+    try {
+      body = text ? JSON.parse(text) : undefined;
+    } catch {
+      // Not JSON; keep the text.
+    }
+    return { status: response.status, headers: response.headers, body };
+  }
+
+  async close() {
+    this.server.closeAllConnections();
+    await new Promise((resolve) => this.server.close(resolve));
+    await dropTestDatabase();
+  }
+}
+
+// Drops this process's test database and closes the client, which would
+// otherwise keep the process alive.
+export async function dropTestDatabase() {
+  const { closeMongoClient, getMongoClient } = await import("../src/db");
+  // Tests that point MONGODB_URI elsewhere have no database to drop.
+  if (TEST_MONGODB_URI && process.env.MONGODB_URI === TEST_MONGODB_URI) {
+    const client = await getMongoClient();
+    await client.db(process.env.MONGODB_DB).dropDatabase();
+  }
+  await closeMongoClient();
+}
+
+// Runs fn and returns how many finds it sent to the sessions collection, i.e.
+// how many session lookups actually reached the database.
+export async function countSessionLookups(fn: () => Promise<unknown>) {
+  const { getMongoClient } = await import("../src/db");
+  const client = await getMongoClient();
+  let lookups = 0;
+  const onCommand = (event: CommandStartedEvent) => {
+    if (event.commandName === "find" && event.command.find === "sessions") {
+      lookups += 1;
+    }
+  };
+  client.on("commandStarted", onCommand);
+  try {
+    await fn();
+  } finally {
+    client.off("commandStarted", onCommand);
+  }
+  return lookups;
+}
+
+// Sets up the environment, then loads the app and listens on a random port.
+export async function startApp(overrides: Record<string, string> = {}) {
+  useTestEnv(overrides);
+  const { app } = await import("../src/app");
+  return TestServer.start(app);
+}
+
+export async function registerUser(server: TestServer, email: string, password = TEST_PASSWORD) {
+  const response = await server.request("POST", "/auth/register", { body: { email, password } });
+  if (response.status !== 201) {
+    throw new Error(`Registration of ${email} failed with ${response.status}: ${JSON.stringify(response.body)}`);
+  }
+  return { token: response.body.token as string, id: response.body.user.id as string };
+}
+
+export async function login(server: TestServer, email: string, password = TEST_PASSWORD) {
+  return server.request("POST", "/auth/login", { body: { email, password } });
+}
+
+// Registers a user and gives it the admin role directly in the database.
+export async function registerAdmin(server: TestServer, email: string) {
+  const { getUsersCollection } = await import("../src/users");
+  const { id } = await registerUser(server, email);
+  const users = await getUsersCollection();
+  await users.updateOne({ email }, { $set: { roles: ["user", "admin"] } });
+  const response = await login(server, email);
+  return { id, token: response.body.token as string };
+}
+
+// Inserts a user straight into the database, for setups where registration
+// is closed.
+export async function insertUser(email: string, roles: string[] = ["user"]) {
+  const { getUsersCollection } = await import("../src/users");
+  const { createPasswordHash } = await import("../src/utils/password");
+  const { salt, hash } = await createPasswordHash(TEST_PASSWORD);
+  const users = await getUsersCollection();
+  const result = await users.insertOne({
+    tenantId: "default",
+    email,
+    passwordHash: hash,
+    passwordSalt: salt,
+    roles: roles as ("user" | "admin")[],
+    createdAt: new Date(),
+  });
+  return result.insertedId.toHexString();
+}
+
+// Stands in for an hCaptcha/Turnstile siteverify endpoint. Tokens starting
+// with "pass" succeed, "slow" never gets an answer, and anything else fails.
+export class CaptchaProvider {
+  readonly requests: URLSearchParams[] = [];
+  private readonly server = http.createServer((req, res) => {
+    const chunks: Buffer[] = [];
+    req.on("data", (chunk: Buffer) => chunks.push(chunk));
+    req.on("end", () => {
+      const form = new URLSearchParams(Buffer.concat(chunks).toString());
+      this.requests.push(form);
+      const token = form.get("response") ?? "";
+      if (!token.startsWith("slow")) {
+        res.writeHead(200, { "Content-Type": "application/json" });
+        res.end(JSON.stringify({ success: token.startsWith("pass") }));
+      }
+    });
+  });
+
+  async listen() {
+    this.server.listen(0, "127.0.0.1");
+    await once(this.server, "listening");
+    return `http://127.0.0.1:${(this.server.address() as AddressInfo).port}/siteverify`;
+  }
+
+  async close() {
+    this.server.closeAllConnections();
+    await new Promise((resolve) => this.server.close(resolve));
+  }
+}
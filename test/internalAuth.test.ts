@@ -0,0 +1,56 @@
+import assert from "node:assert/strict";
+import crypto from "crypto";
+import jwt from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const INTERNAL_JWT_SECRET = crypto.randomBytes(32).toString("hex");
+
+const internalToken = (secret = INTERNAL_JWT_SECRET) =>
+  jwt.sign({ sub: "gateway" }, secret, { algorithm: "HS256", audience: "internal", expiresIn: 60 });
+
+describe("internal service tokens on /auth/validate", () => {
+  let server: TestServer;
+  let userToken: string;
+
+  before(async () => {
+    server = await startApp({ INTERNAL_JWT_SECRET });
+    const { createToken } = await import("../src/utils/jwt");
+    userToken = createToken({ sub: "user-1", email: "user@example.com", roles: ["user"], tenantId: "default" });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  const validate = (token: string, internal?: string) =>
+    server.request("GET", "/auth/validate", {
+      token,
+      headers: internal ? { "X-Internal-Authorization": `Bearer ${internal}` } : {},
+    });
+
+  it("rejects a user bearer token on its own", async () => {
+    const response = await validate(userToken);
+    assert.equal(response.status, 401);
+    assert.equal(response.body.error.code, "internal_auth_required");
+  });
+
+  it("does not accept a user token as the internal one", async () => {
+    const response = await validate(userToken, userToken);
+    assert.equal(response.status, 401);
+    assert.equal(response.body.error.code, "internal_auth_required");
+  });
+
+  it("rejects an internal token signed with the wrong key", async () => {
+    const response = await validate(userToken, internalToken(crypto.randomBytes(32).toString("hex")));
+    assert.equal(response.status, 401);
+    assert.equal(response.body.error.code, "internal_auth_required");
+  });
+
+  it("validates the user token for a service with a valid internal token", { skip: needsMongo }, async () => {
+    const { token } = await registerUser(server, "validated@example.com");
+    const response = await validate(token, internalToken());
+    assert.equal(response.status, 200);
+    assert.equal(response.body.email, "validated@example.com");
+  });
+});
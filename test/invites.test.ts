@@ -0,0 +1,67 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { insertUser, login, needsMongo, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+describe("invite-gated registration", { skip: needsMongo }, () => {
+  let server: TestServer;
+  let adminToken: string;
+  const clock = new ManualClock();
+
+  const createInvite = async (body: Record<string, unknown> = {}) => {
+    const response = await server.request("POST", "/admin/invites", { token: adminToken, body });
+    assert.equal(response.status, 201);
+    return response.body.invite.token as string;
+  };
+
+  const register = (email: string, invite?: string) =>
+    server.request("POST", "/auth/register", { body: { email, password: TEST_PASSWORD, invite } });
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({ REGISTRATION_ENABLED: "false" });
+    await insertUser("inviter@example.com", ["user", "admin"]);
+    ({ token: adminToken } = (await login(server, "inviter@example.com")).body);
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  it("creates the account with the invite's roles, even with registration closed", async () => {
+    const invite = await createInvite({ roles: ["user", "admin"] });
+    const response = await register("invited@example.com", invite);
+    assert.equal(response.status, 201);
+    assert.deepEqual(response.body.user.roles, ["user", "admin"]);
+  });
+
+  it("works only once", async () => {
+    const invite = await createInvite();
+    assert.equal((await register("first@example.com", invite)).status, 201);
+    const second = await register("second@example.com", invite);
+    assert.equal(second.status, 403);
+    assert.equal(second.body.error.code, "invalid_invite");
+  });
+
+  it("works only once when used concurrently", async () => {
+    const invite = await createInvite();
+    const responses = await Promise.all(
+      Array.from({ length: 5 }, (_, i) => register(`race-${i}@example.com`, invite)),
+    );
+    assert.equal(responses.filter((response) => response.status === 201).length, 1);
+    assert.equal(responses.filter((response) => response.status === 403).length, 4);
+  });
+
+  it("rejects unknown, expired, and other-email invites", async () => {
+    assert.equal((await register("unknown@example.com", "not-an-invite")).status, 403);
+
+    const expiring = await createInvite({ expiresInSeconds: 60 });
+    clock.advance(61_000);
+    assert.equal((await register("late@example.com", expiring)).status, 403);
+
+    const forSomeoneElse = await createInvite({ email: "intended@example.com" });
+    assert.equal((await register("other@example.com", forSomeoneElse)).status, 403);
+    assert.equal((await register("intended@example.com", forSomeoneElse)).status, 201);
+  });
+});
@@ -0,0 +1,44 @@
+import assert from "node:assert/strict";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+const BOUND_IP = "203.0.113.9";
+
+describe("IP-bound sessions", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp();
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("carries the binding in the bip claim", async () => {
+    const { createToken, parseAuthPayload } = await import("../src/utils/jwt");
+    const payload = { sub: "user-1", email: "bip@example.com", roles: ["user"], tenantId: "default" };
+    const decoded = jwt.decode(createToken({ ...payload, boundIp: BOUND_IP })) as JwtPayload;
+    assert.equal(decoded.bip, BOUND_IP);
+    assert.equal(parseAuthPayload(decoded).boundIp, BOUND_IP);
+    assert.equal((jwt.decode(createToken(payload)) as JwtPayload).bip, undefined);
+  });
+
+  it("refuses a bound token from another network", { skip: needsMongo }, async () => {
+    await registerUser(server, "bound@example.com");
+    const login = await server.request("POST", "/auth/login", {
+      body: { email: "bound@example.com", password: TEST_PASSWORD, bindIp: true },
+      headers: { "X-Forwarded-For": BOUND_IP },
+    });
+    assert.equal(login.status, 200);
+    const { token } = login.body;
+    assert.equal((jwt.decode(token) as JwtPayload).bip, BOUND_IP);
+    const me = (ip: string) => server.request("GET", "/auth/me", { token, headers: { "X-Forwarded-For": ip } });
+
+    assert.equal((await me(BOUND_IP)).status, 200);
+    const elsewhere = await me("198.51.100.1");
+    assert.equal(elsewhere.status, 401);
+    assert.equal(elsewhere.body.error.code, "token_ip_mismatch");
+  });
+});
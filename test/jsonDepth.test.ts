@@ -0,0 +1,66 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { startApp, type TestServer } from "./helpers";
+
+const MAX_DEPTH = 4;
+
+function nestedObject(depth: number) {
+  let json = "1";
+  for (let i = 0; i < depth; i += 1) {
+    json = `{"k":${json}}`;
+  }
+  return json;
+}
+
+describe("JSON_MAX_DEPTH", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ JSON_MAX_DEPTH: String(MAX_DEPTH) });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  function post(path: string, rawBody: string, contentType = "application/json") {
+    return server.request("POST", path, { rawBody, headers: { "Content-Type": contentType } });
+  }
+
+  it("accepts a body nested exactly to the limit", async () => {
+    const response = await post("/auth/login", nestedObject(MAX_DEPTH));
+    assert.equal(response.status, 400);
+    assert.equal(response.body.error.code, "missing_credentials");
+  });
+
+  it("rejects a body one level deeper with 422 json_too_complex", async () => {
+    const response = await post("/auth/login", nestedObject(MAX_DEPTH + 1));
+    assert.equal(response.status, 422);
+    assert.equal(response.body.error.code, "json_too_complex");
+    assert.equal(response.body.error.details.maxDepth, MAX_DEPTH);
+  });
+
+  it("counts arrays and objects alike", async () => {
+    const response = await post("/auth/login", `{"k":[{"k":[{}]}]}`);
+    assert.equal(response.body.error.code, "json_too_complex");
+  });
+
+  it("ignores brackets inside strings, including after escaped quotes", async () => {
+    const response = await post("/auth/login", JSON.stringify({ note: `\\"${"[{".repeat(50)}"` }));
+    assert.equal(response.status, 400);
+    assert.equal(response.body.error.code, "missing_credentials");
+  });
+
+  it("guards the general JSON parser before authentication", async () => {
+    assert.equal((await post("/admin/invites", nestedObject(MAX_DEPTH))).status, 401);
+    const response = await post("/admin/invites", nestedObject(MAX_DEPTH + 1));
+    assert.equal(response.status, 422);
+    assert.equal(response.body.error.code, "json_too_complex");
+  });
+
+  it("guards SCIM bodies", async () => {
+    const response = await post("/scim/v2/Users", nestedObject(MAX_DEPTH + 1), "application/scim+json");
+    assert.equal(response.status, 422);
+    assert.equal(response.body.error.code, "json_too_complex");
+  });
+});
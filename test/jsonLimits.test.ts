@@ -0,0 +1,104 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { startApp, type TestServer } from "./helpers";
+
+// Generous for a rejection, but far below what parsing the payloads would take.
+const MAX_REJECTION_MS = 1_000;
+
+function nestedArrays(depth: number) {
+  return "[".repeat(depth) + "]".repeat(depth);
+}
+
+// Small deterministic PRNG so a failing payload can be reproduced.
+function prng(seed: number) {
+  return () => {
+    seed = (seed * 1_103_515_245 + 12_345) % 2 ** 31;
+    return seed / 2 ** 31;
+  };
+}
+
+// None of these carry a usable email and password, so every one is refused
+// before the database is involved.
+function pathologicalBodies(random: () => number) {
+  const size = (max: number) => 1 + Math.floor(random() * max);
+  return [
+    nestedArrays(size(500_000)),
+    `{"email":${nestedArrays(size(3_000))}}`,
+    `{"email":"${"a".repeat(size(4_000))}"`,
+    `{"password":"${"p".repeat(1_025 + size(2_000))}"}`,
+    `{"email":"${"e".repeat(255 + size(2_000))}@example.com","password":"x"}`,
+    JSON.stringify(Object.fromEntries(Array.from({ length: size(300) }, (_, i) => [`k${i}`, i]))),
+    "\u0000".repeat(size(2_000)),
+    `"${"\\".repeat(size(2_000))}`,
+    "{".repeat(size(2_000)),
+  ];
+}
+
+describe("pathological JSON on the auth endpoints", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp();
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  async function timedPost(path: string, rawBody: string) {
+    const started = performance.now();
+    const response = await server.request("POST", path, {
+      rawBody,
+      headers: { "Content-Type": "application/json" },
+    });
+    return { response, elapsed: performance.now() - started };
+  }
+
+  for (const path of ["/auth/register", "/auth/login"]) {
+    it(`${path}: refuses a 1 MB nested array body by size`, async () => {
+      const { response, elapsed } = await timedPost(path, nestedArrays(512 * 1024));
+      assert.equal(response.status, 413);
+      assert.equal(response.body.error.code, "payload_too_large");
+      assert.ok(elapsed < MAX_REJECTION_MS, `took ${elapsed}ms`);
+    });
+
+    it(`${path}: refuses deep nesting within the size cap`, async () => {
+      const { response, elapsed } = await timedPost(path, `{"email":${nestedArrays(1_000)}}`);
+      assert.equal(response.status, 422);
+      assert.equal(response.body.error.code, "json_too_complex");
+      assert.ok(elapsed < MAX_REJECTION_MS, `took ${elapsed}ms`);
+    });
+
+    it(`${path}: refuses an overlong password before hashing`, async () => {
+      const { response } = await timedPost(
+        path,
+        JSON.stringify({ email: "long@example.com", password: "p".repeat(1_025) }),
+      );
+      assert.equal(response.status, 422);
+      assert.equal(response.body.error.code, "field_too_long");
+      assert.equal(response.body.error.details.field, "password");
+    });
+
+    it(`${path}: refuses an overlong email`, async () => {
+      const { response } = await timedPost(
+        path,
+        JSON.stringify({ email: `${"e".repeat(250)}@example.com`, password: "x" }),
+      );
+      assert.equal(response.status, 422);
+      assert.equal(response.body.error.details.field, "email");
+    });
+
+    it(`${path}: answers generated payloads with a 4xx in bounded time`, async () => {
+      const random = prng(174);
+      for (let round = 0; round < 5; round += 1) {
+        for (const body of pathologicalBodies(random)) {
+          const { response, elapsed } = await timedPost(path, body);
+          const sample = JSON.stringify(body.slice(0, 40));
+          assert.ok(response.status >= 400 && response.status < 500, `${response.status} for ${sample}`);
+          assert.equal(response.body.ok, false);
+          assert.ok(elapsed < MAX_REJECTION_MS, `took ${elapsed}ms for ${sample}`);
+        }
+      }
+    });
+  }
+});
@@ -0,0 +1,63 @@
+import assert from "node:assert/strict";
+import crypto from "crypto";
+import jwt from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { startApp, TestServer } from "./helpers";
+
+const randomKey = () => crypto.randomBytes(32).toString("hex");
+
+// Mid-rotation: the new key is listed first and the old one is still accepted.
+const JWT_KEYS = { current: randomKey(), previous: randomKey() };
+const ADMIN_TOKENS = { current: randomKey(), previous: randomKey() };
+const SCIM_TOKENS = { current: randomKey(), previous: randomKey() };
+
+describe("secret rotation", () => {
+  let server: TestServer;
+  let adminServer: TestServer;
+
+  before(async () => {
+    server = await startApp({
+      JWT_SECRET: `${JWT_KEYS.current},${JWT_KEYS.previous}`,
+      ADMIN_TOKEN: `${ADMIN_TOKENS.current},${ADMIN_TOKENS.previous}`,
+      SCIM_TOKEN: `${SCIM_TOKENS.current},${SCIM_TOKENS.previous}`,
+    });
+    const { adminApp } = await import("../src/adminApp");
+    adminServer = await TestServer.start(adminApp);
+  });
+
+  after(async () => {
+    await adminServer.close();
+    await server.close();
+  });
+
+  it("signs with the first JWT key and verifies with either", async () => {
+    const { createToken, verifyToken } = await import("../src/utils/jwt");
+    const payload = { sub: "user-1", email: "rotation@example.com", roles: ["user"], tenantId: "default" };
+    const claims = { sub: payload.sub, email: payload.email, roles: payload.roles, tenant_id: payload.tenantId };
+
+    const issued = createToken(payload);
+    jwt.verify(issued, JWT_KEYS.current);
+    assert.equal(verifyToken(issued).sub, "user-1");
+
+    const fromPrevious = jwt.sign(claims, JWT_KEYS.previous, { expiresIn: 60 });
+    assert.equal(verifyToken(fromPrevious).sub, "user-1");
+
+    const fromUnknown = jwt.sign(claims, randomKey(), { expiresIn: 60 });
+    assert.throws(() => verifyToken(fromUnknown), { name: "JsonWebTokenError" });
+  });
+
+  it("accepts either admin token on the admin listener", async () => {
+    const health = (token: string) => adminServer.request("GET", "/healthz", { headers: { "X-Admin-Token": token } });
+    assert.equal((await health(ADMIN_TOKENS.current)).status, 200);
+    assert.equal((await health(ADMIN_TOKENS.previous)).status, 200);
+    assert.equal((await health(randomKey())).status, 401);
+  });
+
+  it("accepts either SCIM token", async () => {
+    // An id that is not an ObjectId is answered with 404 without touching the database.
+    const lookup = (token: string) => server.request("GET", "/scim/v2/Users/not-a-user", { token });
+    assert.equal((await lookup(SCIM_TOKENS.current)).status, 404);
+    assert.equal((await lookup(SCIM_TOKENS.previous)).status, 404);
+    assert.equal((await lookup(randomKey())).status, 401);
+  });
+});
@@ -0,0 +1,41 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { login, needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const THRESHOLD = 3;
+const LOCKOUT_SECONDS = 600;
+
+describe("account lockout", { skip: needsMongo }, () => {
+  let server: TestServer;
+  const clock = new ManualClock();
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({
+      LOGIN_LOCKOUT_ENABLED: "true",
+      LOGIN_LOCKOUT_THRESHOLD: String(THRESHOLD),
+      LOGIN_LOCKOUT_DURATION_SECONDS: String(LOCKOUT_SECONDS),
+    });
+    await registerUser(server, "locked@example.com");
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  it("answers a locked account with 423 until the lock expires", async () => {
+    for (let i = 0; i < THRESHOLD; i += 1) {
+      assert.equal((await login(server, "locked@example.com", "wrong-password")).status, 401);
+    }
+
+    const locked = await login(server, "locked@example.com");
+    assert.equal(locked.status, 423);
+    assert.equal(locked.body.error.code, "account_locked");
+    assert.ok(locked.body.error.details.lockedUntil);
+
+    clock.advance((LOCKOUT_SECONDS + 1) * 1000);
+    assert.equal((await login(server, "locked@example.com")).status, 200);
+  });
+});
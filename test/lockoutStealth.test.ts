@@ -0,0 +1,53 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { login, needsMongo, registerUser, startApp, type TestResponse, type TestServer } from "./helpers";
+
+const THRESHOLD = 3;
+const LOCKOUT_SECONDS = 600;
+
+// Everything but the per-request id.
+function observable(response: TestResponse) {
+  const { request_id: _requestId, ...error } = response.body.error;
+  return { status: response.status, error };
+}
+
+describe("stealth lockout", { skip: needsMongo }, () => {
+  let server: TestServer;
+  const clock = new ManualClock();
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({
+      LOGIN_LOCKOUT_ENABLED: "true",
+      LOGIN_LOCKOUT_THRESHOLD: String(THRESHOLD),
+      LOGIN_LOCKOUT_DURATION_SECONDS: String(LOCKOUT_SECONDS),
+      STEALTH_LOCKOUT: "true",
+    });
+    await registerUser(server, "stealth@example.com");
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  it("answers a locked account like any failed login and still refuses it", async () => {
+    for (let i = 0; i < THRESHOLD; i += 1) {
+      assert.equal((await login(server, "stealth@example.com", "wrong-password")).status, 401);
+    }
+
+    const wrongPassword = observable(await login(server, "stealth@example.com", "wrong-password"));
+    const correctPassword = observable(await login(server, "stealth@example.com"));
+    const unknownAccount = observable(await login(server, "nobody@example.com"));
+    assert.deepEqual(wrongPassword, {
+      status: 401,
+      error: { code: "invalid_credentials", message: "Invalid credentials", details: null },
+    });
+    assert.deepEqual(correctPassword, wrongPassword);
+    assert.deepEqual(unknownAccount, wrongPassword);
+
+    clock.advance((LOCKOUT_SECONDS + 1) * 1000);
+    assert.equal((await login(server, "stealth@example.com")).status, 200);
+  });
+});
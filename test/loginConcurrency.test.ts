@@ -0,0 +1,60 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { login, needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const MAX_CONCURRENT = 2;
+const BURST = 20;
+
+describe("concurrent logins per email", { skip: needsMongo }, () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ LOGIN_MAX_CONCURRENT_PER_EMAIL: String(MAX_CONCURRENT) });
+    await registerUser(server, "target@example.com");
+    await registerUser(server, "bystander@example.com");
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("turns away attempts beyond the limit while others are in flight", async () => {
+    // Spellings of one account share its slots.
+    const emails = ["target@example.com", "TARGET@example.com", " Target@Example.com"];
+    const responses = await Promise.all(
+      Array.from({ length: BURST }, (_, i) =>
+        login(server, emails[i % emails.length], i % 2 ? "wrong-password" : undefined),
+      ),
+    );
+
+    const rejected = responses.filter((response) => response.status === 429);
+    assert.ok(rejected.length > 0, "expected some attempts to be turned away");
+    assert.ok(rejected.length <= BURST - MAX_CONCURRENT);
+    for (const response of rejected) {
+      assert.equal(response.body.error.code, "too_many_concurrent_logins");
+    }
+    for (const response of responses.filter((response) => response.status !== 429)) {
+      assert.ok([200, 401].includes(response.status), `unexpected ${response.status}`);
+    }
+  });
+
+  it("frees the slots once the attempts finish", async () => {
+    const [target, bystander] = await Promise.all([
+      login(server, "target@example.com"),
+      login(server, "bystander@example.com"),
+    ]);
+    assert.equal(target.status, 200);
+    assert.equal(bystander.status, 200);
+  });
+
+  it("does not hold other accounts to the same slots", async () => {
+    const responses = await Promise.all([
+      ...Array.from({ length: MAX_CONCURRENT }, () => login(server, "target@example.com")),
+      login(server, "bystander@example.com"),
+    ]);
+    assert.deepEqual(
+      responses.map((response) => response.status),
+      Array(MAX_CONCURRENT + 1).fill(200),
+    );
+  });
+});
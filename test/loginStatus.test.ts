@@ -0,0 +1,81 @@
+import assert from "node:assert/strict";
+import crypto from "crypto";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+type StatusCase = {
+  name: string;
+  // Builds the request headers; may register users first.
+  headers: (server: TestServer) => Promise<Record<string, string>>;
+  status: number;
+  reason?: string;
+  needsMongo?: boolean;
+};
+
+const bearer = (token: string) => ({ Authorization: `Bearer ${token}` });
+
+// A token whose one-second lifetime ended a minute ago.
+async function expiredToken() {
+  const { createToken } = await import("../src/utils/jwt");
+  setClock(new ManualClock(Date.now() - 60_000));
+  try {
+    const payload = { sub: "user-1", email: "user@example.com", roles: ["user"], tenantId: "default" };
+    return createToken({ ...payload, jti: crypto.randomUUID() }, {}, { expiresIn: 1 });
+  } finally {
+    setClock(systemClock);
+  }
+}
+
+const CASES: StatusCase[] = [
+  { name: "no credentials", headers: async () => ({}), status: 200, reason: "anonymous" },
+  { name: "wrong scheme", headers: async () => ({ Authorization: "Basic dXNlcjpwYXNz" }), status: 400 },
+  { name: "bearer without a token", headers: async () => ({ Authorization: "Bearer " }), status: 400 },
+  { name: "unverifiable token", headers: async () => bearer("not-a-jwt"), status: 200, reason: "invalid" },
+  { name: "expired token", headers: async () => bearer(await expiredToken()), status: 200, reason: "expired" },
+  {
+    name: "revoked session",
+    headers: async (server) => {
+      const { token } = await registerUser(server, "status-revoked@example.com");
+      const current = await server.request("GET", "/auth/sessions/current", { token });
+      await server.request("DELETE", `/auth/sessions/${current.body.session.id}`, { token });
+      return bearer(token);
+    },
+    status: 200,
+    reason: "revoked",
+    needsMongo: true,
+  },
+  {
+    name: "valid session",
+    headers: async (server) => bearer((await registerUser(server, "status-valid@example.com")).token),
+    status: 200,
+    reason: "valid",
+    needsMongo: true,
+  },
+];
+
+describe("GET /auth/status", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp();
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  for (const testCase of CASES) {
+    it(testCase.name, { skip: testCase.needsMongo && needsMongo }, async () => {
+      const response = await server.request("GET", "/auth/status", { headers: await testCase.headers(server) });
+      assert.equal(response.status, testCase.status);
+      if (testCase.reason === undefined) {
+        assert.equal(response.body.error.code, "malformed_authorization");
+        return;
+      }
+      assert.equal(response.body.reason, testCase.reason);
+      assert.equal(response.body.loggedIn, testCase.reason === "valid");
+      assert.equal(response.headers.get("cache-control"), "no-store");
+    });
+  }
+});
@@ -0,0 +1,35 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+describe("password reuse history", { skip: needsMongo }, () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ PASSWORD_HISTORY_SIZE: "2" });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("rejects the current and recent passwords but accepts new and forgotten ones", async () => {
+    const { token } = await registerUser(server, "history@example.com");
+    const change = (currentPassword: string, newPassword: string) =>
+      server.request("POST", "/auth/change-password", { token, body: { currentPassword, newPassword } });
+
+    const sameAsCurrent = await change(TEST_PASSWORD, TEST_PASSWORD);
+    assert.equal(sameAsCurrent.status, 422);
+    assert.equal(sameAsCurrent.body.error.code, "password_reused");
+    assert.equal(sameAsCurrent.body.error.details.historySize, 2);
+
+    assert.equal((await change(TEST_PASSWORD, "SecondPass123!")).status, 200);
+    assert.equal((await change("SecondPass123!", TEST_PASSWORD)).status, 422);
+    assert.equal((await change("SecondPass123!", "ThirdPass123!")).status, 200);
+    assert.equal((await change("ThirdPass123!", "FourthPass123!")).status, 200);
+
+    // Only the last two previous passwords are kept, so the first is usable again.
+    assert.equal((await change("FourthPass123!", "SecondPass123!")).status, 422);
+    assert.equal((await change("FourthPass123!", TEST_PASSWORD)).status, 200);
+  });
+});
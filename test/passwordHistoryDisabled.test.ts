@@ -0,0 +1,24 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+describe("PASSWORD_HISTORY_SIZE=0", { skip: needsMongo }, () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ PASSWORD_HISTORY_SIZE: "0" });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("turns the reuse check off", async () => {
+    const { token } = await registerUser(server, "no-history@example.com");
+    const response = await server.request("POST", "/auth/change-password", {
+      token,
+      body: { currentPassword: TEST_PASSWORD, newPassword: TEST_PASSWORD },
+    });
+    assert.equal(response.status, 200);
+  });
+});
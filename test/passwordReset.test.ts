@@ -0,0 +1,128 @@
+import assert from "node:assert/strict";
+import { once } from "events";
+import http from "http";
+import type { AddressInfo } from "net";
+import { after, before, describe, it } from "node:test";
+import { login, needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const RESETS_PER_EMAIL = 2;
+const RESETS_PER_IP = 5;
+const RELAY_TOKEN = "relay-secret";
+const NEW_PASSWORD = "AnotherStrong456!";
+
+type RelayedEmail = { from?: string; to: string; subject: string; text: string; authorization?: string };
+
+// Stands in for the HTTP mail relay and keeps every message it is handed.
+class EmailRelay {
+  readonly emails: RelayedEmail[] = [];
+  private readonly server = http.createServer((req, res) => {
+    const chunks: Buffer[] = [];
+    req.on("data", (chunk: Buffer) => chunks.push(chunk));
+    req.on("end", () => {
+      this.emails.push({ ...JSON.parse(Buffer.concat(chunks).toString()), authorization: req.headers.authorization });
+      res.writeHead(202).end();
+    });
+  });
+
+  async listen() {
+    this.server.listen(0, "127.0.0.1");
+    await once(this.server, "listening");
+    return `http://127.0.0.1:${(this.server.address() as AddressInfo).port}/send`;
+  }
+
+  to(address: string) {
+    return this.emails.filter((email) => email.to === address);
+  }
+
+  async close() {
+    this.server.closeAllConnections();
+    await new Promise((resolve) => this.server.close(resolve));
+  }
+}
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+// The lookup and email happen after the response, so poll for their effects.
+async function waitFor(condition: () => boolean | Promise<boolean>, timeoutMs = 5_000) {
+  for (let waited = 0; !(await condition()); waited += 50) {
+    if (waited >= timeoutMs) {
+      throw new Error("Timed out waiting for the background reset");
+    }
+    await sleep(50);
+  }
+}
+
+describe("password reset by email", { skip: needsMongo }, () => {
+  let server: TestServer;
+  const relay = new EmailRelay();
+
+  before(async () => {
+    server = await startApp({
+      EMAIL_HTTP_URL: await relay.listen(),
+      EMAIL_FROM: "no-reply@example.com",
+      EMAIL_HTTP_TOKEN: RELAY_TOKEN,
+      PASSWORD_RESETS_PER_EMAIL_PER_HOUR: String(RESETS_PER_EMAIL),
+      PASSWORD_RESET_RATE_LIMIT_MAX: String(RESETS_PER_IP),
+    });
+  });
+
+  after(async () => {
+    await server.close();
+    await relay.close();
+  });
+
+  // Each test uses its own client address so the per-IP limit stays out of the way.
+  function forgotPassword(email: string, ip: string) {
+    return server.request("POST", "/auth/forgot-password", { body: { email }, headers: { "X-Forwarded-For": ip } });
+  }
+
+  it("answers existing and unknown accounts alike and only emails the existing one", async () => {
+    await registerUser(server, "reset@example.com");
+    const known = await forgotPassword("Reset@Example.com", "198.51.100.1");
+    const unknown = await forgotPassword("nobody@example.com", "198.51.100.1");
+    assert.equal(known.status, 200);
+    assert.deepEqual(unknown.body, known.body);
+    assert.equal(unknown.status, known.status);
+
+    await waitFor(() => relay.to("reset@example.com").length === 1);
+    const [email] = relay.to("reset@example.com");
+    assert.equal(email.from, "no-reply@example.com");
+    assert.equal(email.authorization, `Bearer ${RELAY_TOKEN}`);
+    await sleep(200);
+    assert.equal(relay.to("nobody@example.com").length, 0);
+
+    const token = /choose a new one: (\S+)$/m.exec(email.text)?.[1];
+    assert.ok(token);
+    const reset = await server.request("POST", "/auth/reset-password", { body: { token, newPassword: NEW_PASSWORD } });
+    assert.equal(reset.status, 200);
+    assert.equal((await login(server, "reset@example.com", NEW_PASSWORD)).status, 200);
+    const reused = await server.request("POST", "/auth/reset-password", { body: { token, newPassword: NEW_PASSWORD } });
+    assert.equal(reused.body.error.code, "invalid_reset_token");
+  });
+
+  it("caps emails per account and audits the throttled requests", async () => {
+    const { getAuditCollection } = await import("../src/audit");
+    const { id } = await registerUser(server, "flood@example.com");
+    const audit = await getAuditCollection();
+    const throttled = () => audit.countDocuments({ type: "password_reset.throttled", userId: id });
+
+    const attempts = RESETS_PER_EMAIL + 2;
+    for (let i = 1; i <= attempts; i += 1) {
+      // Different addresses, as in a distributed attempt to flood one inbox.
+      assert.equal((await forgotPassword("flood@example.com", `203.0.113.${i}`)).status, 200);
+      // Let each request finish before the next one is counted.
+      await waitFor(async () => relay.to("flood@example.com").length + (await throttled()) === i);
+    }
+    assert.equal(relay.to("flood@example.com").length, RESETS_PER_EMAIL);
+    assert.equal(await throttled(), attempts - RESETS_PER_EMAIL);
+  });
+
+  it("limits requests per client IP", async () => {
+    const statuses = [];
+    for (let i = 0; i <= RESETS_PER_IP; i += 1) {
+      statuses.push((await forgotPassword(`someone${i}@example.com`, "192.0.2.77")).status);
+    }
+    assert.deepEqual(statuses, [...Array(RESETS_PER_IP).fill(200), 429]);
+    assert.equal((await forgotPassword("someone@example.com", "192.0.2.78")).status, 200);
+  });
+});
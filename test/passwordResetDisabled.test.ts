@@ -0,0 +1,28 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { startApp, type TestServer } from "./helpers";
+
+describe("password reset without an email transport", () => {
+  let server: TestServer;
+
+  before(async () => {
+    delete process.env.EMAIL_HTTP_URL;
+    server = await startApp();
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("refuses both steps with 501 instead of pretending to send", async () => {
+    const requested = await server.request("POST", "/auth/forgot-password", { body: { email: "a@example.com" } });
+    assert.equal(requested.status, 501);
+    assert.equal(requested.body.error.code, "email_not_configured");
+
+    const reset = await server.request("POST", "/auth/reset-password", {
+      body: { token: "anything", newPassword: "AnotherStrong456!" },
+    });
+    assert.equal(reset.status, 501);
+    assert.equal(reset.body.error.code, "email_not_configured");
+  });
+});
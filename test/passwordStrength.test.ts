@@ -0,0 +1,51 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, startApp, type TestServer } from "./helpers";
+
+// Each password is checked with the strength endpoint and then used to
+// register, so the two verdicts can never drift apart.
+const CASES = [
+  { name: "too short", email: "short@example.com", password: "Ab1!", failures: ["too_short"] },
+  {
+    name: "lowercase only",
+    email: "lower@example.com",
+    password: "lowercaseonly",
+    failures: ["missing_uppercase", "missing_digit", "missing_symbol"],
+  },
+  { name: "no symbol", email: "nosymbol@example.com", password: "NoSymbol123", failures: ["missing_symbol"] },
+  { name: "has the email", email: "explorer@example.com", password: "Explorer12!", failures: ["similar_to_email"] },
+  { name: "strong", email: "strong@example.com", password: "StrongPass123!", failures: [] },
+  { name: "long and strong", email: "long@example.com", password: "Much-Longer-Pass-123", failures: [] },
+];
+
+describe("password strength and registration agree", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp();
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  for (const { name, email, password, failures } of CASES) {
+    const accepted = failures.length === 0;
+    // Accepted passwords create an account, which needs the database.
+    it(name, { skip: accepted && needsMongo }, async () => {
+      const strength = await server.request("POST", "/auth/password/strength", { body: { email, password } });
+      assert.equal(strength.status, 200);
+      assert.equal(strength.body.accepted, accepted);
+      assert.deepEqual(strength.body.failures.map((failure: { code: string }) => failure.code), failures);
+
+      const register = await server.request("POST", "/auth/register", { body: { email, password } });
+      if (accepted) {
+        assert.equal(register.status, 201);
+      } else {
+        assert.equal(register.status, 400);
+        assert.equal(register.body.error.code, "weak_password");
+        assert.deepEqual(register.body.error.details.failures, failures);
+      }
+    });
+  }
+});
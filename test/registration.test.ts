@@ -0,0 +1,24 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+describe("POST /auth/register with registration enabled", { skip: needsMongo }, () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ REGISTRATION_ENABLED: "true" });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("creates the account and signs it in", async () => {
+    const response = await server.request("POST", "/auth/register", {
+      body: { email: "open@example.com", password: TEST_PASSWORD },
+    });
+    assert.equal(response.status, 201);
+    assert.ok(response.body.token);
+    assert.deepEqual(response.body.user.roles, ["user"]);
+  });
+});
@@ -0,0 +1,41 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { insertUser, login, needsMongo, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+describe("REGISTRATION_ENABLED=false", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ REGISTRATION_ENABLED: "false" });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("refuses self-service registration", async () => {
+    const response = await server.request("POST", "/auth/register", {
+      body: { email: "closed@example.com", password: TEST_PASSWORD },
+    });
+    assert.equal(response.status, 403);
+    assert.equal(response.body.error.code, "registration_disabled");
+  });
+
+  it("answers no email availability checks", async () => {
+    const response = await server.request("GET", "/auth/register/check-email?email=closed@example.com");
+    assert.equal(response.status, 403);
+    assert.equal(response.body.error.code, "email_check_disabled");
+  });
+
+  it("still lets admins create users in bulk", { skip: needsMongo }, async () => {
+    await insertUser("closed-admin@example.com", ["user", "admin"]);
+    const { token } = (await login(server, "closed-admin@example.com")).body;
+    const response = await server.request("POST", "/admin/users/bulk", {
+      token,
+      body: { users: [{ email: "created@example.com", password: TEST_PASSWORD }] },
+    });
+    assert.equal(response.status, 200);
+    assert.equal(response.body.summary.created, 1);
+    assert.equal((await login(server, "created@example.com")).status, 200);
+  });
+});
@@ -0,0 +1,67 @@
+import assert from "node:assert/strict";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { needsMongo, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+const DEFAULT_TTL_SECONDS = 60 * 60;
+const REMEMBER_ME_TTL_SECONDS = 7 * 24 * 60 * 60;
+
+describe("remember me logins", { skip: needsMongo }, () => {
+  let server: TestServer;
+  const clock = new ManualClock();
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({ JWT_EXPIRES_IN: "1h", REMEMBER_ME_TTL_SECONDS: String(REMEMBER_ME_TTL_SECONDS) });
+    await registerUser(server, "remember@example.com");
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  async function loginWith(rememberMe?: boolean) {
+    const body = { email: "remember@example.com", password: TEST_PASSWORD, rememberMe };
+    const response = await server.request("POST", "/auth/login", { body });
+    assert.equal(response.status, 200);
+    const { iat, exp } = jwt.decode(response.body.token) as JwtPayload;
+    const expiresAt = response.body.expiresAt as string;
+    return { token: response.body.token as string, iat: iat!, exp: exp!, expiresAt };
+  }
+
+  async function currentSession(token: string) {
+    const response = await server.request("GET", "/auth/sessions/current", { token });
+    assert.equal(response.status, 200);
+    return response.body.session as { expiresAt: string; rememberMe: boolean };
+  }
+
+  for (const [label, rememberMe, ttl] of [
+    ["omitted", undefined, DEFAULT_TTL_SECONDS],
+    ["false", false, DEFAULT_TTL_SECONDS],
+    ["true", true, REMEMBER_ME_TTL_SECONDS],
+  ] as const) {
+    it(`rememberMe ${label}: token and session last ${ttl}s`, async () => {
+      const { token, iat, exp, expiresAt } = await loginWith(rememberMe);
+      assert.equal(exp - iat, ttl);
+      assert.equal(new Date(expiresAt).getTime(), exp * 1000);
+
+      const session = await currentSession(token);
+      assert.equal(new Date(session.expiresAt).getTime(), exp * 1000);
+      assert.equal(session.rememberMe, rememberMe === true);
+    });
+  }
+
+  it("keeps the remembered session after the default lifetime has passed", async () => {
+    const short = await loginWith(false);
+    const remembered = await loginWith(true);
+
+    clock.advance((DEFAULT_TTL_SECONDS + 60) * 1000);
+    assert.equal((await server.request("GET", "/auth/me", { token: short.token })).status, 401);
+    assert.equal((await server.request("GET", "/auth/me", { token: remembered.token })).status, 200);
+
+    clock.advance(REMEMBER_ME_TTL_SECONDS * 1000);
+    assert.equal((await server.request("GET", "/auth/me", { token: remembered.token })).status, 401);
+  });
+});
@@ -0,0 +1,70 @@
+import assert from "node:assert/strict";
+import crypto from "crypto";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { TtlCache } from "../src/utils/cache";
+import { countSessionLookups, needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const MAX_ENTRIES = 2;
+
+describe("TtlCache", () => {
+  it("evicts the least recently used entry past its size limit", () => {
+    const cache = new TtlCache<number>(MAX_ENTRIES);
+    cache.set("a", 1, 60_000);
+    cache.set("b", 2, 60_000);
+    assert.equal(cache.get("a"), 1);
+    cache.set("c", 3, 60_000);
+    assert.equal(cache.size, MAX_ENTRIES);
+    assert.equal(cache.get("b"), undefined);
+    assert.equal(cache.get("a"), 1);
+  });
+});
+
+describe("revoked token cache", { skip: needsMongo }, () => {
+  let server: TestServer;
+  let findActiveSession: typeof import("../src/sessions").findActiveSession;
+
+  before(async () => {
+    server = await startApp({ REVOKED_TOKEN_CACHE_MAX_ENTRIES: String(MAX_ENTRIES) });
+    ({ findActiveSession } = await import("../src/sessions"));
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  const unknownToken = () => {
+    const jti = crypto.randomUUID();
+    const exp = Math.floor(Date.now() / 1000) + 3600;
+    return () => findActiveSession(jti, exp);
+  };
+
+  it("rejects a revoked token again without a lookup", async () => {
+    const { token } = await registerUser(server, "revoked@example.com");
+    const { jti, exp } = jwt.decode(token) as JwtPayload;
+    const current = await server.request("GET", "/auth/sessions/current", { token });
+    await server.request("DELETE", `/auth/sessions/${current.body.session.id}`, { token });
+
+    const lookup = () => findActiveSession(jti as string, exp);
+    assert.equal(await countSessionLookups(lookup), 0);
+    assert.equal(await lookup(), null);
+    assert.equal((await server.request("GET", "/auth/me", { token })).status, 401);
+  });
+
+  it("remembers a token whose session was not found", async () => {
+    const lookup = unknownToken();
+    assert.equal(await countSessionLookups(lookup), 1);
+    assert.equal(await countSessionLookups(lookup), 0);
+  });
+
+  it("stays within its size limit", async () => {
+    const [first, second, third] = [unknownToken(), unknownToken(), unknownToken()];
+    for (const lookup of [first, second, third]) {
+      assert.equal(await countSessionLookups(lookup), 1);
+    }
+    // Only the two most recent fit, so the first is looked up again.
+    assert.equal(await countSessionLookups(third), 0);
+    assert.equal(await countSessionLookups(second), 0);
+    assert.equal(await countSessionLookups(first), 1);
+  });
+});
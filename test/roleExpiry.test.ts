@@ -0,0 +1,57 @@
+import assert from "node:assert/strict";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerAdmin, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+const ADMIN_TTL_SECONDS = 900;
+const USER_TTL_SECONDS = 3600;
+
+function lifetime(token: string) {
+  const { iat, exp } = jwt.decode(token) as JwtPayload;
+  return exp! - iat!;
+}
+
+describe("JWT_EXPIRY_BY_ROLE", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({
+      JWT_EXPIRES_IN: "2h",
+      JWT_EXPIRY_BY_ROLE: `admin=${ADMIN_TTL_SECONDS}, user=${USER_TTL_SECONDS}, guest=60, admin=soon`,
+    });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("picks the highest-privilege role and skips invalid entries", async () => {
+    const { tokenLifetimeForRoles } = await import("../src/users");
+    assert.equal(tokenLifetimeForRoles(["user"]), USER_TTL_SECONDS);
+    assert.equal(tokenLifetimeForRoles(["user", "admin"]), ADMIN_TTL_SECONDS);
+    assert.equal(tokenLifetimeForRoles(["admin", "user"]), ADMIN_TTL_SECONDS);
+    assert.equal(tokenLifetimeForRoles([]), undefined);
+  });
+
+  it("issues user tokens and sessions with the user lifetime", { skip: needsMongo }, async () => {
+    const { token } = await registerUser(server, "user@example.com");
+    assert.equal(lifetime(token), USER_TTL_SECONDS);
+
+    const session = await server.request("GET", "/auth/sessions/current", { token });
+    const { exp } = jwt.decode(token) as JwtPayload;
+    assert.equal(new Date(session.body.session.expiresAt).getTime(), exp! * 1000);
+  });
+
+  it("issues admin tokens with the shorter admin lifetime, even with remember me", { skip: needsMongo }, async () => {
+    const { token } = await registerAdmin(server, "admin@example.com");
+    assert.equal(lifetime(token), ADMIN_TTL_SECONDS);
+
+    const remembered = await server.request("POST", "/auth/login", {
+      body: { email: "admin@example.com", password: TEST_PASSWORD, rememberMe: true },
+    });
+    assert.equal(remembered.status, 200);
+    assert.equal(lifetime(remembered.body.token), ADMIN_TTL_SECONDS);
+    const { exp } = jwt.decode(remembered.body.token) as JwtPayload;
+    assert.equal(new Date(remembered.body.expiresAt).getTime(), exp! * 1000);
+  });
+});
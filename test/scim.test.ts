@@ -0,0 +1,107 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, startApp, type TestServer } from "./helpers";
+
+const SCIM_TOKEN = "okta-provisioning-token";
+const USER_SCHEMA = "urn:ietf:params:scim:schemas:core:2.0:User";
+const PATCH_SCHEMA = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+
+// Request bodies and queries below follow what Okta sends.
+function oktaUser(email: string, externalId: string) {
+  return {
+    schemas: [USER_SCHEMA],
+    userName: email,
+    name: { givenName: "Ada", familyName: "Lovelace" },
+    emails: [{ primary: true, value: email, type: "work" }],
+    displayName: "Ada Lovelace",
+    locale: "en-US",
+    externalId,
+    groups: [],
+    active: true,
+  };
+}
+
+describe("SCIM provisioning", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ SCIM_TOKEN });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  const scim = (method: string, path: string, body?: unknown) =>
+    server.request(method, `/scim/v2${path}`, {
+      body,
+      token: SCIM_TOKEN,
+      headers: body === undefined ? {} : { "Content-Type": "application/scim+json" },
+    });
+  const findByUserName = (email: string) =>
+    scim("GET", `/Users?filter=${encodeURIComponent(`userName eq "${email}"`)}&startIndex=1&count=100`);
+
+  it("refuses requests without the provisioning token", async () => {
+    const response = await server.request("GET", "/scim/v2/Users");
+    assert.equal(response.status, 401);
+    assert.equal(response.headers.get("content-type"), "application/scim+json; charset=utf-8");
+  });
+
+  for (const filter of [
+    'userName sw "ada"',
+    'emails.value eq "ada@example.com"',
+    'userName eq "a@example.com" or userName eq "b@example.com"',
+    "userName pr",
+  ]) {
+    it(`rejects the unsupported filter ${filter}`, async () => {
+      const response = await scim("GET", `/Users?filter=${encodeURIComponent(filter)}`);
+      assert.equal(response.status, 400);
+      assert.equal(response.body.scimType, "invalidFilter");
+      assert.equal(response.body.status, "400");
+    });
+  }
+
+  it("runs Okta's lookup, create, deactivate, and delete sequence", { skip: needsMongo }, async () => {
+    const email = "ada@example.com";
+    const empty = await findByUserName(email);
+    assert.equal(empty.status, 200);
+    assert.equal(empty.body.totalResults, 0);
+    assert.deepEqual(empty.body.Resources, []);
+
+    const created = await scim("POST", "/Users", oktaUser(email, "00u1okta"));
+    assert.equal(created.status, 201);
+    assert.equal(created.body.userName, email);
+    assert.equal(created.body.externalId, "00u1okta");
+    assert.equal(created.body.active, true);
+    const { id } = created.body;
+
+    assert.equal((await scim("POST", "/Users", oktaUser(email, "00u1okta"))).body.scimType, "uniqueness");
+
+    const found = await findByUserName(email.toUpperCase());
+    assert.equal(found.body.totalResults, 1);
+    assert.equal(found.body.Resources[0].id, id);
+
+    const deactivated = await scim("PATCH", `/Users/${id}`, {
+      schemas: [PATCH_SCHEMA],
+      Operations: [{ op: "replace", value: { active: false } }],
+    });
+    assert.equal(deactivated.status, 200);
+    assert.equal(deactivated.body.active, false);
+
+    const reactivated = await scim("PATCH", `/Users/${id}`, {
+      schemas: [PATCH_SCHEMA],
+      Operations: [{ op: "replace", path: "active", value: true }],
+    });
+    assert.equal(reactivated.body.active, true);
+
+    const unsupported = await scim("PATCH", `/Users/${id}`, {
+      schemas: [PATCH_SCHEMA],
+      Operations: [{ op: "replace", path: "name.givenName", value: "Augusta" }],
+    });
+    assert.equal(unsupported.status, 400);
+    assert.equal(unsupported.body.scimType, "invalidPath");
+
+    assert.equal((await scim("DELETE", `/Users/${id}`)).status, 204);
+    assert.equal((await scim("GET", `/Users/${id}`)).status, 404);
+  });
+});
@@ -0,0 +1,63 @@
+import assert from "node:assert/strict";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { countSessionLookups, needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const CACHE_TTL_MS = 120_000;
+
+describe("session cache", { skip: needsMongo }, () => {
+  let server: TestServer;
+  let findActiveSession: typeof import("../src/sessions").findActiveSession;
+  const clock = new ManualClock();
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({ TOKEN_CACHE_TTL_MS: String(CACHE_TTL_MS) });
+    ({ findActiveSession } = await import("../src/sessions"));
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  const sessionOf = async (email: string) => {
+    const { token } = await registerUser(server, email);
+    const { jti, exp } = jwt.decode(token) as JwtPayload;
+    return { token, lookup: () => findActiveSession(jti as string, exp) };
+  };
+
+  it("serves lookups from the cache until the TTL passes", async () => {
+    const { lookup } = await sessionOf("cached@example.com");
+    assert.equal(await countSessionLookups(lookup), 1);
+
+    clock.advance(CACHE_TTL_MS - 1000);
+    assert.equal(await countSessionLookups(lookup), 0);
+
+    clock.advance(2000);
+    assert.equal(await countSessionLookups(lookup), 1);
+  });
+
+  it("drops a revoked session from the cache", async () => {
+    const { token, lookup } = await sessionOf("revoked-cached@example.com");
+    assert.ok(await lookup());
+
+    const current = await server.request("GET", "/auth/sessions/current", { token });
+    const revoked = await server.request("DELETE", `/auth/sessions/${current.body.session.id}`, { token });
+    assert.equal(revoked.status, 200);
+    assert.equal(await lookup(), null);
+    assert.equal((await server.request("GET", "/auth/me", { token })).status, 401);
+  });
+
+  it("keeps the cached record's last activity current", async () => {
+    const { token, lookup } = await sessionOf("active-cached@example.com");
+    const loggedInAt = (await lookup())!.lastActiveAt.getTime();
+
+    clock.advance(61_000);
+    assert.equal((await server.request("GET", "/auth/me", { token })).status, 200);
+
+    assert.equal(await countSessionLookups(lookup), 0);
+    assert.equal((await lookup())!.lastActiveAt.getTime(), loggedInAt + 61_000);
+  });
+});
@@ -0,0 +1,73 @@
+import assert from "node:assert/strict";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, beforeEach, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const LIFETIME_SECONDS = 3600;
+const WINDOW_SECONDS = 300;
+
+describe("POST /auth/session/extend", { skip: needsMongo }, () => {
+  let server: TestServer;
+  // Whole seconds, so token expiries line up exactly with the clock.
+  const clock = new ManualClock(Math.floor(Date.now() / 1000) * 1000);
+  let token: string;
+  let issuedAt: number;
+  let users = 0;
+
+  const extend = () => server.request("POST", "/auth/session/extend", { token });
+  const at = (secondsAfterIssue: number) => clock.set((issuedAt + secondsAfterIssue) * 1000);
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({
+      JWT_EXPIRES_IN: "1h",
+      SESSION_EXTEND_WINDOW_SECONDS: String(WINDOW_SECONDS),
+    });
+  });
+
+  beforeEach(async () => {
+    ({ token } = await registerUser(server, `extend-${(users += 1)}@example.com`));
+    issuedAt = (jwt.decode(token) as JwtPayload).iat as number;
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  it("refuses one second before the window opens", async () => {
+    at(LIFETIME_SECONDS - WINDOW_SECONDS - 1);
+    const response = await extend();
+    assert.equal(response.status, 400);
+    assert.equal(response.body.error.code, "session_extend_too_early");
+    assert.equal(response.body.error.details.secondsRemaining, WINDOW_SECONDS + 1);
+  });
+
+  it("extends at the start of the window, keeping the session", async () => {
+    at(LIFETIME_SECONDS - WINDOW_SECONDS);
+    const response = await extend();
+    assert.equal(response.status, 200);
+    const renewed = jwt.decode(response.body.token) as JwtPayload;
+    const original = jwt.decode(token) as JwtPayload;
+    assert.equal(renewed.jti, original.jti);
+    assert.equal(renewed.exp, (original.exp as number) + LIFETIME_SECONDS - WINDOW_SECONDS);
+
+    // The old token lapses at its own expiry; the new one outlives it.
+    at(LIFETIME_SECONDS + 1);
+    assert.equal((await server.request("GET", "/auth/me", { token })).status, 401);
+    assert.equal((await server.request("GET", "/auth/me", { token: response.body.token })).status, 200);
+  });
+
+  it("extends in the last second", async () => {
+    at(LIFETIME_SECONDS - 1);
+    assert.equal((await extend()).status, 200);
+  });
+
+  it("rejects an expired token", async () => {
+    at(LIFETIME_SECONDS + 1);
+    const response = await extend();
+    assert.equal(response.status, 401);
+    assert.equal(response.body.error.code, "invalid_token");
+  });
+});
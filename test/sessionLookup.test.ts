@@ -0,0 +1,39 @@
+import assert from "node:assert/strict";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { countSessionLookups, needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const PARALLEL_LOOKUPS = 20;
+
+describe("session lookups", { skip: needsMongo }, () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp();
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("shares one database lookup between concurrent checks of the same token", async () => {
+    const { findActiveSession } = await import("../src/sessions");
+    const { token } = await registerUser(server, "parallel@example.com");
+    const { jti, exp } = jwt.decode(token) as JwtPayload;
+
+    let sessions: unknown[] = [];
+    const lookups = await countSessionLookups(async () => {
+      sessions = await Promise.all(
+        Array.from({ length: PARALLEL_LOOKUPS }, () => findActiveSession(jti as string, exp)),
+      );
+    });
+    assert.equal(lookups, 1);
+    assert.equal(sessions.length, PARALLEL_LOOKUPS);
+    for (const session of sessions) {
+      assert.equal((session as { jti: string }).jti, jti);
+    }
+
+    // Once settled, the next check goes to the database again.
+    assert.equal(await countSessionLookups(() => findActiveSession(jti as string, exp)), 1);
+  });
+});
@@ -0,0 +1,94 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { parseUserAgent } from "../src/utils/userAgent";
+import { needsMongo, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+const FIREFOX_LINUX = "Mozilla/5.0 (X11; Linux x86_64; rv:126.0) Gecko/20100101 Firefox/126.0";
+const CHROME_WINDOWS =
+  "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0.0.0 Safari/537.36";
+
+type SessionView = { id: string; current: boolean; lastActiveAt: string; device: { description: string } };
+
+describe("user agent parsing", () => {
+  it("describes browser and OS", () => {
+    assert.equal(parseUserAgent(FIREFOX_LINUX).description, "Firefox on Linux");
+    assert.equal(parseUserAgent(CHROME_WINDOWS).description, "Chrome on Windows");
+    assert.equal(parseUserAgent(undefined).description, "Unknown device");
+  });
+});
+
+describe("session list and revocation", { skip: needsMongo }, () => {
+  let server: TestServer;
+  const clock = new ManualClock();
+  let firefoxToken: string;
+  let chromeToken: string;
+
+  const loginWith = async (userAgent: string) => {
+    const response = await server.request("POST", "/auth/login", {
+      body: { email: "devices@example.com", password: TEST_PASSWORD },
+      headers: { "User-Agent": userAgent },
+    });
+    assert.equal(response.status, 200);
+    return response.body.token as string;
+  };
+
+  const listSessions = async (token: string) => {
+    const response = await server.request("GET", "/auth/sessions", { token });
+    assert.equal(response.status, 200);
+    return response.body.sessions as SessionView[];
+  };
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp();
+    await registerUser(server, "devices@example.com");
+    firefoxToken = await loginWith(FIREFOX_LINUX);
+    chromeToken = await loginWith(CHROME_WINDOWS);
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  it("lists each login's device and marks the caller's session", async () => {
+    const sessions = await listSessions(firefoxToken);
+    const current = sessions.filter((session) => session.current);
+    assert.equal(current.length, 1);
+    assert.equal(current[0].device.description, "Firefox on Linux");
+    assert.ok(sessions.some((session) => !session.current && session.device.description === "Chrome on Windows"));
+  });
+
+  it("updates last activity at most once a minute", async () => {
+    const lastActive = async () =>
+      (await listSessions(firefoxToken)).find((session) => session.current)?.lastActiveAt;
+    const initial = await lastActive();
+
+    clock.advance(30_000);
+    await server.request("GET", "/auth/me", { token: firefoxToken });
+    assert.equal(await lastActive(), initial);
+
+    clock.advance(60_000);
+    await server.request("GET", "/auth/me", { token: firefoxToken });
+    // The update is written in the background.
+    let updated = await lastActive();
+    for (let attempt = 0; updated === initial && attempt < 20; attempt += 1) {
+      await new Promise((resolve) => setTimeout(resolve, 50));
+      updated = await lastActive();
+    }
+    assert.notEqual(updated, initial);
+  });
+
+  it("revokes one session without touching the other", async () => {
+    const chromeSession = (await listSessions(firefoxToken)).find((session) => !session.current);
+    assert.ok(chromeSession);
+    const revoked = await server.request("DELETE", `/auth/sessions/${chromeSession.id}`, { token: firefoxToken });
+    assert.equal(revoked.status, 200);
+
+    const rejected = await server.request("GET", "/auth/me", { token: chromeToken });
+    assert.equal(rejected.status, 401);
+    assert.equal(rejected.body.error.code, "session_revoked");
+    assert.equal((await server.request("GET", "/auth/me", { token: firefoxToken })).status, 200);
+  });
+});
@@ -0,0 +1,70 @@
+import assert from "node:assert/strict";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const LIFETIME_SECONDS = 3600;
+const WINDOW_SECONDS = 300;
+const MAX_LIFETIME_SECONDS = 2 * LIFETIME_SECONDS;
+
+describe("sliding sessions", { skip: needsMongo }, () => {
+  let server: TestServer;
+  // Whole seconds, so token expiries line up exactly with the clock.
+  const clock = new ManualClock(Math.floor(Date.now() / 1000) * 1000);
+
+  before(async () => {
+    setClock(clock);
+    server = await startApp({
+      JWT_EXPIRES_IN: "1h",
+      SESSION_SLIDING: "true",
+      SESSION_EXTEND_WINDOW_SECONDS: String(WINDOW_SECONDS),
+      SESSION_MAX_LIFETIME_SECONDS: String(MAX_LIFETIME_SECONDS),
+    });
+  });
+
+  after(async () => {
+    setClock(systemClock);
+    await server.close();
+  });
+
+  it("renews inside the window, up to the maximum lifetime", async () => {
+    const { token } = await registerUser(server, "sliding@example.com");
+    const original = jwt.decode(token) as JwtPayload;
+    const loginAt = original.iat as number;
+    const at = (secondsAfterLogin: number) => clock.set((loginAt + secondsAfterLogin) * 1000);
+    const me = (current: string) => server.request("GET", "/auth/me", { token: current });
+
+    at(100);
+    const early = await me(token);
+    assert.equal(early.status, 200);
+    assert.equal(early.headers.get("x-refreshed-token"), null);
+
+    at(LIFETIME_SECONDS - WINDOW_SECONDS);
+    const first = (await me(token)).headers.get("x-refreshed-token");
+    assert.ok(first);
+    const renewed = jwt.decode(first) as JwtPayload;
+    assert.equal(renewed.jti, original.jti);
+    assert.equal(renewed.auth_time, loginAt);
+    assert.equal(renewed.exp, loginAt + 2 * LIFETIME_SECONDS - WINDOW_SECONDS);
+    // The presented token keeps working until its own expiry.
+    assert.equal((await me(token)).status, 200);
+
+    // The next renewal would overshoot, so it stops at the maximum lifetime.
+    at(renewed.exp! - WINDOW_SECONDS);
+    const second = (await me(first)).headers.get("x-refreshed-token");
+    assert.ok(second);
+    assert.equal((jwt.decode(second) as JwtPayload).exp, loginAt + MAX_LIFETIME_SECONDS);
+
+    at(MAX_LIFETIME_SECONDS - 10);
+    const capped = await me(second);
+    assert.equal(capped.status, 200);
+    assert.equal(capped.headers.get("x-refreshed-token"), null);
+    const extend = await server.request("POST", "/auth/session/extend", { token: second });
+    assert.equal(extend.status, 400);
+    assert.equal(extend.body.error.code, "session_lifetime_exceeded");
+
+    at(MAX_LIFETIME_SECONDS + 1);
+    assert.equal((await me(second)).status, 401);
+  });
+});
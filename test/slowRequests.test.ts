@@ -0,0 +1,67 @@
+import assert from "node:assert/strict";
+import { setTimeout as delay } from "timers/promises";
+import { after, before, describe, it, mock } from "node:test";
+import { TestServer, useTestEnv } from "./helpers";
+
+const SLOW_REQUEST_MS = 100;
+
+describe("SLOW_REQUEST_MS", () => {
+  let server: TestServer;
+  const warn = mock.method(console, "warn", () => {});
+  const slowWarnings = () => warn.mock.calls.filter((call) => call.arguments[0] === "[slow-request]");
+
+  before(async () => {
+    useTestEnv({ SLOW_REQUEST_MS: String(SLOW_REQUEST_MS) });
+    const { default: express } = await import("express");
+    const { requestId } = await import("../src/middleware/requestId");
+    const { responseTime } = await import("../src/middleware/responseTime");
+    const app = express()
+      .use(responseTime)
+      .use(requestId)
+      .get("/fast", (_req, res) => {
+        res.json({ ok: true });
+      })
+      .get("/slow", async (_req, res) => {
+        await delay(SLOW_REQUEST_MS * 2);
+        res.json({ ok: true });
+      })
+      .get("/stream", async (_req, res) => {
+        res.type("text/event-stream");
+        await delay(SLOW_REQUEST_MS * 2);
+        res.end();
+      });
+    server = await TestServer.start(app);
+  });
+
+  after(async () => {
+    mock.restoreAll();
+    await server.close();
+  });
+
+  it("does not warn below the threshold", async () => {
+    assert.equal((await server.request("GET", "/fast")).status, 200);
+    await delay(20);
+    assert.equal(slowWarnings().length, 0);
+  });
+
+  it("warns above the threshold", async () => {
+    await server.request("GET", "/slow?q=secret", { headers: { "X-Request-Id": "slow-1" } });
+    for (let attempt = 0; slowWarnings().length === 0 && attempt < 50; attempt++) {
+      await delay(10);
+    }
+    assert.equal(slowWarnings().length, 1);
+    const [, entry] = slowWarnings()[0].arguments as [string, Record<string, unknown>];
+    assert.equal(entry.method, "GET");
+    assert.equal(entry.path, "/slow");
+    assert.equal(entry.status, 200);
+    assert.equal(entry.requestId, "slow-1");
+    assert.ok((entry.durationMs as number) >= SLOW_REQUEST_MS);
+  });
+
+  it("never counts event streams as slow", async () => {
+    const earlier = slowWarnings().length;
+    await server.request("GET", "/stream");
+    await delay(20);
+    assert.equal(slowWarnings().length, earlier);
+  });
+});
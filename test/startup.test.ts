@@ -0,0 +1,121 @@
+import assert from "node:assert/strict";
+import { spawn, type ChildProcess } from "child_process";
+import { once } from "events";
+import net, { type AddressInfo, type Server, type Socket } from "net";
+import path from "path";
+import { after, before, describe, it } from "node:test";
+import { dropTestDatabase, needsMongo, TEST_MONGODB_URI, useTestEnv } from "./helpers";
+
+// The database comes up this long after the service starts.
+const MONGO_DELAY_MS = 5_000;
+const SERVER_SCRIPT = path.join(__dirname, "../src/server.js");
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+async function freePort() {
+  const server = net.createServer().listen(0, "127.0.0.1");
+  await once(server, "listening");
+  const { port } = server.address() as AddressInfo;
+  await new Promise((resolve) => server.close(resolve));
+  return port;
+}
+
+// Stands in for a database that is not up yet: connections are dropped until
+// up() is called, then forwarded to the test database.
+class DelayedMongoProxy {
+  private readonly server: Server;
+  private readonly sockets = new Set<Socket>();
+  private isUp = false;
+
+  constructor(target: URL) {
+    this.server = net.createServer((socket) => {
+      this.sockets.add(socket);
+      socket.on("close", () => this.sockets.delete(socket));
+      if (!this.isUp) {
+        socket.destroy();
+        return;
+      }
+      const upstream = net.connect(Number(target.port || 27017), target.hostname);
+      this.sockets.add(upstream);
+      upstream.on("close", () => this.sockets.delete(upstream));
+      socket.pipe(upstream).pipe(socket);
+      socket.on("error", () => upstream.destroy());
+      upstream.on("error", () => socket.destroy());
+    });
+  }
+
+  async listen() {
+    this.server.listen(0, "127.0.0.1");
+    await once(this.server, "listening");
+    return `mongodb://127.0.0.1:${(this.server.address() as AddressInfo).port}`;
+  }
+
+  up() {
+    this.isUp = true;
+  }
+
+  async close() {
+    for (const socket of this.sockets) {
+      socket.destroy();
+    }
+    await new Promise((resolve) => this.server.close(resolve));
+  }
+}
+
+async function readiness(port: number) {
+  try {
+    return (await fetch(`http://127.0.0.1:${port}/healthz/ready`)).status;
+  } catch {
+    return "refused";
+  }
+}
+
+async function startServer(mode: "block" | "lazy", mongoUri: string) {
+  const port = await freePort();
+  const child = spawn(process.execPath, [SERVER_SCRIPT], {
+    env: { ...process.env, MONGODB_URI: mongoUri, PORT: String(port), STARTUP_MODE: mode },
+    stdio: "ignore",
+  });
+  return { child, port };
+}
+
+async function stopServer(child: ChildProcess) {
+  if (child.exitCode === null) {
+    child.kill("SIGTERM");
+    await once(child, "exit");
+  }
+}
+
+describe("startup modes with a late database", { skip: needsMongo }, () => {
+  before(() => {
+    useTestEnv();
+  });
+
+  after(async () => {
+    await dropTestDatabase();
+  });
+
+  for (const mode of ["block", "lazy"] as const) {
+    it(`STARTUP_MODE=${mode}`, async () => {
+      const proxy = new DelayedMongoProxy(new URL(TEST_MONGODB_URI as string));
+      const { child, port } = await startServer(mode, await proxy.listen());
+      try {
+        await sleep(MONGO_DELAY_MS / 2);
+        // block: no listener yet; lazy: listening but not ready.
+        assert.equal(await readiness(port), mode === "block" ? "refused" : 503);
+        await sleep(MONGO_DELAY_MS / 2);
+        proxy.up();
+
+        let status = await readiness(port);
+        for (let waited = 0; status !== 200 && waited < 30_000; waited += 250) {
+          await sleep(250);
+          status = await readiness(port);
+        }
+        assert.equal(status, 200);
+      } finally {
+        await stopServer(child);
+        await proxy.close();
+      }
+    });
+  }
+});
@@ -0,0 +1,64 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { ManualClock, setClock, systemClock } from "../src/utils/clock";
+import { useTestEnv } from "./helpers";
+
+const NOW_SECONDS = 1_800_000_000;
+const LIFETIME_SECONDS = 3600;
+
+// buildClaims and parseAuthPayload are each other's inverse, so every field
+// put into a token comes back out of verifyToken unchanged.
+describe("token claims round trip", () => {
+  let jwtModule: typeof import("../src/utils/jwt");
+
+  before(async () => {
+    useTestEnv({ JWT_EXPIRES_IN: "1h" });
+    setClock(new ManualClock(NOW_SECONDS * 1000));
+    jwtModule = await import("../src/utils/jwt");
+  });
+
+  after(() => {
+    setClock(systemClock);
+  });
+
+  it("returns every field of a full payload", () => {
+    const payload = {
+      sub: "user-1",
+      email: "roundtrip@example.com",
+      roles: ["user", "admin"],
+      tenantId: "acme",
+      jti: "session-1",
+      scope: ["profile:read", "sessions:write"],
+      audience: "billing",
+      actor: "admin-7",
+      boundIp: "203.0.113.9",
+      authTime: NOW_SECONDS - 600,
+      extra: { plan: "pro", features: ["a", "b"], limits: { seats: 5 } },
+    };
+    const verified = jwtModule.verifyToken(jwtModule.createToken(payload));
+    assert.deepEqual(verified, { ...payload, exp: NOW_SECONDS + LIFETIME_SECONDS });
+  });
+
+  it("leaves unset optional fields unset", () => {
+    const payload = { sub: "user-2", email: "minimal@example.com", roles: [], tenantId: "default" };
+    const verified = jwtModule.verifyToken(jwtModule.createToken(payload));
+    assert.deepEqual(verified, {
+      ...payload,
+      jti: undefined,
+      exp: NOW_SECONDS + LIFETIME_SECONDS,
+      scope: undefined,
+      audience: undefined,
+      actor: undefined,
+      boundIp: undefined,
+      // auth_time defaults to the issue time.
+      authTime: NOW_SECONDS,
+      extra: {},
+    });
+  });
+
+  it("passes extra claims given at signing time through", () => {
+    const payload = { sub: "user-3", email: "extra@example.com", roles: ["user"], tenantId: "default" };
+    const verified = jwtModule.verifyToken(jwtModule.createToken(payload, { locale: "es" }));
+    assert.deepEqual(verified.extra, { locale: "es" });
+  });
+});
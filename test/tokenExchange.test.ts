@@ -0,0 +1,73 @@
+import assert from "node:assert/strict";
+import jwt, { type JwtPayload } from "jsonwebtoken";
+import { after, before, describe, it } from "node:test";
+import { grantedScopes, intersectScopes, parseScope } from "../src/utils/scope";
+import { needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+describe("scope intersection", () => {
+  it("allows subsets of the granted scopes", () => {
+    assert.deepEqual(intersectScopes(["user", "admin"], ["user"]), { allowed: ["user"], denied: [] });
+  });
+
+  it("reports escalation attempts instead of dropping them", () => {
+    assert.deepEqual(intersectScopes(["user"], ["user", "admin"]), { allowed: ["user"], denied: ["admin"] });
+  });
+
+  it("narrows from an explicit scope claim rather than the roles", () => {
+    const granted = grantedScopes({ scope: ["read"], roles: ["user", "admin"] });
+    assert.deepEqual(intersectScopes(granted, ["admin"]).denied, ["admin"]);
+  });
+
+  it("parses space-separated strings and arrays, dropping duplicates", () => {
+    assert.deepEqual(parseScope("a  b a"), ["a", "b"]);
+    assert.deepEqual(parseScope(["a", "a", "b"]), ["a", "b"]);
+    assert.equal(parseScope(42), undefined);
+  });
+});
+
+describe("POST /auth/token/exchange", { skip: needsMongo }, () => {
+  let server: TestServer;
+  let token: string;
+
+  before(async () => {
+    server = await startApp();
+    ({ token } = await registerUser(server, "exchange@example.com"));
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("issues an audience-bound token for a subset of the scopes", async () => {
+    const response = await server.request("POST", "/auth/token/exchange", {
+      token,
+      body: { audience: "billing", scope: "user" },
+    });
+    assert.equal(response.status, 200);
+    assert.equal(response.body.scope, "user");
+    const claims = jwt.decode(response.body.access_token) as JwtPayload;
+    assert.equal(claims.aud, "billing");
+    assert.equal(claims.scope, "user");
+  });
+
+  it("rejects scopes the token does not have", async () => {
+    const response = await server.request("POST", "/auth/token/exchange", {
+      token,
+      body: { audience: "billing", scope: ["user", "admin"] },
+    });
+    assert.equal(response.status, 403);
+    assert.equal(response.body.error.code, "invalid_scope");
+    assert.deepEqual(response.body.error.details.denied, ["admin"]);
+  });
+
+  it("does not accept exchanged tokens on this API, including for another exchange", async () => {
+    const exchanged = await server.request("POST", "/auth/token/exchange", { token, body: { audience: "billing" } });
+    const downstreamToken = exchanged.body.access_token as string;
+    assert.equal((await server.request("GET", "/auth/me", { token: downstreamToken })).status, 401);
+    const again = await server.request("POST", "/auth/token/exchange", {
+      token: downstreamToken,
+      body: { audience: "other" },
+    });
+    assert.equal(again.status, 401);
+  });
+});
@@ -0,0 +1,46 @@
+import assert from "node:assert/strict";
+import jwt from "jsonwebtoken";
+import { after, before, describe, it, mock } from "node:test";
+import { login, needsMongo, registerUser, startApp, type TestServer } from "./helpers";
+
+const PAYLOAD = { sub: "user-1", email: "a@example.com", roles: ["user"], tenantId: "default" };
+
+describe("token signing failures", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp();
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  const issueFailed = { name: "ApiError", status: 500, code: "token_issue_failed" };
+
+  it("turns an unserializable claim into token_issue_failed", async () => {
+    const { createToken } = await import("../src/utils/jwt");
+    assert.throws(() => createToken(PAYLOAD, { big: 1n }), issueFailed);
+  });
+
+  it("turns an unusable lifetime into token_issue_failed", async () => {
+    const { createToken } = await import("../src/utils/jwt");
+    assert.throws(() => createToken(PAYLOAD, {}, { expiresIn: "soon" as never }), issueFailed);
+  });
+
+  it("answers login with a 500 and keeps serving", { skip: needsMongo }, async () => {
+    await registerUser(server, "signing@example.com");
+    const sign = mock.method(jwt, "sign", () => {
+      throw new Error("forced signing failure");
+    });
+    try {
+      const response = await login(server, "signing@example.com");
+      assert.equal(response.status, 500);
+      assert.equal(response.body.error.code, "token_issue_failed");
+      assert.equal(sign.mock.callCount(), 1);
+    } finally {
+      sign.mock.restore();
+    }
+    assert.equal((await login(server, "signing@example.com")).status, 200);
+  });
+});
@@ -0,0 +1,85 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { cidrContains, parseCidr, parseCidrList } from "../src/utils/ip";
+import { startApp, type TestServer } from "./helpers";
+
+const CHECK_LIMIT = 2;
+
+describe("CIDR matching", () => {
+  it("matches IPv4 prefixes at their bit boundary", () => {
+    const cidr = parseCidr("10.1.0.0/15")!;
+    assert.ok(cidrContains(cidr, "10.0.0.1"));
+    assert.ok(cidrContains(cidr, "10.1.255.255"));
+    assert.ok(!cidrContains(cidr, "10.2.0.0"));
+    assert.ok(!cidrContains(cidr, "11.0.0.1"));
+  });
+
+  it("treats an IPv4-mapped IPv6 peer as IPv4", () => {
+    const cidr = parseCidr("127.0.0.1")!;
+    assert.equal(cidr.prefixLength, 32);
+    assert.ok(cidrContains(cidr, "::ffff:127.0.0.1"));
+    assert.ok(!cidrContains(cidr, "::1"));
+  });
+
+  it("matches IPv6 prefixes and ignores zone ids", () => {
+    const cidr = parseCidr("fd00:abcd::/32")!;
+    assert.ok(cidrContains(cidr, "fd00:abcd:1::5"));
+    assert.ok(cidrContains(cidr, "fd00:abcd::1%eth0"));
+    assert.ok(!cidrContains(cidr, "fd00:abce::1"));
+    assert.ok(!cidrContains(cidr, "10.0.0.1"));
+  });
+
+  it("rejects malformed entries and skips them in lists", () => {
+    for (const value of ["10.0.0.0/33", "10.0.0.0/-1", "10.0.0.0/8/8", "10.0.0", "fd00::/129", "host.example"]) {
+      assert.equal(parseCidr(value), null, value);
+    }
+    const cidrs = parseCidrList(" 10.0.0.0/8, nonsense ,::1 ,", "test");
+    assert.deepEqual(
+      cidrs.map((cidr) => cidr.prefixLength),
+      [8, 128],
+    );
+  });
+
+  it("never matches an unparseable address", () => {
+    assert.ok(!cidrContains(parseCidr("0.0.0.0/0")!, "not-an-ip"));
+  });
+});
+
+// The limiter is keyed by req.ip, so whether a spoofed header escapes it shows
+// which address the app settled on.
+describe("X-Forwarded-For from a trusted proxy", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({
+      TRUSTED_PROXIES: "127.0.0.1/32,::1/128",
+      EMAIL_CHECK_RATE_LIMIT_MAX: String(CHECK_LIMIT),
+    });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  function check(forwardedFor: string) {
+    return server.request("GET", "/auth/register/check-email?email=invalid", {
+      headers: { "X-Forwarded-For": forwardedFor },
+    });
+  }
+
+  it("uses the forwarded client address", async () => {
+    for (let i = 1; i <= CHECK_LIMIT * 3; i += 1) {
+      assert.equal((await check(`203.0.113.${i}`)).status, 400);
+    }
+  });
+
+  it("stops at the first untrusted hop", async () => {
+    // Only 198.51.100.9 was added by the trusted proxy; what the client put
+    // before it is not believed.
+    const responses = [];
+    for (let i = 1; i <= CHECK_LIMIT + 1; i += 1) {
+      responses.push((await check(`192.0.2.${i}, 198.51.100.9`)).status);
+    }
+    assert.deepEqual(responses, [400, 400, 429]);
+  });
+});
@@ -0,0 +1,32 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { startApp, type TestServer } from "./helpers";
+
+const CHECK_LIMIT = 2;
+
+describe("X-Forwarded-For from an untrusted peer", () => {
+  let server: TestServer;
+
+  before(async () => {
+    // The test client connects from loopback, which is not in the list.
+    server = await startApp({
+      TRUSTED_PROXIES: "10.0.0.0/8",
+      EMAIL_CHECK_RATE_LIMIT_MAX: String(CHECK_LIMIT),
+    });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  it("ignores the header and limits by the socket address", async () => {
+    const responses = [];
+    for (let i = 1; i <= CHECK_LIMIT + 1; i += 1) {
+      const response = await server.request("GET", "/auth/register/check-email?email=invalid", {
+        headers: { "X-Forwarded-For": `203.0.113.${i}` },
+      });
+      responses.push(response.status);
+    }
+    assert.deepEqual(responses, [400, 400, 429]);
+  });
+});
@@ -0,0 +1,37 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { startApp, type TestServer } from "./helpers";
+
+const MAX_URL_LEN = 256;
+
+describe("MAX_URL_LEN", () => {
+  let server: TestServer;
+
+  before(async () => {
+    server = await startApp({ MAX_URL_LEN: String(MAX_URL_LEN) });
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  const pathOfLength = (length: number) => `/${"a".repeat(length - 1)}`;
+
+  it("refuses an over-long path with 414", async () => {
+    const response = await server.request("GET", pathOfLength(MAX_URL_LEN + 1));
+    assert.equal(response.status, 414);
+    assert.equal(response.body.error.code, "uri_too_long");
+    assert.equal(response.body.error.details.maxLength, MAX_URL_LEN);
+    assert.ok(response.body.error.request_id);
+  });
+
+  it("counts the query string", async () => {
+    const response = await server.request("GET", `/health?q=${"a".repeat(MAX_URL_LEN)}`);
+    assert.equal(response.status, 414);
+  });
+
+  it("routes a path at the limit as usual", async () => {
+    const response = await server.request("GET", pathOfLength(MAX_URL_LEN));
+    assert.equal(response.status, 404);
+  });
+});
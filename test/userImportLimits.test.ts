@@ -0,0 +1,89 @@
+import assert from "node:assert/strict";
+import { after, before, describe, it } from "node:test";
+import { needsMongo, registerAdmin, registerUser, startApp, TEST_PASSWORD, type TestServer } from "./helpers";
+
+const ROWS_MAX = 4;
+const ROW_MAX_BYTES = 256;
+const BODY_MAX_BYTES = ROWS_MAX * ROW_MAX_BYTES;
+
+const row = (email: string) => JSON.stringify({ email, password: TEST_PASSWORD });
+
+describe("user import size limits", { skip: needsMongo }, () => {
+  let server: TestServer;
+  let adminToken: string;
+
+  before(async () => {
+    server = await startApp({
+      ADMIN_IMPORT_USERS_MAX: String(ROWS_MAX),
+      ADMIN_IMPORT_ROW_MAX_BYTES: String(ROW_MAX_BYTES),
+    });
+    adminToken = (await registerAdmin(server, "importer@example.com")).token;
+  });
+
+  after(async () => {
+    await server.close();
+  });
+
+  function importNdjson(rawBody: string, token = adminToken) {
+    return server.request("POST", "/admin/users/import", {
+      token,
+      rawBody,
+      headers: { "Content-Type": "application/x-ndjson" },
+    });
+  }
+
+  it("imports NDJSON within the limits", async () => {
+    const response = await importNdjson([row("one@example.com"), row("two@example.com")].join("\n"));
+    assert.equal(response.status, 200);
+    assert.equal(response.body.summary.created, 2);
+  });
+
+  it("stops at the row cap and reports truncation", async () => {
+    const rows = Array.from({ length: ROWS_MAX + 1 }, (_, i) => row(`cap${i}@example.com`));
+    const response = await importNdjson(rows.join("\n"));
+    assert.equal(response.status, 200);
+    assert.equal(response.body.summary.created, ROWS_MAX);
+    assert.equal(response.body.summary.truncated, true);
+  });
+
+  it("refuses an overlong row", async () => {
+    const long = JSON.stringify({ email: "long@example.com", password: TEST_PASSWORD, pad: "x".repeat(ROW_MAX_BYTES) });
+    const response = await importNdjson([row("before@example.com"), long].join("\n"));
+    assert.equal(response.status, 413);
+    assert.equal(response.body.error.code, "row_too_large");
+  });
+
+  it("refuses an unterminated row once it passes the row limit", async () => {
+    const response = await importNdjson(`{"email":"${"e".repeat(ROW_MAX_BYTES)}`);
+    assert.equal(response.body.error.code, "row_too_large");
+  });
+
+  it("refuses an upload past the total limit, even of blank lines", async () => {
+    const response = await importNdjson("\n".repeat(BODY_MAX_BYTES) + row("late@example.com"));
+    assert.equal(response.status, 413);
+    assert.equal(response.body.error.code, "payload_too_large");
+  });
+
+  it("refuses JSON array uploads", async () => {
+    const entries = [{ email: "array@example.com", password: TEST_PASSWORD }];
+    const response = await server.request("POST", "/admin/users/import", { token: adminToken, body: entries });
+    assert.equal(response.status, 415);
+    assert.equal(response.body.error.code, "unsupported_media_type");
+  });
+
+  it("checks the caller before reading the body", async () => {
+    const { token } = await registerUser(server, "not-admin@example.com");
+    const body = "x".repeat(200 * 1024);
+    const anonymous = await server.request("POST", "/admin/users/import", {
+      rawBody: body,
+      headers: { "Content-Type": "application/json" },
+    });
+    assert.equal(anonymous.status, 401);
+    const forbidden = await server.request("POST", "/admin/users/import", {
+      token,
+      rawBody: body,
+      headers: { "Content-Type": "application/json" },
+    });
+    assert.equal(forbidden.status, 403);
+  });
+});